@@ -1,5 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+use super::sea_orm_active_enums::BetType;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -11,6 +12,11 @@ pub struct Model {
     pub user_id: String,
     pub candidate_id: String,
     pub amount: i32,
+    pub created_at: DateTimeUtc,
+    pub odds: Option<i32>,
+    pub bet_type: BetType,
+    pub following_candidate_ids: String,
+    pub guess: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -10,6 +10,10 @@ pub struct Model {
     pub name: String,
     pub match_id: String,
     pub is_winner: Option<i8>,
+    pub odds: Option<i32>,
+    pub placing: Option<i32>,
+    pub handicap: Option<i32>,
+    pub score: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -24,6 +28,10 @@ pub enum Relation {
         on_delete = "Restrict"
     )]
     Match,
+    #[sea_orm(has_many = "super::parlay_leg::Entity")]
+    ParlayLeg,
+    #[sea_orm(has_many = "super::position::Entity")]
+    Position,
 }
 
 impl Related<super::bet::Entity> for Entity {
@@ -38,4 +46,16 @@ impl Related<super::r#match::Entity> for Entity {
     }
 }
 
+impl Related<super::parlay_leg::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ParlayLeg.def()
+    }
+}
+
+impl Related<super::position::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Position.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
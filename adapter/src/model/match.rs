@@ -1,5 +1,6 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+use super::sea_orm_active_enums::{MarketType, MatchStatus};
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -7,12 +8,23 @@ use sea_orm::entity::prelude::*;
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: String,
+    pub number: i32,
     pub title: String,
     pub channel_id: String,
     pub message_id: Option<String>,
     pub created_at: DateTimeUtc,
+    pub close_at: Option<DateTimeUtc>,
     pub closed_at: Option<DateTimeUtc>,
     pub winner_candidate_id: Option<String>,
+    pub cancelled_at: Option<DateTimeUtc>,
+    pub voided_at: Option<DateTimeUtc>,
+    pub status: MatchStatus,
+    pub market_type: MarketType,
+    pub over_under_line: Option<i32>,
+    pub result_value: Option<i32>,
+    pub liquidity: Option<i32>,
+    pub min_bettors: Option<i32>,
+    pub min_candidates: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -27,6 +39,12 @@ pub enum Relation {
         on_delete = "Restrict"
     )]
     Candidate,
+    #[sea_orm(has_many = "super::parlay_leg::Entity")]
+    ParlayLeg,
+    #[sea_orm(has_many = "super::position::Entity")]
+    Position,
+    #[sea_orm(has_one = "super::settlement::Entity")]
+    Settlement,
 }
 
 impl Related<super::bet::Entity> for Entity {
@@ -41,4 +59,22 @@ impl Related<super::candidate::Entity> for Entity {
     }
 }
 
+impl Related<super::parlay_leg::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ParlayLeg.def()
+    }
+}
+
+impl Related<super::position::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Position.def()
+    }
+}
+
+impl Related<super::settlement::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Settlement.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -4,5 +4,13 @@ pub mod prelude;
 
 pub mod bet;
 pub mod candidate;
+pub mod challenge;
+pub mod channel;
+pub mod job;
 pub mod r#match;
+pub mod parlay;
+pub mod parlay_leg;
+pub mod position;
+pub mod sea_orm_active_enums;
+pub mod settlement;
 pub mod user;
@@ -2,5 +2,12 @@
 
 pub use super::bet::Entity as Bet;
 pub use super::candidate::Entity as Candidate;
+pub use super::challenge::Entity as Challenge;
+pub use super::channel::Entity as Channel;
+pub use super::job::Entity as Job;
+pub use super::parlay::Entity as Parlay;
+pub use super::parlay_leg::Entity as ParlayLeg;
+pub use super::position::Entity as Position;
 pub use super::r#match::Entity as Match;
+pub use super::settlement::Entity as Settlement;
 pub use super::user::Entity as User;
@@ -14,3 +14,129 @@ pub enum Status {
     #[sea_orm(string_value = "Cancelled")]
     Cancelled,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum MatchStatus {
+    #[sea_orm(string_value = "Open")]
+    Open,
+    #[sea_orm(string_value = "Closed")]
+    Closed,
+    #[sea_orm(string_value = "Settled")]
+    Settled,
+    #[sea_orm(string_value = "Cancelled")]
+    Cancelled,
+    #[sea_orm(string_value = "Void")]
+    Void,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(32))")]
+pub enum RoundingPolicy {
+    #[sea_orm(string_value = "LargestRemainder")]
+    LargestRemainder,
+    #[sea_orm(string_value = "House")]
+    House,
+    #[sea_orm(string_value = "EarliestBettor")]
+    EarliestBettor,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum NoWinnerPolicy {
+    #[sea_orm(string_value = "Refund")]
+    Refund,
+    #[sea_orm(string_value = "Jackpot")]
+    Jackpot,
+    #[sea_orm(string_value = "House")]
+    House,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum MarketType {
+    #[sea_orm(string_value = "Parimutuel")]
+    Parimutuel,
+    #[sea_orm(string_value = "FixedOdds")]
+    FixedOdds,
+    #[sea_orm(string_value = "ClosestGuess")]
+    ClosestGuess,
+    #[sea_orm(string_value = "Lmsr")]
+    Lmsr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum BetType {
+    #[sea_orm(string_value = "Win")]
+    Win,
+    #[sea_orm(string_value = "Exacta")]
+    Exacta,
+    #[sea_orm(string_value = "Trifecta")]
+    Trifecta,
+    #[sea_orm(string_value = "Guess")]
+    Guess,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum ChallengeStatus {
+    #[sea_orm(string_value = "Pending")]
+    Pending,
+    #[sea_orm(string_value = "Accepted")]
+    Accepted,
+    #[sea_orm(string_value = "Declined")]
+    Declined,
+    #[sea_orm(string_value = "Settled")]
+    Settled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum LegResult {
+    #[sea_orm(string_value = "Pending")]
+    Pending,
+    #[sea_orm(string_value = "Won")]
+    Won,
+    #[sea_orm(string_value = "Lost")]
+    Lost,
+    #[sea_orm(string_value = "Void")]
+    Void,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum ParlayStatus {
+    #[sea_orm(string_value = "Open")]
+    Open,
+    #[sea_orm(string_value = "Won")]
+    Won,
+    #[sea_orm(string_value = "Lost")]
+    Lost,
+    #[sea_orm(string_value = "Refunded")]
+    Refunded,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(32))")]
+pub enum JobKind {
+    #[sea_orm(string_value = "CloseMatch")]
+    CloseMatch,
+    #[sea_orm(string_value = "RemindMatch")]
+    RemindMatch,
+    #[sea_orm(string_value = "ExpireMatches")]
+    ExpireMatches,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+pub enum JobStatus {
+    #[sea_orm(string_value = "Pending")]
+    Pending,
+    #[sea_orm(string_value = "Running")]
+    Running,
+    #[sea_orm(string_value = "Done")]
+    Done,
+    #[sea_orm(string_value = "Failed")]
+    Failed,
+}
@@ -11,12 +11,17 @@ pub struct Model {
     pub traq_display_id: String,
     pub channel_id: String,
     pub balance: i32,
+    pub notify: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::bet::Entity")]
     Bet,
+    #[sea_orm(has_many = "super::parlay::Entity")]
+    Parlay,
+    #[sea_orm(has_many = "super::position::Entity")]
+    Position,
 }
 
 impl Related<super::bet::Entity> for Entity {
@@ -25,4 +30,16 @@ impl Related<super::bet::Entity> for Entity {
     }
 }
 
+impl Related<super::parlay::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Parlay.def()
+    }
+}
+
+impl Related<super::position::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Position.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
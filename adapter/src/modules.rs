@@ -1,8 +1,12 @@
 use kernel::{
-    model::{bet::Bet, candidate::Candidate, r#match::Match, user::User},
+    model::{
+        bet::Bet, candidate::Candidate, challenge::Challenge, channel::Channel, job::Job,
+        parlay::Parlay, position::Position, r#match::Match, user::User,
+    },
     repository::{
-        bet::BetRepository, candidate::CandidateRepository, r#match::MatchRepository,
-        user::UserRepository,
+        bet::BetRepository, candidate::CandidateRepository, challenge::ChallengeRepository,
+        channel::ChannelRepository, job::JobRepository, parlay::ParlayRepository,
+        position::PositionRepository, r#match::MatchRepository, user::UserRepository,
     },
     traq::{message::MessageTraqRepository, stamp::StampTraqRepository},
 };
@@ -16,6 +20,11 @@ pub struct RepositoriesModule {
     match_repository: DatabaseRepositoryImpl<Match>,
     candidate_repository: DatabaseRepositoryImpl<Candidate>,
     bet_repository: DatabaseRepositoryImpl<Bet>,
+    channel_repository: DatabaseRepositoryImpl<Channel>,
+    challenge_repository: DatabaseRepositoryImpl<Challenge>,
+    position_repository: DatabaseRepositoryImpl<Position>,
+    parlay_repository: DatabaseRepositoryImpl<Parlay>,
+    job_repository: DatabaseRepositoryImpl<Job>,
 
     message_traq_repository: TraqRepositoryImpl,
     stamp_repository: TraqRepositoryImpl,
@@ -26,10 +35,20 @@ pub trait RepositoriesModuleExt {
     type MatchRepo: MatchRepository;
     type CandidateRepo: CandidateRepository;
     type BetRepo: BetRepository;
+    type ChannelRepo: ChannelRepository;
+    type ChallengeRepo: ChallengeRepository;
+    type PositionRepo: PositionRepository;
+    type ParlayRepo: ParlayRepository;
+    type JobRepo: JobRepository;
     fn user_repository(&self) -> &Self::UserRepo;
     fn match_repository(&self) -> &Self::MatchRepo;
     fn candidate_repository(&self) -> &Self::CandidateRepo;
     fn bet_repository(&self) -> &Self::BetRepo;
+    fn channel_repository(&self) -> &Self::ChannelRepo;
+    fn challenge_repository(&self) -> &Self::ChallengeRepo;
+    fn position_repository(&self) -> &Self::PositionRepo;
+    fn parlay_repository(&self) -> &Self::ParlayRepo;
+    fn job_repository(&self) -> &Self::JobRepo;
 
     type MessageTraqRepo: MessageTraqRepository;
     type StampRepo: StampTraqRepository;
@@ -42,6 +61,11 @@ impl RepositoriesModuleExt for RepositoriesModule {
     type MatchRepo = DatabaseRepositoryImpl<Match>;
     type CandidateRepo = DatabaseRepositoryImpl<Candidate>;
     type BetRepo = DatabaseRepositoryImpl<Bet>;
+    type ChannelRepo = DatabaseRepositoryImpl<Channel>;
+    type ChallengeRepo = DatabaseRepositoryImpl<Challenge>;
+    type PositionRepo = DatabaseRepositoryImpl<Position>;
+    type ParlayRepo = DatabaseRepositoryImpl<Parlay>;
+    type JobRepo = DatabaseRepositoryImpl<Job>;
     fn user_repository(&self) -> &Self::UserRepo {
         &self.user_repository
     }
@@ -54,6 +78,21 @@ impl RepositoriesModuleExt for RepositoriesModule {
     fn bet_repository(&self) -> &Self::BetRepo {
         &self.bet_repository
     }
+    fn channel_repository(&self) -> &Self::ChannelRepo {
+        &self.channel_repository
+    }
+    fn challenge_repository(&self) -> &Self::ChallengeRepo {
+        &self.challenge_repository
+    }
+    fn position_repository(&self) -> &Self::PositionRepo {
+        &self.position_repository
+    }
+    fn parlay_repository(&self) -> &Self::ParlayRepo {
+        &self.parlay_repository
+    }
+    fn job_repository(&self) -> &Self::JobRepo {
+        &self.job_repository
+    }
 
     type MessageTraqRepo = TraqRepositoryImpl;
     type StampRepo = TraqRepositoryImpl;
@@ -74,6 +113,11 @@ impl RepositoriesModule {
             match_repository: DatabaseRepositoryImpl::new(db.clone()),
             candidate_repository: DatabaseRepositoryImpl::new(db.clone()),
             bet_repository: DatabaseRepositoryImpl::new(db.clone()),
+            channel_repository: DatabaseRepositoryImpl::new(db.clone()),
+            challenge_repository: DatabaseRepositoryImpl::new(db.clone()),
+            position_repository: DatabaseRepositoryImpl::new(db.clone()),
+            parlay_repository: DatabaseRepositoryImpl::new(db.clone()),
+            job_repository: DatabaseRepositoryImpl::new(db.clone()),
             message_traq_repository: TraqRepositoryImpl::new(access_token.clone()),
             stamp_repository: TraqRepositoryImpl::new(access_token),
         }
@@ -1,29 +1,77 @@
 use kernel::{
     model::{
-        bet::{Bet, NewBetForLatestMatch},
-        r#match::Match,
+        bet::{
+            self, Bet, CashOut, CashOutBetForLatestMatch, DeleteBetForLatestMatch,
+            NewBetForLatestMatch, NewGuessBetForLatestMatch, NewOrderedBetForLatestMatch,
+            UpdateBetForLatestMatch,
+        },
+        r#match::{self, Match, MatchEvent, GUESS_CANDIDATE_NAME},
+        settlement,
+        statistic::new_statistics,
         Id,
     },
     repository::{bet::BetRepository, error::RepositoryError},
 };
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel, QueryFilter, Set,
     SqlErr, TransactionError, TransactionTrait, TryIntoModel,
 };
 
-use crate::model::bet::{ActiveModel, Column, Entity, Model};
+use crate::model::{
+    bet::{ActiveModel, Column, Entity, Model},
+    r#match::Model as MatchModel,
+    sea_orm_active_enums::BetType,
+    user::Model as UserModel,
+};
 
-use super::DatabaseRepositoryImpl;
+use super::{
+    channel::{add_house_balance, find_or_create_model},
+    r#match::{current_status, find_latest_model},
+    user::add_balance,
+    DatabaseRepositoryImpl,
+};
 
 impl From<Model> for Bet {
     fn from(model: Model) -> Self {
-        Bet::new(
-            Id::new(model.id),
-            Id::new(model.user_id),
-            Id::new(model.match_id),
-            Id::new(model.candidate_id),
-            model.amount,
-        )
+        Bet {
+            id: Id::new(model.id),
+            user_id: Id::new(model.user_id),
+            match_id: Id::new(model.match_id),
+            candidate_id: Id::new(model.candidate_id),
+            amount: model.amount,
+            created_at: model.created_at,
+            odds: model.odds,
+            bet_type: model.bet_type.into(),
+            following_candidate_ids: model
+                .following_candidate_ids
+                .split(',')
+                .filter(|id| !id.is_empty())
+                .map(|id| Id::new(id.to_string()))
+                .collect(),
+            guess: model.guess,
+        }
+    }
+}
+
+impl From<BetType> for bet::BetType {
+    fn from(bet_type: BetType) -> Self {
+        match bet_type {
+            BetType::Win => bet::BetType::Win,
+            BetType::Exacta => bet::BetType::Exacta,
+            BetType::Trifecta => bet::BetType::Trifecta,
+            BetType::Guess => bet::BetType::Guess,
+        }
+    }
+}
+
+impl From<bet::BetType> for BetType {
+    fn from(bet_type: bet::BetType) -> Self {
+        match bet_type {
+            bet::BetType::Win => BetType::Win,
+            bet::BetType::Exacta => BetType::Exacta,
+            bet::BetType::Trifecta => BetType::Trifecta,
+            bet::BetType::Guess => BetType::Guess,
+        }
     }
 }
 
@@ -40,67 +88,245 @@ impl TryFrom<ActiveModel> for Bet {
 
 const PARTICIPATION_PRIZE_POINT: i32 = 1000;
 
+// event を受け付けている channel の match と、bet するユーザーを取得する
+async fn find_match_and_user<C: ConnectionTrait>(
+    db: &C,
+    channel_id: &str,
+    match_number: Option<i32>,
+    traq_id: &str,
+    event: MatchEvent,
+) -> Result<(MatchModel, UserModel), RepositoryError> {
+    let match_ = find_latest_model(db, channel_id, match_number).await?;
+    r#match::transition(current_status(&match_), event)?;
+
+    let user = crate::model::user::Entity::find()
+        .filter(crate::model::user::Column::TraqId.eq(traq_id))
+        .filter(crate::model::user::Column::ChannelId.eq(channel_id))
+        .one(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+        .ok_or(RepositoryError::RecordNotFound(
+            "User not found".to_string(),
+        ))?;
+
+    Ok((match_, user))
+}
+
+async fn find_candidate<C: ConnectionTrait>(
+    db: &C,
+    match_id: &str,
+    name: &str,
+) -> Result<crate::model::candidate::Model, RepositoryError> {
+    crate::model::candidate::Entity::find()
+        .filter(crate::model::candidate::Column::MatchId.eq(match_id))
+        .filter(crate::model::candidate::Column::Name.eq(name))
+        .one(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+        .ok_or(RepositoryError::RecordNotFound(
+            "Candidate not found".to_string(),
+        ))
+}
+
+// ユーザーがこの match で bet している全ての候補への bet を取得する
+async fn find_user_bets<C: ConnectionTrait>(
+    db: &C,
+    match_id: &str,
+    user_id: &str,
+) -> Result<Vec<Model>, RepositoryError> {
+    Entity::find()
+        .filter(Column::MatchId.eq(match_id))
+        .filter(Column::UserId.eq(user_id))
+        .all(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))
+}
+
+// 賭けるポイントの合計や追加後のポイントが i32 に収まらない
+fn amount_overflow() -> RepositoryError {
+    RepositoryError::InvalidArgument("Amount overflow".to_string())
+}
+
+// 残高を増減させる。残高が足りなくなる場合は InsufficientBalance を返す
+async fn change_balance<C: ConnectionTrait>(
+    db: &C,
+    user_id: &str,
+    amount: i32,
+) -> Result<i32, RepositoryError> {
+    let balance = add_balance(db, user_id, amount).await?;
+    if balance < 0 {
+        return Err(RepositoryError::InsufficientBalance);
+    }
+    Ok(balance)
+}
+
 impl BetRepository for DatabaseRepositoryImpl<Bet> {
     async fn insert_for_latest_match(
         &self,
         m: NewBetForLatestMatch,
+    ) -> Result<Vec<Bet>, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Vec<Bet>, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let (match_, user) = find_match_and_user(
+                        txn,
+                        &m.channel_id,
+                        m.match_number,
+                        &m.traq_id,
+                        MatchEvent::Bet,
+                    )
+                    .await?;
+                    let mut bets = find_user_bets(txn, &match_.id, &user.id).await?;
+
+                    // 参加賞はこの match で初めて bet したときだけもらえる
+                    let prize = if bets.is_empty() {
+                        PARTICIPATION_PRIZE_POINT
+                    } else {
+                        0
+                    };
+                    // 残高を変える前に、合計や追加後のポイントが i32 に収まるか確かめる
+                    let mut total = 0_i32;
+                    let mut stakes = Vec::new();
+                    for stake in m.stakes {
+                        let candidate =
+                            find_candidate(txn, &match_.id, &stake.candidate_name).await?;
+                        total = total
+                            .checked_add(stake.amount)
+                            .ok_or_else(amount_overflow)?;
+                        if let Some(bet) = bets
+                            .iter()
+                            .find(|b| b.bet_type == BetType::Win && b.candidate_id == candidate.id)
+                        {
+                            bet.amount
+                                .checked_add(stake.amount)
+                                .ok_or_else(amount_overflow)?;
+                        }
+                        stakes.push((stake, candidate));
+                    }
+                    let user_id = user.id.clone();
+                    change_balance(txn, &user.id, prize - total).await?;
+
+                    let mut results = Vec::new();
+                    for (stake, candidate) in stakes {
+                        // 既に bet している候補の場合はポイントの追加として扱う
+                        if let Some(i) = bets.iter().position(|b| {
+                            b.bet_type == BetType::Win && b.candidate_id == candidate.id
+                        }) {
+                            let bet = bets.remove(i);
+                            let amount = bet.amount + stake.amount;
+                            let mut bet = bet.into_active_model();
+                            bet.amount = Set(amount);
+                            let bet = bet.update(txn).await.map_err(|e| {
+                                RepositoryError::UnexpectedError(anyhow::anyhow!(e))
+                            })?;
+                            results.push(bet.into());
+                            continue;
+                        }
+
+                        let model = Model {
+                            id: stake.id.value.to_string(),
+                            user_id: user_id.clone(),
+                            match_id: match_.id.clone(),
+                            candidate_id: candidate.id,
+                            amount: stake.amount,
+                            created_at: m.created_at,
+                            // 固定オッズの場合は bet した時点の倍率で払い戻す
+                            odds: candidate.odds,
+                            bet_type: BetType::Win,
+                            following_candidate_ids: "".to_string(),
+                            guess: None,
+                        };
+
+                        let result =
+                            model.into_active_model().save(txn).await.map_err(|e| {
+                                match e.sql_err() {
+                                    Some(SqlErr::UniqueConstraintViolation(s)) => {
+                                        RepositoryError::DuplicatedRecord(s.to_string())
+                                    }
+                                    _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+                                }
+                            })?;
+                        results.push(result.try_into()?);
+                    }
+
+                    Ok(results)
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn insert_ordered_for_latest_match(
+        &self,
+        m: NewOrderedBetForLatestMatch,
     ) -> Result<Bet, RepositoryError> {
         self.db
             .0
             .transaction::<_, Bet, RepositoryError>(|txn| {
                 Box::pin(async move {
-                    let match_ = crate::model::r#match::Entity::find()
-                        .filter(crate::model::r#match::Column::ClosedAt.is_null())
-                        .filter(crate::model::r#match::Column::WinnerCandidateId.is_null())
-                        .order_by_desc(crate::model::r#match::Column::CreatedAt)
-                        .one(txn)
-                        .await
-                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
-                        .ok_or(RepositoryError::RecordNotFound(
-                            "Match not found".to_string(),
-                        ))?;
+                    let (match_, user) = find_match_and_user(
+                        txn,
+                        &m.channel_id,
+                        m.match_number,
+                        &m.traq_id,
+                        MatchEvent::Bet,
+                    )
+                    .await?;
+                    let bets = find_user_bets(txn, &match_.id, &user.id).await?;
 
-                    let candidate = crate::model::candidate::Entity::find()
-                        .filter(crate::model::candidate::Column::MatchId.eq(&match_.id))
-                        .filter(crate::model::candidate::Column::Name.eq(&m.candidate_name))
-                        .one(txn)
-                        .await
-                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
-                        .ok_or(RepositoryError::RecordNotFound(
-                            "Candidate not found".to_string(),
-                        ))?;
-
-                    let user = crate::model::user::Entity::find()
-                        .filter(crate::model::user::Column::TraqId.eq(&m.traq_id))
-                        .filter(crate::model::user::Column::ChannelId.eq(&m.channel_id))
-                        .one(txn)
-                        .await
-                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
-                        .ok_or(RepositoryError::RecordNotFound(
-                            "User not found".to_string(),
-                        ))?;
+                    let mut candidate_ids = Vec::new();
+                    for name in m.candidate_names.iter() {
+                        candidate_ids.push(find_candidate(txn, &match_.id, name).await?.id);
+                    }
+                    let bet_type: BetType = m.bet_type.into();
+                    let following_candidate_ids = candidate_ids[1..].join(",");
 
+                    let prize = if bets.is_empty() {
+                        PARTICIPATION_PRIZE_POINT
+                    } else {
+                        0
+                    };
+                    // 既に同じ着順に bet している場合はポイントの追加として扱う
+                    let existing = bets.into_iter().find(|b| {
+                        b.bet_type == bet_type
+                            && b.candidate_id == candidate_ids[0]
+                            && b.following_candidate_ids == following_candidate_ids
+                    });
+                    let amount = match &existing {
+                        Some(bet) => bet
+                            .amount
+                            .checked_add(m.amount)
+                            .ok_or_else(amount_overflow)?,
+                        None => m.amount,
+                    };
                     let user_id = user.id.clone();
-                    let user_balance = user.balance + PARTICIPATION_PRIZE_POINT - m.amount;
-                    // MEMO: これ usecase に移す
-                    if user_balance < 0 {
-                        return Err(RepositoryError::InsufficientBalance);
+                    change_balance(txn, &user.id, prize - m.amount).await?;
+
+                    if let Some(bet) = existing {
+                        let mut bet = bet.into_active_model();
+                        bet.amount = Set(amount);
+                        return Ok(bet
+                            .update(txn)
+                            .await
+                            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                            .into());
                     }
-                    let mut user_model = user.into_active_model();
-                    user_model.balance = Set(user_balance);
-                    user_model
-                        .save(txn)
-                        .await
-                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
 
                     let model = Model {
                         id: m.id.value.to_string(),
-                        user_id: user_id,
-                        match_id: match_.id,
-                        candidate_id: candidate.id,
+                        user_id,
+                        match_id: match_.id.clone(),
+                        candidate_id: candidate_ids[0].clone(),
                         amount: m.amount,
+                        created_at: m.created_at,
+                        odds: None,
+                        bet_type,
+                        following_candidate_ids,
+                        guess: None,
                     };
-
                     let result =
                         model.into_active_model().save(txn).await.map_err(|e| {
                             match e.sql_err() {
@@ -110,7 +336,83 @@ impl BetRepository for DatabaseRepositoryImpl<Bet> {
                                 _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
                             }
                         })?;
+                    result.try_into()
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn insert_guess_for_latest_match(
+        &self,
+        m: NewGuessBetForLatestMatch,
+    ) -> Result<Bet, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Bet, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let (match_, user) = find_match_and_user(
+                        txn,
+                        &m.channel_id,
+                        m.match_number,
+                        &m.traq_id,
+                        MatchEvent::Bet,
+                    )
+                    .await?;
+                    let candidate = find_candidate(txn, &match_.id, GUESS_CANDIDATE_NAME).await?;
+                    let bets = find_user_bets(txn, &match_.id, &user.id).await?;
+
+                    let prize = if bets.is_empty() {
+                        PARTICIPATION_PRIZE_POINT
+                    } else {
+                        0
+                    };
+                    // 既に予想している場合は予想を更新してポイントを追加する
+                    let existing = bets.into_iter().find(|b| b.bet_type == BetType::Guess);
+                    let amount = match &existing {
+                        Some(bet) => bet
+                            .amount
+                            .checked_add(m.amount)
+                            .ok_or_else(amount_overflow)?,
+                        None => m.amount,
+                    };
+                    let user_id = user.id.clone();
+                    change_balance(txn, &user.id, prize - m.amount).await?;
+
+                    if let Some(bet) = existing {
+                        let mut bet = bet.into_active_model();
+                        bet.amount = Set(amount);
+                        bet.guess = Set(Some(m.guess));
+                        return Ok(bet
+                            .update(txn)
+                            .await
+                            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                            .into());
+                    }
 
+                    let model = Model {
+                        id: m.id.value.to_string(),
+                        user_id,
+                        match_id: match_.id.clone(),
+                        candidate_id: candidate.id,
+                        amount: m.amount,
+                        created_at: m.created_at,
+                        odds: None,
+                        bet_type: BetType::Guess,
+                        following_candidate_ids: "".to_string(),
+                        guess: Some(m.guess),
+                    };
+                    let result =
+                        model.into_active_model().save(txn).await.map_err(|e| {
+                            match e.sql_err() {
+                                Some(SqlErr::UniqueConstraintViolation(s)) => {
+                                    RepositoryError::DuplicatedRecord(s.to_string())
+                                }
+                                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+                            }
+                        })?;
                     result.try_into()
                 })
             })
@@ -120,6 +422,239 @@ impl BetRepository for DatabaseRepositoryImpl<Bet> {
                 _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
             })
     }
+    async fn update_for_latest_match(
+        &self,
+        m: UpdateBetForLatestMatch,
+    ) -> Result<Bet, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Bet, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let (match_, user) = find_match_and_user(
+                        txn,
+                        &m.channel_id,
+                        m.match_number,
+                        &m.traq_id,
+                        MatchEvent::Bet,
+                    )
+                    .await?;
+                    let candidate = find_candidate(txn, &match_.id, &m.candidate_name).await?;
+                    // 候補を変更できるのは 1 着を当てる bet だけ
+                    let mut bets = find_user_bets(txn, &match_.id, &user.id)
+                        .await?
+                        .into_iter()
+                        .filter(|b| b.bet_type == BetType::Win)
+                        .collect::<Vec<_>>();
+
+                    let from = match m.from_candidate_name {
+                        Some(name) => {
+                            let from = find_candidate(txn, &match_.id, &name).await?;
+                            bets.iter().position(|b| b.candidate_id == from.id)
+                        }
+                        // 変更元が指定されていない場合は 1 つの候補にだけ bet しているときに限り変更できる
+                        None if bets.len() > 1 => {
+                            return Err(RepositoryError::DuplicatedRecord(
+                                "Multiple bets found".to_string(),
+                            ))
+                        }
+                        None => bets.first().map(|_| 0),
+                    }
+                    .ok_or(RepositoryError::RecordNotFound("Bet not found".to_string()))?;
+                    let bet = bets.remove(from);
+                    if bet.candidate_id == candidate.id {
+                        return Ok(bet.into());
+                    }
+
+                    // 変更先の候補にも bet している場合は 1 つにまとめる
+                    if let Some(to) = bets.into_iter().find(|b| b.candidate_id == candidate.id) {
+                        let amount = to
+                            .amount
+                            .checked_add(bet.amount)
+                            .ok_or_else(amount_overflow)?;
+                        Entity::delete_by_id(bet.id.clone())
+                            .exec(txn)
+                            .await
+                            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                        let mut to = to.into_active_model();
+                        to.amount = Set(amount);
+                        return Ok(to
+                            .update(txn)
+                            .await
+                            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                            .into());
+                    }
+
+                    // 賭けたポイントはそのまま候補だけを移す
+                    let mut bet = bet.into_active_model();
+                    bet.candidate_id = Set(candidate.id);
+                    bet.odds = Set(candidate.odds);
+                    Ok(bet
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                        .into())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn delete_for_latest_match(
+        &self,
+        m: DeleteBetForLatestMatch,
+    ) -> Result<Vec<Bet>, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Vec<Bet>, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let (match_, user) = find_match_and_user(
+                        txn,
+                        &m.channel_id,
+                        m.match_number,
+                        &m.traq_id,
+                        MatchEvent::Bet,
+                    )
+                    .await?;
+                    let bets = find_user_bets(txn, &match_.id, &user.id).await?;
+                    let count = bets.len();
+                    let deleted = match m.candidate_name {
+                        Some(name) => {
+                            let candidate = find_candidate(txn, &match_.id, &name).await?;
+                            bets.into_iter()
+                                .filter(|b| {
+                                    b.bet_type == BetType::Win && b.candidate_id == candidate.id
+                                })
+                                .collect::<Vec<_>>()
+                        }
+                        None => bets,
+                    };
+                    if deleted.is_empty() {
+                        return Err(RepositoryError::RecordNotFound("Bet not found".to_string()));
+                    }
+
+                    // 賭けたポイントを返却し、全ての bet を取り消した場合は参加賞も取り消す
+                    let refund = deleted.iter().map(|b| b.amount).sum::<i32>();
+                    let prize = if deleted.len() == count {
+                        PARTICIPATION_PRIZE_POINT
+                    } else {
+                        0
+                    };
+                    change_balance(txn, &user.id, refund - prize).await?;
+
+                    Entity::delete_many()
+                        .filter(Column::Id.is_in(deleted.iter().map(|b| b.id.clone())))
+                        .exec(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    Ok(deleted.into_iter().map(|b| b.into()).collect())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn cash_out_for_latest_match(
+        &self,
+        m: CashOutBetForLatestMatch,
+    ) -> Result<CashOut, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, CashOut, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let (match_, user) = find_match_and_user(
+                        txn,
+                        &m.channel_id,
+                        m.match_number,
+                        &m.traq_id,
+                        MatchEvent::CashOut,
+                    )
+                    .await?;
+                    // cash out できるのは 1 着を当てる bet だけ
+                    let user_bets = find_user_bets(txn, &match_.id, &user.id).await?;
+                    let count = user_bets.len();
+                    let mut bets = user_bets
+                        .into_iter()
+                        .filter(|b| b.bet_type == BetType::Win)
+                        .collect::<Vec<_>>();
+                    let index = match m.candidate_name {
+                        Some(name) => {
+                            let candidate = find_candidate(txn, &match_.id, &name).await?;
+                            bets.iter().position(|b| b.candidate_id == candidate.id)
+                        }
+                        None if bets.len() > 1 => {
+                            return Err(RepositoryError::DuplicatedRecord(
+                                "Multiple bets found".to_string(),
+                            ))
+                        }
+                        None => bets.first().map(|_| 0),
+                    }
+                    .ok_or(RepositoryError::RecordNotFound("Bet not found".to_string()))?;
+                    let bet = bets.remove(index);
+
+                    // 提示額は現在のプール全体の分け方から計算する
+                    let pool = Entity::find()
+                        .filter(Column::MatchId.eq(&match_.id))
+                        .all(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                    let candidates = crate::model::candidate::Entity::find()
+                        .filter(crate::model::candidate::Column::MatchId.eq(&match_.id))
+                        .all(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                    let statistics = new_statistics(
+                        pool.into_iter().map(|b| b.into()).collect(),
+                        candidates.into_iter().map(|c| c.into()).collect(),
+                    );
+                    let channel = find_or_create_model(txn, &m.channel_id).await?;
+                    let value = settlement::cashout_value(
+                        &bet.clone().into(),
+                        &statistics,
+                        &channel.clone().into(),
+                    );
+                    // 最後の bet を cash out する場合は、bet し直して参加賞を何度も受け取れないように参加賞も取り消す
+                    let prize = if count == 1 {
+                        PARTICIPATION_PRIZE_POINT
+                    } else {
+                        0
+                    };
+                    // 受け取れるポイントが無い場合は、賭けたポイントを全て失うだけなので cash out させない
+                    if value <= 0 {
+                        return Err(RepositoryError::InvalidArgument(
+                            "Cash out value is zero".to_string(),
+                        ));
+                    }
+                    // 参加賞を使ってしまっていて取り消せない場合は cash out させない
+                    if user.balance + value - prize < 0 {
+                        return Err(RepositoryError::InsufficientBalance);
+                    }
+                    // 確定する場合でも、ユーザーが見た提示額より下がっていれば確定せずに提示し直す
+                    if m.accepted_value.is_none_or(|accepted| value < accepted) {
+                        return Ok(CashOut::new(bet.into(), value, prize, user.balance, false));
+                    }
+
+                    // bet をプールから取り除いて提示額を支払い、差し引いた分はハウスが受け取る
+                    let balance = change_balance(txn, &user.id, value - prize).await?;
+                    add_house_balance(txn, &m.channel_id, bet.amount - value).await?;
+                    Entity::delete_by_id(bet.id.clone())
+                        .exec(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    Ok(CashOut::new(bet.into(), value, prize, balance, true))
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
     async fn select_by_match_id(&self, match_id: Id<Match>) -> Result<Vec<Bet>, RepositoryError> {
         let result = Entity::find()
             .filter(Column::MatchId.eq(match_id.value.to_string()))
@@ -14,12 +14,16 @@ use super::DatabaseRepositoryImpl;
 
 impl From<Model> for Candidate {
     fn from(model: Model) -> Self {
-        Candidate::new(
-            Id::new(model.id),
-            model.name,
-            Id::new(model.match_id),
-            model.is_winner.and_then(|v| Some(v != 0)),
-        )
+        Candidate {
+            id: Id::new(model.id),
+            name: model.name,
+            match_id: Id::new(model.match_id),
+            is_winner: model.is_winner.map(|v| v != 0),
+            odds: model.odds,
+            placing: model.placing,
+            handicap: model.handicap,
+            score: model.score,
+        }
     }
 }
 
@@ -44,6 +48,10 @@ impl CandidateRepository for DatabaseRepositoryImpl<Candidate> {
                     name: c.name,
                     match_id: c.match_id.value.to_string(),
                     is_winner: None,
+                    odds: c.odds,
+                    placing: None,
+                    handicap: c.handicap,
+                    score: None,
                 }
                 .into_active_model()
             })
@@ -0,0 +1,284 @@
+use kernel::{
+    model::{
+        challenge::{
+            self, AcceptChallengeForLatest, Challenge, DeclineChallengeForLatest, NewChallenge,
+            ResolveChallengeForLatest,
+        },
+        Id,
+    },
+    repository::{challenge::ChallengeRepository, error::RepositoryError},
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, EntityTrait, IntoActiveModel,
+    QueryFilter, QueryOrder, Select, Set, TransactionError, TransactionTrait,
+};
+
+use crate::model::{
+    challenge::{Column, Entity, Model},
+    sea_orm_active_enums::ChallengeStatus,
+    user::Model as UserModel,
+};
+
+use super::{user::add_balance, DatabaseRepositoryImpl};
+
+impl From<Model> for Challenge {
+    fn from(model: Model) -> Self {
+        Challenge {
+            id: Id::new(model.id),
+            channel_id: Id::new(model.channel_id),
+            title: model.title,
+            amount: model.amount,
+            challenger_id: Id::new(model.challenger_id),
+            opponent_id: Id::new(model.opponent_id),
+            arbiter_id: model.arbiter_id.map(Id::new),
+            winner_id: model.winner_id.map(Id::new),
+            status: model.status.into(),
+            created_at: model.created_at,
+            accepted_at: model.accepted_at,
+            finished_at: model.finished_at,
+        }
+    }
+}
+
+impl From<ChallengeStatus> for challenge::ChallengeStatus {
+    fn from(status: ChallengeStatus) -> Self {
+        match status {
+            ChallengeStatus::Pending => challenge::ChallengeStatus::Pending,
+            ChallengeStatus::Accepted => challenge::ChallengeStatus::Accepted,
+            ChallengeStatus::Declined => challenge::ChallengeStatus::Declined,
+            ChallengeStatus::Settled => challenge::ChallengeStatus::Settled,
+        }
+    }
+}
+
+// channel に登録しているユーザーを取得する。見つからない場合は not_found をメッセージにした RecordNotFound を返す
+async fn find_user<C: ConnectionTrait>(
+    db: &C,
+    channel_id: &str,
+    traq_id: &str,
+    not_found: &str,
+) -> Result<UserModel, RepositoryError> {
+    crate::model::user::Entity::find()
+        .filter(crate::model::user::Column::TraqId.eq(traq_id))
+        .filter(crate::model::user::Column::ChannelId.eq(channel_id))
+        .one(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+        .ok_or(RepositoryError::RecordNotFound(not_found.to_string()))
+}
+
+// 勝負が決まるまでポイントを預かる。残高が足りない場合は InsufficientBalance を返す
+async fn escrow<C: ConnectionTrait>(
+    db: &C,
+    user_id: &str,
+    amount: i32,
+) -> Result<(), RepositoryError> {
+    let balance = add_balance(db, user_id, -amount).await?;
+    if balance < 0 {
+        return Err(RepositoryError::InsufficientBalance);
+    }
+    Ok(())
+}
+
+async fn find_latest_model<C: ConnectionTrait>(
+    db: &C,
+    query: Select<Entity>,
+) -> Result<Model, RepositoryError> {
+    query
+        .order_by_desc(Column::CreatedAt)
+        .one(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+        .ok_or(RepositoryError::RecordNotFound(
+            "Challenge not found".to_string(),
+        ))
+}
+
+impl ChallengeRepository for DatabaseRepositoryImpl<Challenge> {
+    async fn insert(&self, c: NewChallenge) -> Result<Challenge, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Challenge, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let channel_id = c.channel_id.value;
+                    let challenger =
+                        find_user(txn, &channel_id, &c.challenger_traq_id, "User not found")
+                            .await?;
+                    let opponent =
+                        find_user(txn, &channel_id, &c.opponent_traq_id, "Opponent not found")
+                            .await?;
+                    let arbiter = match c.arbiter_traq_id {
+                        Some(traq_id) => {
+                            Some(find_user(txn, &channel_id, &traq_id, "Arbiter not found").await?)
+                        }
+                        None => None,
+                    };
+                    escrow(txn, &challenger.id, c.amount).await?;
+
+                    let model = Model {
+                        id: c.id.value,
+                        channel_id,
+                        title: c.title,
+                        amount: c.amount,
+                        challenger_id: challenger.id,
+                        opponent_id: opponent.id,
+                        arbiter_id: arbiter.map(|a| a.id),
+                        winner_id: None,
+                        status: ChallengeStatus::Pending,
+                        created_at: c.created_at,
+                        accepted_at: None,
+                        finished_at: None,
+                    };
+                    let result = model
+                        .into_active_model()
+                        .insert(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    Ok(result.into())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn accept_for_latest(
+        &self,
+        c: AcceptChallengeForLatest,
+    ) -> Result<Challenge, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Challenge, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let user =
+                        find_user(txn, &c.channel_id.value, &c.traq_id, "User not found").await?;
+                    let challenge = find_latest_model(
+                        txn,
+                        Entity::find()
+                            .filter(Column::ChannelId.eq(c.channel_id.value))
+                            .filter(Column::OpponentId.eq(user.id.clone()))
+                            .filter(Column::Status.eq(ChallengeStatus::Pending)),
+                    )
+                    .await?;
+                    escrow(txn, &user.id, challenge.amount).await?;
+
+                    let mut challenge = challenge.into_active_model();
+                    challenge.status = Set(ChallengeStatus::Accepted);
+                    challenge.accepted_at = Set(Some(c.accepted_at));
+                    let result = challenge
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    Ok(result.into())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn decline_for_latest(
+        &self,
+        c: DeclineChallengeForLatest,
+    ) -> Result<Challenge, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Challenge, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let user =
+                        find_user(txn, &c.channel_id.value, &c.traq_id, "User not found").await?;
+                    // 挑まれた側は断り、挑んだ側は取り下げることができる
+                    let challenge = find_latest_model(
+                        txn,
+                        Entity::find()
+                            .filter(Column::ChannelId.eq(c.channel_id.value))
+                            .filter(
+                                Condition::any()
+                                    .add(Column::ChallengerId.eq(user.id.clone()))
+                                    .add(Column::OpponentId.eq(user.id.clone())),
+                            )
+                            .filter(Column::Status.eq(ChallengeStatus::Pending)),
+                    )
+                    .await?;
+                    add_balance(txn, &challenge.challenger_id, challenge.amount).await?;
+
+                    let mut challenge = challenge.into_active_model();
+                    challenge.status = Set(ChallengeStatus::Declined);
+                    challenge.finished_at = Set(Some(c.declined_at));
+                    let result = challenge
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    Ok(result.into())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn resolve_for_latest(
+        &self,
+        c: ResolveChallengeForLatest,
+    ) -> Result<Challenge, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Challenge, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let user =
+                        find_user(txn, &c.channel_id.value, &c.traq_id, "User not found").await?;
+                    let winner = find_user(
+                        txn,
+                        &c.channel_id.value,
+                        &c.winner_traq_id,
+                        "Winner not found",
+                    )
+                    .await?;
+                    let challenge = find_latest_model(
+                        txn,
+                        Entity::find()
+                            .filter(Column::ChannelId.eq(c.channel_id.value))
+                            .filter(
+                                Condition::any()
+                                    .add(Column::ChallengerId.eq(user.id.clone()))
+                                    .add(Column::OpponentId.eq(user.id.clone()))
+                                    .add(Column::ArbiterId.eq(user.id.clone())),
+                            )
+                            .filter(Column::Status.eq(ChallengeStatus::Accepted)),
+                    )
+                    .await?;
+                    let can_resolve = Challenge::from(challenge.clone())
+                        .can_resolve(&Id::new(user.id), &Id::new(winner.id.clone()));
+                    if !can_resolve {
+                        return Err(RepositoryError::PermissionDenied(
+                            "User cannot resolve the challenge with this winner".to_string(),
+                        ));
+                    }
+                    // 両者から預かったポイントを勝者に払い戻す
+                    add_balance(txn, &winner.id, challenge.amount * 2).await?;
+
+                    let mut challenge = challenge.into_active_model();
+                    challenge.status = Set(ChallengeStatus::Settled);
+                    challenge.winner_id = Set(Some(winner.id));
+                    challenge.finished_at = Set(Some(c.resolved_at));
+                    let result = challenge
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    Ok(result.into())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+}
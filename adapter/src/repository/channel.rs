@@ -0,0 +1,206 @@
+use kernel::{
+    model::{
+        channel::{Channel, UpdateChannel},
+        settlement, Id,
+    },
+    repository::{channel::ChannelRepository, error::RepositoryError},
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel, QueryFilter,
+    QuerySelect, Set, TransactionError, TransactionTrait,
+};
+use sea_query::{Expr, OnConflict};
+
+use crate::model::{
+    channel::{ActiveModel, Column, Entity, Model},
+    sea_orm_active_enums::{NoWinnerPolicy, RoundingPolicy},
+};
+
+use super::DatabaseRepositoryImpl;
+
+impl From<Model> for Channel {
+    fn from(model: Model) -> Self {
+        Channel::new(
+            Id::new(model.id),
+            model.rounding_policy.into(),
+            model.house_balance,
+            model.no_winner_policy.into(),
+            model.jackpot,
+            model.commission_percent,
+            model.match_ttl_hours,
+        )
+    }
+}
+
+impl From<RoundingPolicy> for settlement::RoundingPolicy {
+    fn from(policy: RoundingPolicy) -> Self {
+        match policy {
+            RoundingPolicy::LargestRemainder => settlement::RoundingPolicy::LargestRemainder,
+            RoundingPolicy::House => settlement::RoundingPolicy::House,
+            RoundingPolicy::EarliestBettor => settlement::RoundingPolicy::EarliestBettor,
+        }
+    }
+}
+
+impl From<settlement::RoundingPolicy> for RoundingPolicy {
+    fn from(policy: settlement::RoundingPolicy) -> Self {
+        match policy {
+            settlement::RoundingPolicy::LargestRemainder => RoundingPolicy::LargestRemainder,
+            settlement::RoundingPolicy::House => RoundingPolicy::House,
+            settlement::RoundingPolicy::EarliestBettor => RoundingPolicy::EarliestBettor,
+        }
+    }
+}
+
+impl From<NoWinnerPolicy> for settlement::NoWinnerPolicy {
+    fn from(policy: NoWinnerPolicy) -> Self {
+        match policy {
+            NoWinnerPolicy::Refund => settlement::NoWinnerPolicy::Refund,
+            NoWinnerPolicy::Jackpot => settlement::NoWinnerPolicy::Jackpot,
+            NoWinnerPolicy::House => settlement::NoWinnerPolicy::House,
+        }
+    }
+}
+
+impl From<settlement::NoWinnerPolicy> for NoWinnerPolicy {
+    fn from(policy: settlement::NoWinnerPolicy) -> Self {
+        match policy {
+            settlement::NoWinnerPolicy::Refund => NoWinnerPolicy::Refund,
+            settlement::NoWinnerPolicy::Jackpot => NoWinnerPolicy::Jackpot,
+            settlement::NoWinnerPolicy::House => NoWinnerPolicy::House,
+        }
+    }
+}
+
+// 設定が保存されていないチャンネルはデフォルトの設定で作成してから返す
+pub(crate) async fn find_or_create_model<C: ConnectionTrait>(
+    db: &C,
+    channel_id: &str,
+) -> Result<Model, RepositoryError> {
+    let model = Entity::find_by_id(channel_id)
+        .one(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    if let Some(model) = model {
+        return Ok(model);
+    }
+
+    default_model(channel_id)
+        .into_active_model()
+        .insert(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))
+}
+
+// channel の行をトランザクションが終わるまでロックして返す。無ければデフォルトの設定で作成する
+pub(crate) async fn lock_or_create_model<C: ConnectionTrait>(
+    db: &C,
+    channel_id: &str,
+) -> Result<Model, RepositoryError> {
+    create_unless_exists(db, channel_id).await?;
+    Entity::find_by_id(channel_id)
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+        .ok_or(RepositoryError::RecordNotFound(
+            "Channel not found".to_string(),
+        ))
+}
+
+// ハウスの収支を増減させる。同じ channel の精算や cash out と同時に更新されても増減が失われないように、DB 上の値に加算する
+pub(crate) async fn add_house_balance<C: ConnectionTrait>(
+    db: &C,
+    channel_id: &str,
+    amount: i32,
+) -> Result<(), RepositoryError> {
+    create_unless_exists(db, channel_id).await?;
+    Entity::update_many()
+        .col_expr(
+            Column::HouseBalance,
+            Expr::col(Column::HouseBalance).add(amount),
+        )
+        .filter(Column::Id.eq(channel_id))
+        .exec(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    Ok(())
+}
+
+// 設定が保存されていなければデフォルトの設定で作成する
+// 同時に作成しようとしても重複して失敗しないよう、既にある行は何も変えずに残す
+async fn create_unless_exists<C: ConnectionTrait>(
+    db: &C,
+    channel_id: &str,
+) -> Result<(), RepositoryError> {
+    Entity::insert(default_model(channel_id).into_active_model())
+        .on_conflict(
+            OnConflict::column(Column::Id)
+                .update_column(Column::Id)
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    Ok(())
+}
+
+fn default_model(channel_id: &str) -> Model {
+    let channel = Channel::default_for(Id::new(channel_id.to_string()));
+    Model {
+        id: channel.id.value,
+        rounding_policy: channel.rounding_policy.into(),
+        house_balance: channel.house_balance,
+        no_winner_policy: channel.no_winner_policy.into(),
+        jackpot: channel.jackpot,
+        commission_percent: channel.commission_percent,
+        match_ttl_hours: channel.match_ttl_hours,
+    }
+}
+
+impl ChannelRepository for DatabaseRepositoryImpl<Channel> {
+    async fn find(&self, channel_id: Id<Channel>) -> Result<Channel, RepositoryError> {
+        let result = Entity::find_by_id(channel_id.value.clone())
+            .one(&self.db.0)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        match result {
+            Some(model) => Ok(model.into()),
+            None => Ok(Channel::default_for(channel_id)),
+        }
+    }
+    async fn upsert(&self, c: UpdateChannel) -> Result<Channel, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Channel, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let model = find_or_create_model(txn, &c.id.value).await?;
+                    let mut channel: ActiveModel = model.into_active_model();
+
+                    if let Some(rounding_policy) = c.rounding_policy {
+                        channel.rounding_policy = Set(rounding_policy.into());
+                    }
+                    if let Some(no_winner_policy) = c.no_winner_policy {
+                        channel.no_winner_policy = Set(no_winner_policy.into());
+                    }
+                    if let Some(commission_percent) = c.commission_percent {
+                        channel.commission_percent = Set(commission_percent);
+                    }
+                    if let Some(match_ttl_hours) = c.match_ttl_hours {
+                        channel.match_ttl_hours = Set(match_ttl_hours);
+                    }
+                    Ok(channel
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                        .into())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+}
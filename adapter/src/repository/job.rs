@@ -0,0 +1,276 @@
+use kernel::{
+    model::{
+        job::{self, Job, JobLease, NewJob},
+        DateTimeUtc, Id,
+    },
+    repository::{error::RepositoryError, job::JobRepository},
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, EntityTrait, IntoActiveModel,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionError, TransactionTrait,
+};
+
+use crate::model::{
+    job::{Column, Entity, Model},
+    sea_orm_active_enums::{JobKind, JobStatus},
+};
+
+use super::DatabaseRepositoryImpl;
+
+impl From<Model> for Job {
+    fn from(model: Model) -> Self {
+        Job {
+            id: Id::new(model.id),
+            kind: model.kind.into(),
+            payload: model.payload,
+            run_at: model.run_at,
+            interval_secs: model.interval_secs,
+            status: model.status.into(),
+            attempts: model.attempts,
+            max_attempts: model.max_attempts,
+            locked_until: model.locked_until,
+            last_error: model.last_error,
+            created_at: model.created_at,
+        }
+    }
+}
+
+impl From<JobKind> for job::JobKind {
+    fn from(kind: JobKind) -> Self {
+        match kind {
+            JobKind::CloseMatch => job::JobKind::CloseMatch,
+            JobKind::RemindMatch => job::JobKind::RemindMatch,
+            JobKind::ExpireMatches => job::JobKind::ExpireMatches,
+        }
+    }
+}
+
+impl From<job::JobKind> for JobKind {
+    fn from(kind: job::JobKind) -> Self {
+        match kind {
+            job::JobKind::CloseMatch => JobKind::CloseMatch,
+            job::JobKind::RemindMatch => JobKind::RemindMatch,
+            job::JobKind::ExpireMatches => JobKind::ExpireMatches,
+        }
+    }
+}
+
+impl From<JobStatus> for job::JobStatus {
+    fn from(status: JobStatus) -> Self {
+        match status {
+            JobStatus::Pending => job::JobStatus::Pending,
+            JobStatus::Running => job::JobStatus::Running,
+            JobStatus::Done => job::JobStatus::Done,
+            JobStatus::Failed => job::JobStatus::Failed,
+        }
+    }
+}
+
+impl From<job::JobStatus> for JobStatus {
+    fn from(status: job::JobStatus) -> Self {
+        match status {
+            job::JobStatus::Pending => JobStatus::Pending,
+            job::JobStatus::Running => JobStatus::Running,
+            job::JobStatus::Done => JobStatus::Done,
+            job::JobStatus::Failed => JobStatus::Failed,
+        }
+    }
+}
+
+// 他の操作と同じトランザクションでジョブを登録する
+pub(crate) async fn insert_job<C: ConnectionTrait>(
+    db: &C,
+    j: NewJob,
+) -> Result<Job, RepositoryError> {
+    Ok(Model {
+        id: j.id.value,
+        kind: j.kind.into(),
+        payload: j.payload,
+        run_at: j.run_at,
+        interval_secs: j.interval_secs,
+        status: JobStatus::Pending,
+        attempts: 0,
+        max_attempts: j.max_attempts,
+        locked_until: None,
+        last_error: None,
+        created_at: j.created_at,
+    }
+    .into_active_model()
+    .insert(db)
+    .await
+    .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+    .into())
+}
+
+// lease を持っているジョブを行をロックして取得する
+// 期限が切れて他の worker が取得し直していた場合は、その worker の結果を上書きしないよう PermissionDenied を返す
+async fn find_leased_model<C: ConnectionTrait>(
+    db: &C,
+    lease: &JobLease,
+) -> Result<Model, RepositoryError> {
+    let model = Entity::find_by_id(&lease.job_id.value)
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+        .ok_or(RepositoryError::RecordNotFound("Job not found".to_string()))?;
+    if !lease.is_held(&model.clone().into()) {
+        return Err(RepositoryError::PermissionDenied(
+            "Job lease is no longer held".to_string(),
+        ));
+    }
+    Ok(model)
+}
+
+impl JobRepository for DatabaseRepositoryImpl<Job> {
+    async fn insert(&self, j: NewJob) -> Result<Job, RepositoryError> {
+        insert_job(&self.db.0, j).await
+    }
+    async fn insert_unless_scheduled(&self, j: NewJob) -> Result<Job, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Job, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let kind: JobKind = j.kind.into();
+                    let scheduled = Entity::find()
+                        .filter(Column::Kind.eq(kind))
+                        .filter(Column::Status.is_in([JobStatus::Pending, JobStatus::Running]))
+                        .lock_exclusive()
+                        .one(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                    match scheduled {
+                        Some(model) => Ok(model.into()),
+                        None => insert_job(txn, j).await,
+                    }
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn claim_due(&self, now: DateTimeUtc, limit: u64) -> Result<Vec<Job>, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Vec<Job>, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    // 複数の worker が同じジョブを取得しないように、行をロックしてから実行中にする
+                    let models = Entity::find()
+                        .filter(
+                            Condition::any()
+                                .add(
+                                    Condition::all()
+                                        .add(Column::Status.eq(JobStatus::Pending))
+                                        .add(Column::RunAt.lte(now)),
+                                )
+                                .add(
+                                    Condition::all()
+                                        .add(Column::Status.eq(JobStatus::Running))
+                                        .add(Column::LockedUntil.lte(now)),
+                                ),
+                        )
+                        .order_by_asc(Column::RunAt)
+                        .limit(limit)
+                        .lock_exclusive()
+                        .all(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    let mut jobs = Vec::new();
+                    for model in models {
+                        // 期限を過ぎた実行中のジョブは、上限まで実行していれば取得せずに失敗させる
+                        if model.status == JobStatus::Running
+                            && model.attempts >= model.max_attempts
+                        {
+                            let schedule = job::schedule_after_failure(&model.clone().into(), now);
+                            let mut job = model.into_active_model();
+                            job.status = Set(schedule.status.into());
+                            job.run_at = Set(schedule.run_at);
+                            job.attempts = Set(schedule.attempts);
+                            job.locked_until = Set(None);
+                            job.last_error = Set(Some("Job lease expired".to_string()));
+                            job.update(txn).await.map_err(|e| {
+                                RepositoryError::UnexpectedError(anyhow::anyhow!(e))
+                            })?;
+                            continue;
+                        }
+                        let attempts = model.attempts + 1;
+                        let mut job = model.into_active_model();
+                        job.status = Set(JobStatus::Running);
+                        job.attempts = Set(attempts);
+                        job.locked_until = Set(Some(job::lease_until(now)));
+                        let job = job
+                            .update(txn)
+                            .await
+                            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                        jobs.push(job.into());
+                    }
+                    Ok(jobs)
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn complete(&self, lease: JobLease, now: DateTimeUtc) -> Result<Job, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Job, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let model = find_leased_model(txn, &lease).await?;
+                    let schedule = job::schedule_after_success(&model.clone().into(), now);
+                    let mut job = model.into_active_model();
+                    job.status = Set(schedule.status.into());
+                    job.run_at = Set(schedule.run_at);
+                    job.attempts = Set(schedule.attempts);
+                    job.locked_until = Set(None);
+                    job.last_error = Set(None);
+                    Ok(job
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                        .into())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn fail(
+        &self,
+        lease: JobLease,
+        error: String,
+        now: DateTimeUtc,
+    ) -> Result<Job, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Job, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let model = find_leased_model(txn, &lease).await?;
+                    let schedule = job::schedule_after_failure(&model.clone().into(), now);
+                    let mut job = model.into_active_model();
+                    job.status = Set(schedule.status.into());
+                    job.run_at = Set(schedule.run_at);
+                    job.attempts = Set(schedule.attempts);
+                    job.locked_until = Set(None);
+                    job.last_error = Set(Some(error));
+                    Ok(job
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                        .into())
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+}
@@ -1,34 +1,272 @@
+use std::collections::HashMap;
+
 use kernel::{
     model::{
-        channel::Channel,
-        r#match::{Match, NewMatch, UpdateMatch, UpdateMatchForLatest},
-        Id,
+        bet::{self, Bet, Refund},
+        candidate::Candidate,
+        channel::{Channel, DEFAULT_MATCH_TTL_HOURS},
+        job::{JobKind, NewJob, DEFAULT_MAX_ATTEMPTS},
+        r#match::{
+            self, CancelMatchForLatest, Match, MatchEvent, NewMatch, Participation, RefundedMatch,
+            UpdateMatch, UpdateMatchForLatest, VoidMatchForLatest,
+        },
+        settlement::{self, SettleMatchForLatest, SettledMatch, Settlement},
+        statistic, DateTimeUtc, Id,
     },
     repository::{error::RepositoryError, r#match::MatchRepository},
 };
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder,
-    Set, TransactionError, TransactionTrait, TryIntoModel,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, IntoActiveModel,
+    QueryFilter, QueryOrder, QuerySelect, Set, SqlErr, TransactionError, TransactionTrait,
+    TryIntoModel,
 };
 
-use crate::model::r#match::{ActiveModel, Column, Entity, Model};
+use crate::model::{
+    r#match::{ActiveModel, Column, Entity, Model},
+    sea_orm_active_enums::{BetType, MarketType, MatchStatus},
+};
 
-use super::DatabaseRepositoryImpl;
+use super::{
+    channel::lock_or_create_model, job::insert_job, parlay::resolve_parlay_legs,
+    position::refund_positions, user::add_balance, DatabaseRepositoryImpl,
+};
 
 impl From<Model> for Match {
     fn from(model: Model) -> Self {
-        Match::new(
-            Id::new(model.id),
-            model.title,
-            Id::new(model.channel_id),
-            model.message_id.map(Id::new),
-            model.created_at,
-            model.closed_at,
-            model.winner_candidate_id.map(Id::new),
-        )
+        Match {
+            id: Id::new(model.id),
+            number: model.number,
+            title: model.title,
+            channel_id: Id::new(model.channel_id),
+            message_id: model.message_id.map(Id::new),
+            created_at: model.created_at,
+            close_at: model.close_at,
+            closed_at: model.closed_at,
+            winner_candidate_id: model.winner_candidate_id.map(Id::new),
+            cancelled_at: model.cancelled_at,
+            voided_at: model.voided_at,
+            status: model.status.into(),
+            market_type: model.market_type.into(),
+            over_under_line: model.over_under_line,
+            result_value: model.result_value,
+            liquidity: model.liquidity,
+            min_bettors: model.min_bettors,
+            min_candidates: model.min_candidates,
+        }
+    }
+}
+
+impl From<MatchStatus> for r#match::MatchStatus {
+    fn from(status: MatchStatus) -> Self {
+        match status {
+            MatchStatus::Open => r#match::MatchStatus::Open,
+            MatchStatus::Closed => r#match::MatchStatus::Closed,
+            MatchStatus::Settled => r#match::MatchStatus::Settled,
+            MatchStatus::Cancelled => r#match::MatchStatus::Cancelled,
+            MatchStatus::Void => r#match::MatchStatus::Void,
+        }
     }
 }
 
+impl From<r#match::MatchStatus> for MatchStatus {
+    fn from(status: r#match::MatchStatus) -> Self {
+        match status {
+            r#match::MatchStatus::Open => MatchStatus::Open,
+            r#match::MatchStatus::Closed => MatchStatus::Closed,
+            r#match::MatchStatus::Settled => MatchStatus::Settled,
+            r#match::MatchStatus::Cancelled => MatchStatus::Cancelled,
+            r#match::MatchStatus::Void => MatchStatus::Void,
+        }
+    }
+}
+
+impl From<MarketType> for r#match::MarketType {
+    fn from(market_type: MarketType) -> Self {
+        match market_type {
+            MarketType::Parimutuel => r#match::MarketType::Parimutuel,
+            MarketType::FixedOdds => r#match::MarketType::FixedOdds,
+            MarketType::ClosestGuess => r#match::MarketType::ClosestGuess,
+            MarketType::Lmsr => r#match::MarketType::Lmsr,
+        }
+    }
+}
+
+impl From<r#match::MarketType> for MarketType {
+    fn from(market_type: r#match::MarketType) -> Self {
+        match market_type {
+            r#match::MarketType::Parimutuel => MarketType::Parimutuel,
+            r#match::MarketType::FixedOdds => MarketType::FixedOdds,
+            r#match::MarketType::ClosestGuess => MarketType::ClosestGuess,
+            r#match::MarketType::Lmsr => MarketType::Lmsr,
+        }
+    }
+}
+
+// channel で操作の対象になる match を取得する。状態の判定は呼び出し側で transition を通して行う
+// 番号が指定されていればその match を、なければ進行中の唯一の match か、進行中のものがなければ最も新しい match を返す
+// 進行中の match が複数ある場合は番号の指定が必要なので AmbiguousMatch を返す
+// 精算とキャンセルのように同じ match を同時に操作しても二重に処理しないよう、トランザクションが終わるまで行をロックする
+pub(crate) async fn find_latest_model<C: ConnectionTrait>(
+    db: &C,
+    channel_id: &str,
+    match_number: Option<i32>,
+) -> Result<Model, RepositoryError> {
+    let not_found =
+        || RepositoryError::RecordNotFound("Match with the same channel_id not found".to_string());
+    if let Some(number) = match_number {
+        return Entity::find()
+            .filter(Column::ChannelId.eq(channel_id))
+            .filter(Column::Number.eq(number))
+            .lock_exclusive()
+            .one(db)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+            .ok_or_else(not_found);
+    }
+
+    let mut ongoing = Entity::find()
+        .filter(Column::ChannelId.eq(channel_id))
+        .filter(Column::Status.is_in([MatchStatus::Open, MatchStatus::Closed]))
+        .lock_exclusive()
+        .all(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    match ongoing.len() {
+        0 => Entity::find()
+            .filter(Column::ChannelId.eq(channel_id))
+            .order_by_desc(Column::CreatedAt)
+            .lock_exclusive()
+            .one(db)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+            .ok_or_else(not_found),
+        1 => Ok(ongoing.remove(0)),
+        _ => Err(RepositoryError::AmbiguousMatch),
+    }
+}
+
+// 締め切り時刻を過ぎた match は、自動で締め切られる前でも締め切り済みとして扱う
+pub(crate) fn current_status(model: &Model) -> r#match::MatchStatus {
+    let now = DateTimeUtc::from(std::time::SystemTime::now());
+    match model.status.clone().into() {
+        r#match::MatchStatus::Open if model.close_at.is_some_and(|close_at| close_at <= now) => {
+            r#match::MatchStatus::Closed
+        }
+        status => status,
+    }
+}
+
+// transition を通して match の状態を進める。許可されていない操作の場合は InvalidTransition を返す
+pub(crate) fn apply_event(
+    match_: &mut ActiveModel,
+    event: MatchEvent,
+) -> Result<(), RepositoryError> {
+    let next = r#match::transition(match_.status.clone().unwrap().into(), event)?;
+    match_.status = Set(next.into());
+    Ok(())
+}
+
+// bet したタイミングで差し引いたポイントをそれぞれのユーザーに返却する
+pub(crate) async fn refund_bets<C: ConnectionTrait>(
+    db: &C,
+    match_id: &str,
+) -> Result<Vec<Refund>, RepositoryError> {
+    let bets = crate::model::bet::Entity::find()
+        .filter(crate::model::bet::Column::MatchId.eq(match_id))
+        .all(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    let mut refunds = Vec::new();
+    for bet in bets {
+        let balance = add_balance(db, &bet.user_id, bet.amount).await?;
+        refunds.push(Refund::new(Id::new(bet.user_id), bet.amount, balance));
+    }
+    Ok(refunds)
+}
+
+// start で指定された参加の条件を満たしているか
+// LMSR の賭けでは株を持っているユーザーが、その株の候補に bet しているものとして数える
+async fn meets_threshold<C: ConnectionTrait>(
+    db: &C,
+    model: &Model,
+) -> Result<bool, RepositoryError> {
+    if model.min_bettors.is_none() && model.min_candidates.is_none() {
+        return Ok(true);
+    }
+    let stakes = match model.market_type {
+        MarketType::Lmsr => crate::model::position::Entity::find()
+            .filter(crate::model::position::Column::MatchId.eq(&model.id))
+            .filter(crate::model::position::Column::Shares.gt(0))
+            .all(db)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+            .into_iter()
+            .map(|p| (p.user_id, p.candidate_id))
+            .collect::<Vec<_>>(),
+        _ => crate::model::bet::Entity::find()
+            .filter(crate::model::bet::Column::MatchId.eq(&model.id))
+            .filter(crate::model::bet::Column::Amount.gt(0))
+            .all(db)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+            .into_iter()
+            .map(|b| (b.user_id, b.candidate_id))
+            .collect::<Vec<_>>(),
+    };
+    let stakes = stakes
+        .iter()
+        .map(|(user, candidate)| (user.as_str(), candidate.as_str()))
+        .collect::<Vec<_>>();
+    Ok(r#match::meets_participation(
+        model.min_bettors,
+        model.min_candidates,
+        &stakes,
+    ))
+}
+
+// 参加の条件を満たしていない match を無効にしてポイントを返却する。受付中の場合は締め切ってから無効にする
+async fn void_below_threshold<C: ConnectionTrait>(
+    db: &C,
+    mut match_: ActiveModel,
+    now: DateTimeUtc,
+) -> Result<RefundedMatch, RepositoryError> {
+    let match_id = match_.id.clone().unwrap();
+    if match_.status.clone().unwrap() == MatchStatus::Open {
+        apply_event(&mut match_, MatchEvent::Close)?;
+        match_.closed_at = Set(Some(now));
+    }
+    apply_event(&mut match_, MatchEvent::Void)?;
+    match_.voided_at = Set(Some(now));
+
+    let mut refunds = refund_bets(db, &match_id).await?;
+    refunds.extend(refund_positions(db, &match_id).await?);
+    let parlays = resolve_parlay_legs(db, &match_id, None, now).await?;
+
+    let match_ = match_
+        .update(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    Ok(RefundedMatch::new(match_.into(), refunds, parlays))
+}
+
+// チャンネルの設定した時間が経っても終了していないか確かめる
+// 設定が保存されていないチャンネルはデフォルトの時間で判定する
+async fn is_expired<C: ConnectionTrait>(
+    db: &C,
+    model: &Model,
+    now: DateTimeUtc,
+) -> Result<bool, RepositoryError> {
+    let ttl_hours = crate::model::channel::Entity::find_by_id(&model.channel_id)
+        .one(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+        .map_or(DEFAULT_MATCH_TTL_HOURS, |c| c.match_ttl_hours);
+    Ok(
+        r#match::expires_at(model.created_at, model.close_at, ttl_hours)
+            .is_some_and(|expires_at| expires_at <= now),
+    )
+}
+
 impl TryFrom<ActiveModel> for Match {
     type Error = RepositoryError;
 
@@ -46,27 +284,37 @@ impl MatchRepository for DatabaseRepositoryImpl<Match> {
             .0
             .transaction::<_, Match, RepositoryError>(|txn| {
                 Box::pin(async move {
-                    // 同じ channel_id で finished_at が null の match が存在する場合はエラー
-                    let exists = Entity::find()
+                    // 番号は channel ごとに 1 から順に振る
+                    // 同時に作成された match に同じ番号を振らないよう、channel の行をロックしてから最大の番号を調べる
+                    lock_or_create_model(txn, &m.channel_id.value).await?;
+                    let number = Entity::find()
                         .filter(Column::ChannelId.eq(&m.channel_id.value.to_string()))
-                        .filter(Column::WinnerCandidateId.is_null())
-                        .all(txn)
+                        .order_by_desc(Column::Number)
+                        .one(txn)
                         .await
-                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
-                    if exists.len() > 0 {
-                        return Err(RepositoryError::DuplicatedRecord(
-                            "Match with the same channel_id already exists".to_string(),
-                        ));
-                    }
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                        .map(|latest| latest.number + 1)
+                        .unwrap_or(1);
 
                     let model = Model {
                         id: m.id.value.to_string(),
+                        number,
                         title: m.title,
                         channel_id: m.channel_id.value.to_string(),
                         message_id: None,
                         created_at: m.created_at,
+                        close_at: m.close_at,
                         closed_at: None,
                         winner_candidate_id: None,
+                        cancelled_at: None,
+                        voided_at: None,
+                        status: MatchStatus::Open,
+                        market_type: m.market_type.into(),
+                        over_under_line: m.over_under_line,
+                        result_value: None,
+                        liquidity: m.liquidity,
+                        min_bettors: m.min_bettors,
+                        min_candidates: m.min_candidates,
                     };
 
                     let result = model
@@ -75,6 +323,38 @@ impl MatchRepository for DatabaseRepositoryImpl<Match> {
                         .await
                         .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
 
+                    // 締め切り時刻が指定されていれば、その時刻に締め切るジョブと、その少し前にリマインドを送るジョブを登録する
+                    if let Some(close_at) = m.close_at {
+                        if let Some(remind_at) = r#match::remind_at(close_at, m.created_at) {
+                            insert_job(
+                                txn,
+                                NewJob::new(
+                                    Id::gen(),
+                                    JobKind::RemindMatch,
+                                    m.id.value.clone(),
+                                    remind_at,
+                                    None,
+                                    DEFAULT_MAX_ATTEMPTS,
+                                    m.created_at,
+                                ),
+                            )
+                            .await?;
+                        }
+                        insert_job(
+                            txn,
+                            NewJob::new(
+                                Id::gen(),
+                                JobKind::CloseMatch,
+                                m.id.value,
+                                close_at,
+                                None,
+                                DEFAULT_MAX_ATTEMPTS,
+                                m.created_at,
+                            ),
+                        )
+                        .await?;
+                    }
+
                     result.try_into()
                 })
             })
@@ -119,70 +399,39 @@ impl MatchRepository for DatabaseRepositoryImpl<Match> {
                 _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
             })
     }
-    async fn update_for_latest(&self, m: UpdateMatchForLatest) -> Result<Match, RepositoryError> {
+    async fn update_for_latest(
+        &self,
+        m: UpdateMatchForLatest,
+    ) -> Result<Participation<Match>, RepositoryError> {
         self.db
             .0
-            .transaction::<_, Match, RepositoryError>(|txn| {
+            .transaction::<_, Participation<Match>, RepositoryError>(|txn| {
                 Box::pin(async move {
-                    let channel_id = m.channel_id;
-                    let model = Entity::find()
-                        .filter(Column::ChannelId.eq(&channel_id.value))
-                        .order_by_desc(Column::CreatedAt)
-                        .one(txn)
-                        .await
-                        .map_err(|e| match e {
-                            DbErr::RecordNotFound(str) => RepositoryError::RecordNotFound(str),
-                            _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
-                        })?;
-
-                    let model = model.ok_or(RepositoryError::RecordNotFound(
-                        "Match with the same channel_id not found".to_string(),
-                    ))?;
-                    let match_id = model.id.clone();
+                    let model = find_latest_model(txn, &m.channel_id.value, m.match_number).await?;
+                    let meets = m.closed_at.is_none() || meets_threshold(txn, &model).await?;
                     let mut match_ = model.into_active_model();
 
                     if let Some(closed_at) = m.closed_at {
-                        if match_.closed_at.is_set() {
-                            return Err(RepositoryError::DuplicatedRecord(
-                                "Match is already closed".to_string(),
-                            ));
-                        }
+                        apply_event(&mut match_, MatchEvent::Close)?;
                         match_.closed_at = Set(closed_at);
                     }
-                    if let Some(winner_candidate_name) = m.winner_candidate_name {
-                        if match_.winner_candidate_id.is_set() {
-                            return Err(RepositoryError::DuplicatedRecord(
-                                "Winner candidate is already set".to_string(),
-                            ));
-                        }
-                        match winner_candidate_name {
-                            Some(name) => {
-                                let candidate = crate::model::candidate::Entity::find()
-                                    .filter(crate::model::candidate::Column::MatchId.eq(match_id))
-                                    .filter(crate::model::candidate::Column::Name.eq(name))
-                                    .one(txn)
-                                    .await
-                                    .map_err(|e| {
-                                        RepositoryError::UnexpectedError(anyhow::anyhow!(e))
-                                    })?
-                                    .ok_or(RepositoryError::RecordNotFound(
-                                        "Candidate not found".to_string(),
-                                    ))?;
-                                match_.winner_candidate_id = Set(Some(candidate.id));
-                                return Err(RepositoryError::DuplicatedRecord(
-                                    "Winner candidate is already set".to_string(),
-                                ));
-                            }
-                            None => {
-                                match_.winner_candidate_id = Set(None);
-                            }
-                        }
+                    // 締め切った時点で参加の条件を満たしていなければ、そのまま無効にする
+                    if !meets {
+                        let now = m
+                            .closed_at
+                            .flatten()
+                            .unwrap_or_else(|| DateTimeUtc::from(std::time::SystemTime::now()));
+                        return Ok(Participation::Voided(Box::new(
+                            void_below_threshold(txn, match_, now).await?,
+                        )));
                     }
-                    Ok(match_
-                        .update(txn)
-                        .await
-                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
-                        .into())
+                    Ok(Participation::Met(
+                        match_
+                            .update(txn)
+                            .await
+                            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                            .into(),
+                    ))
                 })
             })
             .await
@@ -203,40 +452,395 @@ impl MatchRepository for DatabaseRepositoryImpl<Match> {
             None => Ok(None),
         }
     }
-    async fn find_latest(&self, channel_id: Id<Channel>) -> Result<Option<Match>, RepositoryError> {
-        let result = Entity::find()
-            .filter(Column::ChannelId.eq(&channel_id.value.to_string()))
-            .order_by_desc(Column::CreatedAt)
-            .one(&self.db.0)
-            .await
-            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
-
-        match result {
-            Some(model) => Ok(Some(model.into())),
-            None => Ok(None),
+    async fn find_latest(
+        &self,
+        channel_id: Id<Channel>,
+        match_number: Option<i32>,
+    ) -> Result<Option<Match>, RepositoryError> {
+        match find_latest_model(&self.db.0, &channel_id.value, match_number).await {
+            Ok(model) => Ok(Some(model.into())),
+            Err(RepositoryError::RecordNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
         }
     }
-    async fn delete_latest(&self, channel_id: Id<Channel>) -> Result<(), RepositoryError> {
-        let model = Entity::find()
-            .filter(Column::ChannelId.eq(&channel_id.value.to_string()))
-            .filter(Column::WinnerCandidateId.is_null())
-            .order_by_desc(Column::CreatedAt)
-            .one(&self.db.0)
-            .await
-            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    async fn settle_latest(
+        &self,
+        m: SettleMatchForLatest,
+    ) -> Result<Participation<SettledMatch>, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Participation<SettledMatch>, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let model = find_latest_model(txn, &m.channel_id.value, m.match_number).await?;
+                    let match_id = model.id.clone();
+                    r#match::transition(model.status.clone().into(), MatchEvent::Settle)?;
+                    // 参加の条件を満たしていない場合は、精算せずに無効にしてポイントを返却する
+                    if !meets_threshold(txn, &model).await? {
+                        return Ok(Participation::Voided(Box::new(
+                            void_below_threshold(txn, model.into_active_model(), m.settled_at)
+                                .await?,
+                        )));
+                    }
+                    // over/under の賭けで結果が基準値と等しい場合は引き分け (push) にする
+                    let is_over_under_push =
+                        model.over_under_line.is_some() && m.result_value == model.over_under_line;
+                    let mut match_ = model.into_active_model();
+                    apply_event(&mut match_, MatchEvent::Settle)?;
 
-        match model {
-            Some(model) => {
-                model
+                    let candidates = crate::model::candidate::Entity::find()
+                        .filter(crate::model::candidate::Column::MatchId.eq(&match_id))
+                        .all(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                    let mut winner_ids = Vec::new();
+                    for name in m.winner_candidate_names.iter() {
+                        let winner = candidates.iter().find(|c| &c.name == name).ok_or(
+                            RepositoryError::RecordNotFound("Candidate not found".to_string()),
+                        )?;
+                        if !winner_ids.contains(&winner.id) {
+                            winner_ids.push(winner.id.clone());
+                        }
+                    }
+                    let mut placing_ids = Vec::new();
+                    for name in m.placing_candidate_names.iter() {
+                        let candidate = candidates.iter().find(|c| &c.name == name).ok_or(
+                            RepositoryError::RecordNotFound("Candidate not found".to_string()),
+                        )?;
+                        placing_ids.push(candidate.id.clone());
+                    }
+                    // ハンディキャップのある賭けで得点が指定された場合は、ハンディキャップを加えた得点で勝者を決める
+                    let mut scored = Vec::new();
+                    for (name, score) in m.scores.iter() {
+                        let candidate = candidates.iter().find(|c| &c.name == name).ok_or(
+                            RepositoryError::RecordNotFound("Candidate not found".to_string()),
+                        )?;
+                        let mut candidate: Candidate = candidate.clone().into();
+                        candidate.score = Some(*score);
+                        scored.push(candidate);
+                    }
+                    let is_push = is_over_under_push
+                        || !scored.is_empty()
+                            && match settlement::handicap_winner(&scored) {
+                                Some(winner) => {
+                                    winner_ids = vec![winner.id.value.clone()];
+                                    false
+                                }
+                                None => true,
+                            };
+
+                    // match_id のユニーク制約により、同じ match が二重に精算されることはない
+                    let settlement = crate::model::settlement::Model {
+                        id: m.id.value,
+                        match_id: match_id.clone(),
+                        created_at: m.settled_at,
+                    }
                     .into_active_model()
-                    .delete(&self.db.0)
+                    .insert(txn)
                     .await
-                    .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
-                Ok(())
-            }
-            None => Err(RepositoryError::RecordNotFound(
-                "Match with the same channel_id not found".to_string(),
-            )),
+                    .map_err(|e| match e.sql_err() {
+                        Some(SqlErr::UniqueConstraintViolation(s)) => {
+                            RepositoryError::DuplicatedRecord(s)
+                        }
+                        _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+                    })?;
+
+                    if match_.closed_at.as_ref().is_none() {
+                        match_.closed_at = Set(Some(m.settled_at));
+                    }
+                    if m.result_value.is_some() {
+                        match_.result_value = Set(m.result_value);
+                    }
+                    // 勝者が 1 人に決まった場合のみ match に記録し、デッドヒートは candidate.is_winner で表す
+                    if let [winner_id] = winner_ids.as_slice() {
+                        match_.winner_candidate_id = Set(Some(winner_id.clone()));
+                    }
+                    let match_ = match_
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    for candidate in candidates.iter() {
+                        let mut candidate = candidate.clone().into_active_model();
+                        let is_winner = winner_ids.contains(candidate.id.as_ref());
+                        candidate.is_winner = Set(Some(is_winner as i8));
+                        if let Some(c) =
+                            scored.iter().find(|c| &c.id.value == candidate.id.as_ref())
+                        {
+                            candidate.score = Set(c.score);
+                        }
+                        if let Some(i) = placing_ids
+                            .iter()
+                            .position(|id| id == candidate.id.as_ref())
+                        {
+                            candidate.placing = Set(Some(i as i32 + 1));
+                        }
+                        candidate
+                            .update(txn)
+                            .await
+                            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                    }
+
+                    let bets = crate::model::bet::Entity::find()
+                        .filter(crate::model::bet::Column::MatchId.eq(&match_id))
+                        .all(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                    let placing_ids = placing_ids.into_iter().map(Id::new).collect::<Vec<_>>();
+                    // パーレーの leg は引き分けの場合に無効にする
+                    let leg_winner_ids = if is_push {
+                        Vec::new()
+                    } else {
+                        winner_ids.iter().cloned().map(Id::new).collect::<Vec<_>>()
+                    };
+                    let all_bets = bets.iter().map(|b| b.clone().into()).collect::<Vec<Bet>>();
+                    // 1 着を当てる bet 以外は、それぞれの bet_type のプールで別に精算する
+                    let other_bets = bets
+                        .iter()
+                        .filter(|b| b.bet_type != BetType::Win)
+                        .map(|b| b.clone().into())
+                        .collect::<Vec<_>>();
+                    let statistics = statistic::new_statistics(
+                        bets.into_iter().map(|b| b.into()).collect(),
+                        candidates.into_iter().map(|c| c.into()).collect(),
+                    );
+
+                    // bet したタイミングでポイントを差し引いているので、払い戻しだけを加算する
+                    // 同じ channel の他の精算が持ち越しのポイントを二重に使わないよう、channel の行をロックして読む
+                    let channel = lock_or_create_model(txn, &m.channel_id.value).await?;
+                    let distribution = match (match_.market_type.clone(), m.result_value) {
+                        // 引き分け (push) の場合は全ての bet を返却する
+                        _ if is_push => {
+                            settlement::new_push_distribution(&all_bets, &channel.clone().into())
+                        }
+                        // 数値を予想する賭けは結果の値に最も近い予想で分ける
+                        (MarketType::ClosestGuess, Some(result)) => {
+                            settlement::new_closest_guess_distribution(
+                                &other_bets,
+                                result,
+                                &channel.clone().into(),
+                            )
+                        }
+                        (MarketType::ClosestGuess, None) => {
+                            return Err(RepositoryError::RecordNotFound(
+                                "Result value not found".to_string(),
+                            ))
+                        }
+                        // LMSR の賭けは bet ではなく保有している株で払い戻す
+                        (MarketType::Lmsr, _) => {
+                            let positions = crate::model::position::Entity::find()
+                                .filter(crate::model::position::Column::MatchId.eq(&match_id))
+                                .all(txn)
+                                .await
+                                .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                                .into_iter()
+                                .map(|p| p.into())
+                                .collect::<Vec<_>>();
+                            settlement::new_lmsr_distribution(
+                                &positions,
+                                &winner_ids.into_iter().map(Id::new).collect::<Vec<_>>(),
+                                &channel.clone().into(),
+                            )
+                        }
+                        (market_type, _) => {
+                            let distribution = settlement::new_distribution(
+                                &statistics,
+                                &winner_ids.into_iter().map(Id::new).collect::<Vec<_>>(),
+                                market_type.into(),
+                                &channel.clone().into(),
+                            );
+                            // 着順を当てる bet は bet_type ごとに別のプールで精算する
+                            [bet::BetType::Exacta, bet::BetType::Trifecta]
+                                .into_iter()
+                                .fold(distribution, |distribution, bet_type| {
+                                    distribution.merge(settlement::new_ordered_distribution(
+                                        &other_bets,
+                                        bet_type,
+                                        &placing_ids,
+                                        &channel.clone().into(),
+                                    ))
+                                })
+                        }
+                    };
+                    for payout in distribution.payouts.iter().filter(|p| p.amount > 0) {
+                        add_balance(txn, &payout.user_id.value, payout.amount).await?;
+                    }
+
+                    // 持ち越されていたポイントはプールに含めたので、新たに持ち越すポイントで置き換える
+                    let house_balance = channel.house_balance + distribution.house;
+                    let mut channel = channel.into_active_model();
+                    channel.house_balance = Set(house_balance);
+                    channel.jackpot = Set(distribution.rollover);
+                    channel
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    // channel のハウスの収支を更新した後で、パーレーの払い戻しをハウスの収支に反映する
+                    let parlays = resolve_parlay_legs(
+                        txn,
+                        &match_id,
+                        Some((&leg_winner_ids, &statistics, &distribution)),
+                        m.settled_at,
+                    )
+                    .await?;
+
+                    Ok(Participation::Met(SettledMatch::new(
+                        match_.into(),
+                        Settlement::new(
+                            Id::new(settlement.id),
+                            Id::new(settlement.match_id),
+                            settlement.created_at,
+                        ),
+                        distribution,
+                        parlays,
+                    )))
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn cancel_latest(
+        &self,
+        m: CancelMatchForLatest,
+    ) -> Result<RefundedMatch, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, RefundedMatch, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let model = find_latest_model(txn, &m.channel_id.value, m.match_number).await?;
+                    let match_id = model.id.clone();
+                    let mut match_ = model.into_active_model();
+                    apply_event(&mut match_, MatchEvent::Cancel)?;
+                    match_.cancelled_at = Set(Some(m.cancelled_at));
+
+                    let mut refunds = refund_bets(txn, &match_id).await?;
+                    refunds.extend(refund_positions(txn, &match_id).await?);
+                    let parlays = resolve_parlay_legs(txn, &match_id, None, m.cancelled_at).await?;
+
+                    let match_ = match_
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    Ok(RefundedMatch::new(match_.into(), refunds, parlays))
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn void_latest(&self, m: VoidMatchForLatest) -> Result<RefundedMatch, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, RefundedMatch, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let model = find_latest_model(txn, &m.channel_id.value, m.match_number).await?;
+                    let match_id = model.id.clone();
+                    let mut match_ = model.into_active_model();
+                    apply_event(&mut match_, MatchEvent::Void)?;
+                    match_.voided_at = Set(Some(m.voided_at));
+
+                    let mut refunds = refund_bets(txn, &match_id).await?;
+                    refunds.extend(refund_positions(txn, &match_id).await?);
+                    let parlays = resolve_parlay_legs(txn, &match_id, None, m.voided_at).await?;
+
+                    let match_ = match_
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    Ok(RefundedMatch::new(match_.into(), refunds, parlays))
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn find_expired(&self, now: DateTimeUtc) -> Result<Vec<Match>, RepositoryError> {
+        // 期限を設定している channel の match だけを見れば良いので、先に channel を 1 回で取得する
+        let ttl_hours = crate::model::channel::Entity::find()
+            .filter(crate::model::channel::Column::MatchTtlHours.gt(0))
+            .all(&self.db.0)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+            .into_iter()
+            .map(|c| (c.id, c.match_ttl_hours))
+            .collect::<HashMap<_, _>>();
+        if ttl_hours.is_empty() {
+            return Ok(Vec::new());
         }
+
+        let models = Entity::find()
+            .filter(Column::Status.is_in([MatchStatus::Open, MatchStatus::Closed]))
+            .filter(Column::ChannelId.is_in(ttl_hours.keys().cloned()))
+            .order_by_asc(Column::CreatedAt)
+            .all(&self.db.0)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        Ok(models
+            .into_iter()
+            .filter(|model| {
+                r#match::expires_at(
+                    model.created_at,
+                    model.close_at,
+                    ttl_hours[&model.channel_id],
+                )
+                .is_some_and(|expires_at| expires_at <= now)
+            })
+            .map(|model| model.into())
+            .collect())
+    }
+    async fn void_expired(
+        &self,
+        match_id: Id<Match>,
+        now: DateTimeUtc,
+    ) -> Result<Option<RefundedMatch>, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Option<RefundedMatch>, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    // 探してから無効にするまでに終了したり期限が延びたりしていないか、行をロックして確かめる
+                    let Some(model) = Entity::find_by_id(&match_id.value)
+                        .filter(Column::Status.is_in([MatchStatus::Open, MatchStatus::Closed]))
+                        .lock_exclusive()
+                        .one(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                    else {
+                        return Ok(None);
+                    };
+                    if !is_expired(txn, &model, now).await? {
+                        return Ok(None);
+                    }
+
+                    let match_id = model.id.clone();
+                    let mut match_ = model.into_active_model();
+                    apply_event(&mut match_, MatchEvent::Expire)?;
+                    match_.voided_at = Set(Some(now));
+
+                    let mut refunds = refund_bets(txn, &match_id).await?;
+                    refunds.extend(refund_positions(txn, &match_id).await?);
+                    let parlays = resolve_parlay_legs(txn, &match_id, None, now).await?;
+
+                    let match_ = match_
+                        .update(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                    Ok(Some(RefundedMatch::new(match_.into(), refunds, parlays)))
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
     }
 }
@@ -6,7 +6,12 @@ use crate::persistence::mariadb::Db;
 
 pub mod bet;
 pub mod candidate;
+pub mod challenge;
+pub mod channel;
+pub mod job;
 pub mod r#match;
+pub mod parlay;
+pub mod position;
 pub mod user;
 
 #[derive(new)]
@@ -0,0 +1,261 @@
+use kernel::{
+    model::{
+        candidate::Candidate,
+        parlay::{self, NewParlay, Parlay, ParlayLeg, ParlaySlip},
+        r#match::{self, MatchEvent},
+        settlement::Distribution,
+        statistic::Statistic,
+        DateTimeUtc, Id,
+    },
+    repository::{error::RepositoryError, parlay::ParlayRepository},
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel, QueryFilter, Set,
+    SqlErr, TransactionError, TransactionTrait,
+};
+
+use crate::model::{
+    parlay::{Entity, Model},
+    parlay_leg,
+    sea_orm_active_enums::{LegResult, ParlayStatus},
+};
+
+use super::{
+    channel::add_house_balance,
+    r#match::{current_status, find_latest_model},
+    user::add_balance,
+    DatabaseRepositoryImpl,
+};
+
+impl From<Model> for Parlay {
+    fn from(model: Model) -> Self {
+        Parlay {
+            id: Id::new(model.id),
+            channel_id: Id::new(model.channel_id),
+            user_id: Id::new(model.user_id),
+            amount: model.amount,
+            status: model.status.into(),
+            payout: model.payout,
+            created_at: model.created_at,
+            settled_at: model.settled_at,
+        }
+    }
+}
+
+impl From<parlay_leg::Model> for ParlayLeg {
+    fn from(model: parlay_leg::Model) -> Self {
+        ParlayLeg {
+            id: Id::new(model.id),
+            parlay_id: Id::new(model.parlay_id),
+            match_id: Id::new(model.match_id),
+            candidate_id: Id::new(model.candidate_id),
+            result: model.result.into(),
+            odds: model.odds,
+        }
+    }
+}
+
+impl From<ParlayStatus> for parlay::ParlayStatus {
+    fn from(status: ParlayStatus) -> Self {
+        match status {
+            ParlayStatus::Open => parlay::ParlayStatus::Open,
+            ParlayStatus::Won => parlay::ParlayStatus::Won,
+            ParlayStatus::Lost => parlay::ParlayStatus::Lost,
+            ParlayStatus::Refunded => parlay::ParlayStatus::Refunded,
+        }
+    }
+}
+
+impl From<parlay::ParlayStatus> for ParlayStatus {
+    fn from(status: parlay::ParlayStatus) -> Self {
+        match status {
+            parlay::ParlayStatus::Open => ParlayStatus::Open,
+            parlay::ParlayStatus::Won => ParlayStatus::Won,
+            parlay::ParlayStatus::Lost => ParlayStatus::Lost,
+            parlay::ParlayStatus::Refunded => ParlayStatus::Refunded,
+        }
+    }
+}
+
+impl From<LegResult> for parlay::LegResult {
+    fn from(result: LegResult) -> Self {
+        match result {
+            LegResult::Pending => parlay::LegResult::Pending,
+            LegResult::Won => parlay::LegResult::Won,
+            LegResult::Lost => parlay::LegResult::Lost,
+            LegResult::Void => parlay::LegResult::Void,
+        }
+    }
+}
+
+impl From<parlay::LegResult> for LegResult {
+    fn from(result: parlay::LegResult) -> Self {
+        match result {
+            parlay::LegResult::Pending => LegResult::Pending,
+            parlay::LegResult::Won => LegResult::Won,
+            parlay::LegResult::Lost => LegResult::Lost,
+            parlay::LegResult::Void => LegResult::Void,
+        }
+    }
+}
+
+// 勝った候補 (引き分けの場合は空) と精算の結果
+type SettledLegs<'a> = (&'a [Id<Candidate>], &'a [Statistic], &'a Distribution);
+
+// match の結果が出たときに、その match を含む leg の結果を記録し、全ての leg の結果が出たパーレーを精算する
+// settled が None の場合はキャンセルや無効になったものとして leg を無効にする
+// パーレーの払い戻しはパーレーを作成した channel のハウスが負担する
+pub(crate) async fn resolve_parlay_legs<C: ConnectionTrait>(
+    db: &C,
+    match_id: &str,
+    settled: Option<SettledLegs<'_>>,
+    resolved_at: DateTimeUtc,
+) -> Result<Vec<ParlaySlip>, RepositoryError> {
+    let legs = parlay_leg::Entity::find()
+        .filter(parlay_leg::Column::MatchId.eq(match_id))
+        .filter(parlay_leg::Column::Result.eq(LegResult::Pending))
+        .all(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    let mut parlay_ids = Vec::new();
+    for leg in legs {
+        let (result, odds) = match settled {
+            Some((winner_ids, statistics, distribution)) => parlay::resolve_leg(
+                &Id::new(leg.candidate_id.clone()),
+                winner_ids,
+                statistics,
+                distribution,
+            ),
+            None => (parlay::LegResult::Void, None),
+        };
+        if !parlay_ids.contains(&leg.parlay_id) {
+            parlay_ids.push(leg.parlay_id.clone());
+        }
+        let mut leg = leg.into_active_model();
+        leg.result = Set(result.into());
+        leg.odds = Set(odds);
+        leg.update(db)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    }
+
+    let mut slips = Vec::new();
+    for parlay_id in parlay_ids {
+        let parlay = Entity::find_by_id(&parlay_id)
+            .one(db)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+            .ok_or(RepositoryError::RecordNotFound(
+                "Parlay not found".to_string(),
+            ))?;
+        let legs = parlay_leg::Entity::find()
+            .filter(parlay_leg::Column::ParlayId.eq(&parlay_id))
+            .all(db)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+            .into_iter()
+            .map(|l| l.into())
+            .collect::<Vec<ParlayLeg>>();
+        let Some((status, payout)) = parlay::parlay_outcome(parlay.amount, &legs) else {
+            continue;
+        };
+
+        if payout > 0 {
+            add_balance(db, &parlay.user_id, payout).await?;
+        }
+        // 賭けられたポイントはハウスが預かり、払い戻しとの差額がハウスの収支になる
+        add_house_balance(db, &parlay.channel_id, parlay.amount - payout).await?;
+
+        let mut parlay = parlay.into_active_model();
+        parlay.status = Set(status.into());
+        parlay.payout = Set(Some(payout));
+        parlay.settled_at = Set(Some(resolved_at));
+        let parlay = parlay
+            .update(db)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+        slips.push(ParlaySlip::new(parlay.into(), legs));
+    }
+    Ok(slips)
+}
+
+impl ParlayRepository for DatabaseRepositoryImpl<Parlay> {
+    async fn insert(&self, p: NewParlay) -> Result<ParlaySlip, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, ParlaySlip, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let user = crate::model::user::Entity::find()
+                        .filter(crate::model::user::Column::TraqId.eq(&p.traq_id))
+                        .filter(crate::model::user::Column::ChannelId.eq(&p.channel_id.value))
+                        .one(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                        .ok_or(RepositoryError::RecordNotFound(
+                            "User not found".to_string(),
+                        ))?;
+                    let balance = add_balance(txn, &user.id, -p.amount).await?;
+                    if balance < 0 {
+                        return Err(RepositoryError::InsufficientBalance);
+                    }
+
+                    let parlay = Model {
+                        id: p.id.value,
+                        channel_id: p.channel_id.value,
+                        user_id: user.id,
+                        amount: p.amount,
+                        status: ParlayStatus::Open,
+                        payout: None,
+                        created_at: p.created_at,
+                        settled_at: None,
+                    }
+                    .into_active_model()
+                    .insert(txn)
+                    .await
+                    .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    // leg はそれぞれの channel で bet を受け付けている match から選ぶ
+                    let mut legs = Vec::new();
+                    for leg in p.legs {
+                        let match_ =
+                            find_latest_model(txn, &leg.channel_id.value, leg.match_number).await?;
+                        r#match::transition(current_status(&match_), MatchEvent::Bet)?;
+                        let candidate = crate::model::candidate::Entity::find()
+                            .filter(crate::model::candidate::Column::MatchId.eq(&match_.id))
+                            .filter(crate::model::candidate::Column::Name.eq(&leg.candidate_name))
+                            .one(txn)
+                            .await
+                            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                            .ok_or(RepositoryError::RecordNotFound(
+                                "Candidate not found".to_string(),
+                            ))?;
+                        let leg = parlay_leg::Model {
+                            id: Id::<ParlayLeg>::gen().value,
+                            parlay_id: parlay.id.clone(),
+                            match_id: match_.id,
+                            candidate_id: candidate.id,
+                            result: LegResult::Pending,
+                            odds: None,
+                        }
+                        .into_active_model()
+                        .insert(txn)
+                        .await
+                        .map_err(|e| match e.sql_err() {
+                            Some(SqlErr::UniqueConstraintViolation(s)) => {
+                                RepositoryError::DuplicatedRecord(s)
+                            }
+                            _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+                        })?;
+                        legs.push(leg.into());
+                    }
+
+                    Ok(ParlaySlip::new(parlay.into(), legs))
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+}
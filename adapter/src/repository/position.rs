@@ -0,0 +1,197 @@
+use kernel::{
+    model::{
+        bet::Refund,
+        lmsr::{self, DEFAULT_LIQUIDITY},
+        position::{Position, Trade, TradeForLatestMatch},
+        r#match::{self, Match, MatchEvent},
+        Id,
+    },
+    repository::{error::RepositoryError, position::PositionRepository},
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel, QueryFilter,
+    QueryOrder, Set, TransactionError, TransactionTrait,
+};
+
+use crate::model::position::{Column, Entity, Model};
+
+use super::{
+    r#match::{current_status, find_latest_model},
+    user::add_balance,
+    DatabaseRepositoryImpl,
+};
+
+impl From<Model> for Position {
+    fn from(model: Model) -> Self {
+        Position {
+            id: Id::new(model.id),
+            match_id: Id::new(model.match_id),
+            user_id: Id::new(model.user_id),
+            candidate_id: Id::new(model.candidate_id),
+            shares: model.shares,
+            cost: model.cost,
+            created_at: model.created_at,
+        }
+    }
+}
+
+// キャンセルや無効になった LMSR の賭けで、ユーザーごとの売買の収支を打ち消す
+// 支払ったポイントが多ければその分を返却し、売却で利益が出ていればその分を回収する
+pub(crate) async fn refund_positions<C: ConnectionTrait>(
+    db: &C,
+    match_id: &str,
+) -> Result<Vec<Refund>, RepositoryError> {
+    let positions = Entity::find()
+        .filter(Column::MatchId.eq(match_id))
+        .order_by_asc(Column::CreatedAt)
+        .all(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+    let mut costs: Vec<(String, i64)> = Vec::new();
+    for position in positions {
+        match costs
+            .iter_mut()
+            .find(|(user_id, _)| user_id == &position.user_id)
+        {
+            Some((_, cost)) => *cost += position.cost as i64,
+            None => costs.push((position.user_id, position.cost as i64)),
+        }
+    }
+    let mut refunds = Vec::new();
+    for (user_id, cost) in costs.into_iter().filter(|(_, cost)| *cost != 0) {
+        let cost = i32::try_from(cost)
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+        let balance = add_balance(db, &user_id, cost).await?;
+        refunds.push(Refund::new(Id::new(user_id), cost, balance));
+    }
+    Ok(refunds)
+}
+
+impl PositionRepository for DatabaseRepositoryImpl<Position> {
+    async fn trade_for_latest_match(
+        &self,
+        m: TradeForLatestMatch,
+    ) -> Result<Trade, RepositoryError> {
+        self.db
+            .0
+            .transaction::<_, Trade, RepositoryError>(|txn| {
+                Box::pin(async move {
+                    let match_ = find_latest_model(txn, &m.channel_id, m.match_number).await?;
+                    r#match::transition(current_status(&match_), MatchEvent::Bet)?;
+
+                    let user = crate::model::user::Entity::find()
+                        .filter(crate::model::user::Column::TraqId.eq(&m.traq_id))
+                        .filter(crate::model::user::Column::ChannelId.eq(&m.channel_id))
+                        .one(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+                        .ok_or(RepositoryError::RecordNotFound(
+                            "User not found".to_string(),
+                        ))?;
+                    let candidates = crate::model::candidate::Entity::find()
+                        .filter(crate::model::candidate::Column::MatchId.eq(&match_.id))
+                        .all(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                    let index = candidates
+                        .iter()
+                        .position(|c| c.name == m.candidate_name)
+                        .ok_or(RepositoryError::RecordNotFound(
+                            "Candidate not found".to_string(),
+                        ))?;
+                    let candidate_id = candidates[index].id.clone();
+
+                    // 価格は全てのユーザーが保有している候補ごとの株数の合計で決まる
+                    let positions = Entity::find()
+                        .filter(Column::MatchId.eq(&match_.id))
+                        .all(txn)
+                        .await
+                        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+                    let quantities = candidates
+                        .iter()
+                        .map(|c| {
+                            positions
+                                .iter()
+                                .filter(|p| p.candidate_id == c.id)
+                                .try_fold(0i32, |sum, p| sum.checked_add(p.shares))
+                                .ok_or(RepositoryError::InvalidArgument(
+                                    "Too many shares".to_string(),
+                                ))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let liquidity = match_.liquidity.unwrap_or(DEFAULT_LIQUIDITY);
+                    let current = positions
+                        .into_iter()
+                        .find(|p| p.user_id == user.id && p.candidate_id == candidate_id);
+
+                    // 購入なら支払うポイント、売却なら受け取るポイントを負の値で表す
+                    let points = if m.shares > 0 {
+                        lmsr::buy_cost(&quantities, liquidity, index, m.shares)
+                    } else {
+                        let held = current.as_ref().map(|p| p.shares).unwrap_or_default();
+                        if held < -m.shares {
+                            return Err(RepositoryError::InsufficientShares);
+                        }
+                        lmsr::sell_proceeds(&quantities, liquidity, index, -m.shares).map(|p| -p)
+                    }
+                    .ok_or(RepositoryError::InvalidArgument(
+                        "Too many shares".to_string(),
+                    ))?;
+                    let balance = add_balance(txn, &user.id, -points).await?;
+                    if balance < 0 {
+                        return Err(RepositoryError::InsufficientBalance);
+                    }
+
+                    let position = match current {
+                        Some(position) => {
+                            let shares = position.shares.checked_add(m.shares);
+                            let cost = position.cost.checked_add(points);
+                            let (Some(shares), Some(cost)) = (shares, cost) else {
+                                return Err(RepositoryError::InvalidArgument(
+                                    "Too many shares".to_string(),
+                                ));
+                            };
+                            let mut position = position.into_active_model();
+                            position.shares = Set(shares);
+                            position.cost = Set(cost);
+                            position.update(txn).await
+                        }
+                        None => {
+                            Model {
+                                id: m.id.value,
+                                match_id: match_.id,
+                                user_id: user.id,
+                                candidate_id,
+                                shares: m.shares,
+                                cost: points,
+                                created_at: m.traded_at,
+                            }
+                            .into_active_model()
+                            .insert(txn)
+                            .await
+                        }
+                    }
+                    .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                    Ok(Trade::new(position.into(), points.abs(), balance))
+                })
+            })
+            .await
+            .map_err(|e| match e {
+                TransactionError::Transaction(repo_err) => repo_err,
+                _ => RepositoryError::UnexpectedError(anyhow::anyhow!(e)),
+            })
+    }
+    async fn select_by_match_id(
+        &self,
+        match_id: Id<Match>,
+    ) -> Result<Vec<Position>, RepositoryError> {
+        let result = Entity::find()
+            .filter(Column::MatchId.eq(match_id.value))
+            .all(&self.db.0)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        Ok(result.into_iter().map(|model| model.into()).collect())
+    }
+}
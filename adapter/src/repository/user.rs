@@ -1,14 +1,15 @@
 use kernel::{
     model::{
-        user::{self, FindUser, UpdateBalance, User},
+        user::{self, FindUser, UpdateBalance, UpdateNotify, User},
         Id,
     },
     repository::{error::RepositoryError, user::UserRepository},
 };
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter, Set, TryIntoModel,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel, QueryFilter, Set,
+    TryIntoModel,
 };
-use sea_query::OnConflict;
+use sea_query::{Expr, OnConflict};
 
 use crate::model::user::{ActiveModel, Column, Entity, Model};
 
@@ -22,6 +23,7 @@ impl From<Model> for User {
             model.traq_display_id,
             model.channel_id,
             model.balance,
+            model.notify,
         )
     }
 }
@@ -37,6 +39,32 @@ impl TryFrom<ActiveModel> for User {
     }
 }
 
+// 他の repository のトランザクション内から残高を増減させるためのヘルパー。更新後の残高を返す
+// 同時に更新されても増減が失われないように、読み込んだ残高ではなく DB 上の残高に加算する
+pub(crate) async fn add_balance<C: ConnectionTrait>(
+    db: &C,
+    user_id: &str,
+    amount: i32,
+) -> Result<i32, RepositoryError> {
+    Entity::update_many()
+        .col_expr(Column::Balance, Expr::col(Column::Balance).add(amount))
+        .filter(Column::Id.eq(user_id))
+        .exec(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+    // 更新した行はトランザクションが終わるまでロックされるので、加算した後の残高が読める
+    let user = Entity::find_by_id(user_id)
+        .one(db)
+        .await
+        .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+        .ok_or(RepositoryError::RecordNotFound(
+            "User not found".to_string(),
+        ))?;
+
+    Ok(user.balance)
+}
+
 impl UserRepository for DatabaseRepositoryImpl<user::User> {
     async fn insert(&self, user: user::NewUser) -> Result<User, RepositoryError> {
         let model = Model {
@@ -45,6 +73,7 @@ impl UserRepository for DatabaseRepositoryImpl<user::User> {
             traq_display_id: user.traq_display_id,
             channel_id: user.channel_id,
             balance: user.balance,
+            notify: true,
         };
 
         let result = model
@@ -100,4 +129,24 @@ impl UserRepository for DatabaseRepositoryImpl<user::User> {
 
         Ok(())
     }
+    async fn update_notify(&self, user: UpdateNotify) -> Result<User, RepositoryError> {
+        let model = Entity::find()
+            .filter(Column::TraqId.eq(user.traq_id))
+            .filter(Column::ChannelId.eq(user.channel_id.value))
+            .one(&self.db.0)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?
+            .ok_or(RepositoryError::RecordNotFound(
+                "User not found".to_string(),
+            ))?;
+
+        let mut model = model.into_active_model();
+        model.notify = Set(user.notify);
+        let result = model
+            .update(&self.db.0)
+            .await
+            .map_err(|e| RepositoryError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        Ok(result.into())
+    }
 }
@@ -1,11 +1,25 @@
 use derive_new::new;
-use kernel::model::{bet::NewBetForLatestMatch, Id};
+use kernel::model::{
+    bet::{
+        BetType, CashOutBetForLatestMatch, DeleteBetForLatestMatch, NewBetForLatestMatch,
+        NewOrderedBetForLatestMatch, NewStake, UpdateBetForLatestMatch,
+    },
+    position::TradeForLatestMatch,
+    Id,
+};
 
 #[derive(new)]
 pub struct CreateBet {
     pub channel_id: String,
+    // channel で複数の賭けが進行中の場合に対象を指定する番号
+    pub match_number: Option<i32>,
     pub message_id: String,
     pub traq_id: String,
+    pub stakes: Vec<CreateStake>,
+}
+
+#[derive(new)]
+pub struct CreateStake {
     pub candidate_name: String,
     pub amount: i32,
 }
@@ -13,11 +27,137 @@ pub struct CreateBet {
 impl From<CreateBet> for NewBetForLatestMatch {
     fn from(c: CreateBet) -> Self {
         NewBetForLatestMatch::new(
+            c.traq_id,
+            c.channel_id,
+            c.match_number,
+            c.stakes
+                .into_iter()
+                .map(|s| NewStake::new(Id::gen(), s.candidate_name, s.amount))
+                .collect(),
+            chrono::Utc::now(),
+        )
+    }
+}
+
+#[derive(new)]
+pub struct CreateOrderedBet {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+    pub traq_id: String,
+    pub bet_type: BetType,
+    // 1 着から順に並べた候補名
+    pub candidate_names: Vec<String>,
+    pub amount: i32,
+}
+
+impl From<CreateOrderedBet> for NewOrderedBetForLatestMatch {
+    fn from(c: CreateOrderedBet) -> Self {
+        NewOrderedBetForLatestMatch {
+            id: Id::gen(),
+            traq_id: c.traq_id,
+            channel_id: c.channel_id,
+            match_number: c.match_number,
+            bet_type: c.bet_type,
+            candidate_names: c.candidate_names,
+            amount: c.amount,
+            created_at: chrono::Utc::now(),
+        }
+    }
+}
+
+#[derive(new)]
+pub struct CreateGuessBet {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+    pub traq_id: String,
+    pub guess: String,
+    pub amount: i32,
+}
+
+#[derive(new)]
+pub struct CancelBet {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+    pub traq_id: String,
+    pub candidate_name: Option<String>,
+}
+
+impl From<CancelBet> for DeleteBetForLatestMatch {
+    fn from(c: CancelBet) -> Self {
+        DeleteBetForLatestMatch::new(c.traq_id, c.channel_id, c.match_number, c.candidate_name)
+    }
+}
+
+#[derive(new)]
+pub struct CashOutBet {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+    pub traq_id: String,
+    pub candidate_name: Option<String>,
+    pub confirmed: bool,
+    // 確定する場合に、ユーザーが見た提示額
+    pub accepted_value: Option<i32>,
+}
+
+impl From<CashOutBet> for CashOutBetForLatestMatch {
+    fn from(c: CashOutBet) -> Self {
+        CashOutBetForLatestMatch::new(
+            c.traq_id,
+            c.channel_id,
+            c.match_number,
+            c.candidate_name,
+            c.accepted_value.filter(|_| c.confirmed),
+        )
+    }
+}
+
+#[derive(new)]
+pub struct ChangeBet {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+    pub traq_id: String,
+    pub from_candidate_name: Option<String>,
+    pub candidate_name: String,
+}
+
+impl From<ChangeBet> for UpdateBetForLatestMatch {
+    fn from(c: ChangeBet) -> Self {
+        UpdateBetForLatestMatch::new(
+            c.traq_id,
+            c.channel_id,
+            c.match_number,
+            c.from_candidate_name,
+            c.candidate_name,
+        )
+    }
+}
+
+// LMSR の賭けで株を売買する。shares が正なら購入、負なら売却を表す
+#[derive(new)]
+pub struct TradeShares {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+    pub traq_id: String,
+    pub candidate_name: String,
+    pub shares: i32,
+}
+
+impl From<TradeShares> for TradeForLatestMatch {
+    fn from(c: TradeShares) -> Self {
+        TradeForLatestMatch::new(
             Id::gen(),
             c.traq_id,
             c.channel_id,
+            c.match_number,
             c.candidate_name,
-            c.amount,
+            c.shares,
+            chrono::Utc::now(),
         )
     }
 }
@@ -0,0 +1,51 @@
+use derive_new::new;
+use kernel::model::{challenge::NewChallenge, Id};
+
+#[derive(new)]
+pub struct CreateChallenge {
+    pub channel_id: String,
+    pub message_id: String,
+    pub traq_id: String,
+    // メンションされたユーザー。1 人目が相手で、2 人目がいれば審判になる
+    pub mentioned_traq_ids: Vec<String>,
+    pub amount: i32,
+    pub title: String,
+}
+
+impl From<CreateChallenge> for NewChallenge {
+    fn from(c: CreateChallenge) -> Self {
+        let mut mentioned = c.mentioned_traq_ids.into_iter();
+        NewChallenge {
+            id: Id::gen(),
+            channel_id: Id::new(c.channel_id),
+            title: c.title,
+            amount: c.amount,
+            challenger_traq_id: c.traq_id,
+            opponent_traq_id: mentioned.next().unwrap_or_default(),
+            arbiter_traq_id: mentioned.next(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+}
+
+#[derive(new)]
+pub struct AcceptChallenge {
+    pub channel_id: String,
+    pub message_id: String,
+    pub traq_id: String,
+}
+
+#[derive(new)]
+pub struct DeclineChallenge {
+    pub channel_id: String,
+    pub message_id: String,
+    pub traq_id: String,
+}
+
+#[derive(new)]
+pub struct ResolveChallenge {
+    pub channel_id: String,
+    pub message_id: String,
+    pub traq_id: String,
+    pub winner_traq_id: Option<String>,
+}
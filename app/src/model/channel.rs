@@ -0,0 +1,9 @@
+use derive_new::new;
+
+// key と value が指定されていない場合は現在の設定を表示する
+#[derive(new)]
+pub struct ConfigureChannel {
+    pub channel_id: String,
+    pub key: Option<String>,
+    pub value: Option<String>,
+}
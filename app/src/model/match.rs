@@ -1,6 +1,9 @@
 use derive_new::new;
 use kernel::model::{
-    r#match::{NewMatch, UpdateMatchForLatest},
+    r#match::{
+        CancelMatchForLatest, MarketType, NewMatch, UpdateMatchForLatest, VoidMatchForLatest,
+    },
+    settlement::SettleMatchForLatest,
     Id,
 };
 
@@ -12,39 +15,115 @@ pub struct CreateMatch {
 
 impl From<CreateMatch> for NewMatch {
     fn from(c: CreateMatch) -> Self {
-        NewMatch::new(
-            Id::gen(),
-            c.title,
-            Id::new(c.channel_id),
-            chrono::Utc::now(),
-        )
+        NewMatch {
+            id: Id::gen(),
+            title: c.title,
+            channel_id: Id::new(c.channel_id),
+            created_at: chrono::Utc::now(),
+            close_at: None,
+            market_type: MarketType::Parimutuel,
+            over_under_line: None,
+            liquidity: None,
+            min_bettors: None,
+            min_candidates: None,
+        }
     }
 }
 
 #[derive(new)]
 pub struct CloseMatch {
     pub channel_id: String,
-    pub message_id: String,
+    pub match_number: Option<i32>,
+    // 締め切り時刻になって自動で締め切る場合は None
+    pub message_id: Option<String>,
 }
 
 impl From<CloseMatch> for UpdateMatchForLatest {
     fn from(c: CloseMatch) -> Self {
-        UpdateMatchForLatest::new(Id::new(c.channel_id), Some(Some(chrono::Utc::now())), None)
+        UpdateMatchForLatest::new(
+            Id::new(c.channel_id),
+            c.match_number,
+            Some(Some(chrono::Utc::now())),
+        )
     }
 }
 
 #[derive(new)]
 pub struct FinishMatch {
     pub channel_id: String,
-    pub winner_candidate_name: String,
+    pub match_number: Option<i32>,
+    pub winner_candidate_names: Vec<String>,
 }
 
-impl From<FinishMatch> for UpdateMatchForLatest {
+impl FinishMatch {
+    // `A>B>C` の形式で指定された場合は着順として扱う
+    pub fn placing_candidate_names(&self) -> Vec<String> {
+        match self.winner_candidate_names.as_slice() {
+            [placing] if placing.contains('>') => {
+                placing.split('>').map(|name| name.to_string()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+    // `候補A 3 候補B 1` の形式で指定された場合は候補ごとの得点として扱う
+    pub fn scores(&self) -> Option<Vec<(String, i32)>> {
+        let names = &self.winner_candidate_names;
+        if names.len() < 2 || !names.len().is_multiple_of(2) {
+            return None;
+        }
+        names
+            .chunks(2)
+            .map(|pair| {
+                pair[1]
+                    .parse::<i32>()
+                    .ok()
+                    .map(|score| (pair[0].clone(), score))
+            })
+            .collect()
+    }
+}
+
+impl From<FinishMatch> for SettleMatchForLatest {
     fn from(c: FinishMatch) -> Self {
-        UpdateMatchForLatest::new(
-            Id::new(c.channel_id),
-            None,
-            Some(Some(c.winner_candidate_name)),
-        )
+        let placing_candidate_names = c.placing_candidate_names();
+        // 着順が指定された場合は 1 着の候補を勝者とする
+        let winner_candidate_names = match placing_candidate_names.first() {
+            Some(winner) => vec![winner.clone()],
+            None => c.winner_candidate_names,
+        };
+        SettleMatchForLatest {
+            id: Id::gen(),
+            channel_id: Id::new(c.channel_id),
+            match_number: c.match_number,
+            winner_candidate_names,
+            placing_candidate_names,
+            result_value: None,
+            scores: Vec::new(),
+            settled_at: chrono::Utc::now(),
+        }
+    }
+}
+
+#[derive(new)]
+pub struct CancelMatch {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+}
+
+impl From<CancelMatch> for CancelMatchForLatest {
+    fn from(c: CancelMatch) -> Self {
+        CancelMatchForLatest::new(Id::new(c.channel_id), c.match_number, chrono::Utc::now())
+    }
+}
+
+#[derive(new)]
+pub struct VoidMatch {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+}
+
+impl From<VoidMatch> for VoidMatchForLatest {
+    fn from(c: VoidMatch) -> Self {
+        VoidMatchForLatest::new(Id::new(c.channel_id), c.match_number, chrono::Utc::now())
     }
 }
@@ -6,3 +6,8 @@ pub struct UpsertMatchMessage {
     pub channel_id: Id<Channel>,
     pub match_id: Id<Match>,
 }
+
+#[derive(new, Debug)]
+pub struct SendMatchReminder {
+    pub match_id: Id<Match>,
+}
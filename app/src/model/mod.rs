@@ -1,4 +1,7 @@
 pub mod bet;
+pub mod challenge;
+pub mod channel;
 pub mod r#match;
 pub mod message;
+pub mod parlay;
 pub mod user;
@@ -0,0 +1,38 @@
+use derive_new::new;
+use kernel::model::{
+    parlay::{NewParlay, NewParlayLeg},
+    Id,
+};
+
+#[derive(new)]
+pub struct CreateParlay {
+    pub channel_id: String,
+    pub message_id: String,
+    pub traq_id: String,
+    pub amount: i32,
+    pub legs: Vec<CreateParlayLeg>,
+}
+
+// channel_id の channel で bet を受け付けている match の候補を選ぶ
+#[derive(new)]
+pub struct CreateParlayLeg {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub candidate_name: String,
+}
+
+impl From<CreateParlay> for NewParlay {
+    fn from(c: CreateParlay) -> Self {
+        NewParlay::new(
+            Id::gen(),
+            Id::new(c.channel_id),
+            c.traq_id,
+            c.amount,
+            c.legs
+                .into_iter()
+                .map(|l| NewParlayLeg::new(Id::new(l.channel_id), l.match_number, l.candidate_name))
+                .collect(),
+            chrono::Utc::now(),
+        )
+    }
+}
@@ -8,6 +8,15 @@ pub struct CreateUser {
     pub channel_id: String,
 }
 
+// value が on か off でない場合は引数が不正とする
+#[derive(new)]
+pub struct ConfigureNotify {
+    pub traq_id: String,
+    pub channel_id: String,
+    pub message_id: String,
+    pub value: Option<String>,
+}
+
 const INITIAL_BALANCE: i32 = 10_000;
 
 impl From<CreateUser> for NewUser {
@@ -2,30 +2,53 @@ use std::sync::Arc;
 
 use adapter::modules::RepositoriesModuleExt;
 use derive_new::new;
-use kernel::model::bet::Bet;
-use kernel::model::message::NewMessage;
+use kernel::model::bet::{Bet, CashOut, NewGuessBetForLatestMatch};
+use kernel::model::channel::Channel;
+use kernel::model::lmsr::MAX_TRADE_SHARES;
+use kernel::model::message::{Message, NewMessage};
+use kernel::model::position::Trade;
+use kernel::model::r#match::{MarketType, TransitionError};
 use kernel::model::stamp::{NewStamp, StampType};
 use kernel::model::Id;
 use kernel::repository::bet::BetRepository;
+use kernel::repository::candidate::CandidateRepository;
 use kernel::repository::error::RepositoryError;
+use kernel::repository::position::PositionRepository;
+use kernel::repository::r#match::MatchRepository;
 use kernel::traq::message::MessageTraqRepository;
 use kernel::traq::stamp::StampTraqRepository;
 
+const GUESS_ONLY_MESSAGE: &str =
+    "数値を予想する賭けです\n`@BOT_bookmaker bet guess 予想 ポイント数`の形式で bet してください";
+const LMSR_ONLY_MESSAGE: &str =
+    "株を売買する賭けです\n`@BOT_bookmaker buy 候補名 株数`の形式で株を購入してください";
+
 #[derive(new)]
 pub struct BetUseCase<R: RepositoriesModuleExt> {
     repositories: Arc<R>,
 }
 
 impl<R: RepositoriesModuleExt> BetUseCase<R> {
-    pub async fn create_bet(&self, source: CreateBet) -> Result<Bet, BetUseCaseError> {
+    pub async fn create_bet(&self, source: CreateBet) -> Result<Vec<Bet>, BetUseCaseError> {
         let message_id = Id::new(source.message_id.clone());
         let channel_id = Id::new(source.channel_id.clone());
-        if source.candidate_name == "" || source.amount <= 0 {
+        let has_duplicated_candidate = source.stakes.iter().enumerate().any(|(i, s)| {
+            source.stakes[..i]
+                .iter()
+                .any(|t| t.candidate_name == s.candidate_name)
+        });
+        if source.stakes.is_empty()
+            || source
+                .stakes
+                .iter()
+                .any(|s| s.candidate_name.is_empty() || s.amount <= 0)
+            || has_duplicated_candidate
+        {
             self.repositories
                 .message_traq_repository()
                 .create(NewMessage::new(
                     channel_id,
-                    "引数が不正です\n賭けの対象となる候補を指定し、賭けるポイントは正の整数を指定してください\n`@BOT_bookmaker bet 候補A ポイント数`の形式で指定できます".to_string(),
+                    "引数が不正です\n賭けの対象となる候補を指定し、賭けるポイントは正の整数を指定してください\n`@BOT_bookmaker bet 候補A ポイント数`の形式で指定できます\n`@BOT_bookmaker bet 候補A ポイント数 候補B ポイント数`のように複数の候補に分けて賭けることもできます".to_string(),
                     true,
                 ))
                 .await
@@ -35,76 +58,499 @@ impl<R: RepositoriesModuleExt> BetUseCase<R> {
             return Err(BetUseCaseError::AmountMustBePositive);
         }
 
+        match self
+            .latest_market_type(&source.channel_id, source.match_number)
+            .await?
+        {
+            Some(MarketType::ClosestGuess) => {
+                return Err(self
+                    .reply_invalid_market(channel_id, GUESS_ONLY_MESSAGE)
+                    .await)
+            }
+            Some(MarketType::Lmsr) => {
+                return Err(self
+                    .reply_invalid_market(channel_id, LMSR_ONLY_MESSAGE)
+                    .await)
+            }
+            _ => {}
+        }
+
         let bet_result = self
             .repositories
             .bet_repository()
             .insert_for_latest_match(source.into())
             .await;
 
+        self.reply(channel_id, message_id, bet_result).await
+    }
+    pub async fn create_ordered_bet(
+        &self,
+        source: CreateOrderedBet,
+    ) -> Result<Bet, BetUseCaseError> {
+        let message_id = Id::new(source.message_id.clone());
+        let channel_id = Id::new(source.channel_id.clone());
+        let names = &source.candidate_names;
+        let has_invalid_order = names.len() != source.bet_type.placings()
+            || names
+                .iter()
+                .enumerate()
+                .any(|(i, name)| name.is_empty() || names[..i].contains(name));
+        if has_invalid_order || source.amount <= 0 {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    format!(
+                        "引数が不正です\n異なる候補を{}つ`>`でつなげて着順を指定し、賭けるポイントは正の整数を指定してください\n`@BOT_bookmaker bet exacta 候補A>候補B ポイント数`\n`@BOT_bookmaker bet trifecta 候補A>候補B>候補C ポイント数`\nの形式で指定できます",
+                        source.bet_type.placings()
+                    ),
+                    true,
+                ))
+                .await
+                .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(BetUseCaseError::InvalidOrder);
+        }
+
+        // 固定オッズの賭けでは着順に対する倍率が決まっていないので、着順を当てる bet は受け付けない
+        match self
+            .latest_market_type(&source.channel_id, source.match_number)
+            .await?
+        {
+            Some(MarketType::FixedOdds) => {
+                return Err(self
+                    .reply_invalid_market(
+                        channel_id,
+                        "固定オッズの賭けでは着順を当てる bet はできません",
+                    )
+                    .await)
+            }
+            Some(MarketType::ClosestGuess) => {
+                return Err(self
+                    .reply_invalid_market(channel_id, GUESS_ONLY_MESSAGE)
+                    .await)
+            }
+            Some(MarketType::Lmsr) => {
+                return Err(self
+                    .reply_invalid_market(channel_id, LMSR_ONLY_MESSAGE)
+                    .await)
+            }
+            _ => {}
+        }
+
+        let bet_result = self
+            .repositories
+            .bet_repository()
+            .insert_ordered_for_latest_match(source.into())
+            .await;
+
+        self.reply(channel_id, message_id, bet_result).await
+    }
+    pub async fn create_guess_bet(&self, source: CreateGuessBet) -> Result<Bet, BetUseCaseError> {
+        let message_id = Id::new(source.message_id.clone());
+        let channel_id = Id::new(source.channel_id.clone());
+        let guess = parse_hundredths(&source.guess);
+        if guess.is_none() || source.amount <= 0 {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    "引数が不正です\n予想は数値で、賭けるポイントは正の整数を指定してください\n`@BOT_bookmaker bet guess 予想 ポイント数`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(BetUseCaseError::AmountMustBePositive);
+        }
+
+        match self
+            .latest_market_type(&source.channel_id, source.match_number)
+            .await?
+        {
+            Some(MarketType::ClosestGuess) | None => {}
+            Some(_) => {
+                return Err(self
+                    .reply_invalid_market(channel_id, "この賭けでは数値を予想する bet はできません")
+                    .await)
+            }
+        }
+
+        let bet_result = self
+            .repositories
+            .bet_repository()
+            .insert_guess_for_latest_match(NewGuessBetForLatestMatch::new(
+                Id::gen(),
+                source.traq_id,
+                source.channel_id,
+                source.match_number,
+                guess.unwrap_or_default(),
+                source.amount,
+                chrono::Utc::now(),
+            ))
+            .await;
+
+        self.reply(channel_id, message_id, bet_result).await
+    }
+    pub async fn trade_shares(&self, source: TradeShares) -> Result<Trade, BetUseCaseError> {
+        let message_id = Id::new(source.message_id.clone());
+        let channel_id = Id::new(source.channel_id.clone());
+        if source.candidate_name.is_empty() || source.shares == 0 {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    "引数が不正です\n売買する候補を指定し、株数は正の整数を指定してください\n`@BOT_bookmaker buy 候補A 株数`\n`@BOT_bookmaker sell 候補A 株数`\nの形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(BetUseCaseError::AmountMustBePositive);
+        }
+        if source.shares.unsigned_abs() > MAX_TRADE_SHARES as u32 {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    format!(
+                        "引数が不正です\n1 回に売買できる株数は{}株までです",
+                        MAX_TRADE_SHARES
+                    ),
+                    true,
+                ))
+                .await
+                .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(BetUseCaseError::InvalidArgument);
+        }
+
+        match self
+            .latest_market_type(&source.channel_id, source.match_number)
+            .await?
+        {
+            Some(MarketType::Lmsr) | None => {}
+            Some(_) => {
+                return Err(self
+                    .reply_invalid_market(channel_id, "この賭けでは株の売買はできません")
+                    .await)
+            }
+        }
+
+        let candidate_name = source.candidate_name.clone();
+        let shares = source.shares;
+        let trade_result = self
+            .repositories
+            .position_repository()
+            .trade_for_latest_match(source.into())
+            .await;
+        let trade = self
+            .reply(Id::new(channel_id.value.clone()), message_id, trade_result)
+            .await?;
+
+        // 売買した価格は bet と違って事前に分からないので、支払ったポイントと受け取ったポイントを知らせる
+        let trade_content = if shares > 0 {
+            format!(
+                "{}の株を{}株購入し、{}ptを支払いました",
+                candidate_name, shares, trade.points
+            )
+        } else {
+            format!(
+                "{}の株を{}株売却し、{}ptを受け取りました",
+                candidate_name, -shares, trade.points
+            )
+        };
+        let content = format!(
+            "{}\n保有: {}株 / 残高: {}pt",
+            trade_content, trade.position.shares, trade.balance
+        );
+        self.repositories
+            .message_traq_repository()
+            .create(NewMessage::new(channel_id, content, true))
+            .await
+            .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        Ok(trade)
+    }
+    pub async fn change_bet(&self, source: ChangeBet) -> Result<Bet, BetUseCaseError> {
+        let message_id = Id::new(source.message_id.clone());
+        let channel_id = Id::new(source.channel_id.clone());
+        if source.candidate_name.is_empty() {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    "引数が不正です\n変更先の候補を指定してください\n`@BOT_bookmaker rebet 候補B`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(BetUseCaseError::CandidateNotFound);
+        }
+
+        let bet_result = self
+            .repositories
+            .bet_repository()
+            .update_for_latest_match(source.into())
+            .await;
+
+        self.reply(channel_id, message_id, bet_result).await
+    }
+    pub async fn cancel_bet(&self, source: CancelBet) -> Result<Vec<Bet>, BetUseCaseError> {
+        let message_id = Id::new(source.message_id.clone());
+        let channel_id = Id::new(source.channel_id.clone());
+        let bet_result = self
+            .repositories
+            .bet_repository()
+            .delete_for_latest_match(source.into())
+            .await;
+
+        self.reply(channel_id, message_id, bet_result).await
+    }
+    // confirmed が false の場合は提示額を知らせるだけで、true の場合は bet を取り下げて提示額を支払う
+    // 確定時の提示額がユーザーの見た提示額より下がっていた場合は、確定せずに提示し直す
+    pub async fn cash_out_bet(&self, source: CashOutBet) -> Result<CashOut, BetUseCaseError> {
+        let message_id = Id::new(source.message_id.clone());
+        let channel_id = Id::new(source.channel_id.clone());
+        if source.confirmed && source.accepted_value.is_none_or(|value| value <= 0) {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    "引数が不正です\n確定するには提示されたポイントを指定してください\n`@BOT_bookmaker cashout 候補A accept 提示されたポイント`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(BetUseCaseError::InvalidArgument);
+        }
+
+        // 固定オッズの賭けは払い戻しが決まっているので、プールの分け方から価値を決められるパリミュチュエルに限る
+        match self
+            .latest_market_type(&source.channel_id, source.match_number)
+            .await?
+        {
+            Some(MarketType::Parimutuel) | None => {}
+            Some(_) => {
+                return Err(self
+                    .reply_invalid_market(channel_id, "この賭けでは cash out できません")
+                    .await)
+            }
+        }
+
+        let confirmed = source.confirmed;
+        let cash_out_result = self
+            .repositories
+            .bet_repository()
+            .cash_out_for_latest_match(source.into())
+            .await;
+        if let Err(RepositoryError::DuplicatedRecord(_)) = cash_out_result {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    "複数の候補に bet しているため、cash out する候補を指定してください\n`@BOT_bookmaker cashout 候補A`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(BetUseCaseError::EnabledBetAlreadyExists);
+        }
+        // 一般的な bet のエラーとは理由が違うので、cash out できない理由をここで知らせる
+        let refused = match cash_out_result {
+            Err(RepositoryError::InvalidArgument(_)) => Some((
+                "他に誰もこの候補に bet していないため、今 cash out しても受け取れるポイントがありません",
+                BetUseCaseError::InvalidArgument,
+            )),
+            Err(RepositoryError::InsufficientBalance) => Some((
+                "この賭けの bet が無くなると参加賞が取り消されますが、取り消す分のポイントが残高に足りないため cash out できません",
+                BetUseCaseError::AmountMustBePositive,
+            )),
+            _ => None,
+        };
+        if let Some((content, error)) = refused {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(channel_id, content.to_string(), true))
+                .await
+                .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(error);
+        }
+        let cash_out = self
+            .reply(
+                Id::new(channel_id.value.clone()),
+                message_id,
+                cash_out_result,
+            )
+            .await?;
+
+        let candidate_name = self
+            .repositories
+            .candidate_repository()
+            .select_by_match_id(Id::new(cash_out.bet.match_id.value.clone()))
+            .await
+            .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?
+            .into_iter()
+            .find(|c| c.id.value == cash_out.bet.candidate_id.value)
+            .map(|c| c.name)
+            .unwrap_or_default();
+        let prize_content = if cash_out.prize > 0 {
+            format!(
+                "\nこの賭けの bet が無くなるため、参加賞の{}ptは取り消されます",
+                cash_out.prize
+            )
+        } else {
+            "".to_string()
+        };
+        let accept_content = format!(
+            "`@BOT_bookmaker cashout {} accept {}`で確定できます (確定時に受け取れるポイントが下がっていた場合は確定しません)",
+            candidate_name, cash_out.value
+        );
+        let content = if cash_out.confirmed {
+            format!(
+                "{}への bet {}ptを cash out し、{}ptを受け取りました{}\n残高: {}pt",
+                candidate_name,
+                cash_out.bet.amount,
+                cash_out.value,
+                prize_content,
+                cash_out.balance
+            )
+        } else if confirmed {
+            format!(
+                "プールが変わり、{}への bet {}ptを cash out して受け取れるポイントが{}ptに下がったため、確定しませんでした{}\n{}",
+                candidate_name, cash_out.bet.amount, cash_out.value, prize_content, accept_content
+            )
+        } else {
+            format!(
+                "{}への bet {}ptを今 cash out すると{}ptを受け取れます{}\n{}",
+                candidate_name, cash_out.bet.amount, cash_out.value, prize_content, accept_content
+            )
+        };
+        self.repositories
+            .message_traq_repository()
+            .create(NewMessage::new(channel_id, content, true))
+            .await
+            .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        Ok(cash_out)
+    }
+    // 対象の match の払い戻しの決め方を取得する。match が定まらない場合のエラーは bet する際に返す
+    async fn latest_market_type(
+        &self,
+        channel_id: &str,
+        match_number: Option<i32>,
+    ) -> Result<Option<MarketType>, BetUseCaseError> {
+        match self
+            .repositories
+            .match_repository()
+            .find_latest(Id::new(channel_id.to_string()), match_number)
+            .await
+        {
+            Ok(latest) => Ok(latest.map(|m| m.market_type)),
+            Err(RepositoryError::AmbiguousMatch) => Ok(None),
+            Err(e) => Err(BetUseCaseError::UnexpectedError(anyhow::anyhow!(e))),
+        }
+    }
+    // 賭けの種類に合わない bet を受け付けず、その理由をチャンネルに送信する
+    async fn reply_invalid_market(
+        &self,
+        channel_id: Id<Channel>,
+        content: &str,
+    ) -> BetUseCaseError {
+        if let Err(e) = self
+            .repositories
+            .message_traq_repository()
+            .create(NewMessage::new(channel_id, content.to_string(), true))
+            .await
+        {
+            return BetUseCaseError::UnexpectedError(anyhow::anyhow!(e));
+        }
+        BetUseCaseError::InvalidMarket
+    }
+    // 成功した場合はスタンプを付け、失敗した場合はエラーの内容をチャンネルに送信する
+    async fn reply<T>(
+        &self,
+        channel_id: Id<Channel>,
+        message_id: Id<Message>,
+        bet_result: Result<T, RepositoryError>,
+    ) -> Result<T, BetUseCaseError> {
         match bet_result {
             Ok(bet) => {
                 self.repositories
                     .stamp_repository()
                     .create(NewStamp::new(message_id, StampType::WhiteCheckMark))
                     .await
-                    .map_err(|e| match e {
-                        _ => BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-                    })?;
+                    .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
 
-                return Ok(bet);
+                Ok(bet)
             }
             Err(e) => {
                 let error_with_message = match e {
                     RepositoryError::RecordNotFound(s) => {
                         if s.contains("Match") {
                             (
-                                "有効な賭けが見つかりませんでした",
+                                "有効な賭けが見つかりませんでした".to_string(),
                                 BetUseCaseError::EnabledMatchNotFound,
                             )
                         } else if s.contains("Candidate") {
                             (
-                                "指定した候補が見つかりませんでした",
+                                "指定した候補が見つかりませんでした".to_string(),
                                 BetUseCaseError::CandidateNotFound,
                             )
                         } else if s.contains("User") {
                             (
-                                "ユーザー登録していません\n`@BOT_bookmaker reg`で先に登録してください",
+                                "ユーザー登録していません\n`@BOT_bookmaker reg`で先に登録してください".to_string(),
                                 BetUseCaseError::UserNotFound,
                             )
+                        } else if s.contains("Bet") {
+                            (
+                                "まだこの賭けに bet していません".to_string(),
+                                BetUseCaseError::BetNotFound,
+                            )
                         } else {
                             (
-                                "予期せぬエラーが発生しました",
+                                "予期せぬエラーが発生しました".to_string(),
                                 BetUseCaseError::UnexpectedError(anyhow::anyhow!(
-                                    "Record not found but not Match or Candidate or User"
+                                    "Record not found but not Match or Candidate or User or Bet"
                                 )),
                             )
                         }
                     }
                     RepositoryError::DuplicatedRecord(_) => (
-                        "すでにこの賭けに参加しています",
+                        "複数の候補に bet しているため、変更元の候補を指定してください\n`@BOT_bookmaker rebet 候補A 候補B`の形式で指定できます".to_string(),
                         BetUseCaseError::EnabledBetAlreadyExists,
                     ),
+                    RepositoryError::AmbiguousMatch => (
+                        AMBIGUOUS_MATCH_MESSAGE.to_string(),
+                        BetUseCaseError::AmbiguousMatch,
+                    ),
                     RepositoryError::InsufficientBalance => (
-                        "ポイントが不足しています",
+                        "ポイントが不足しています".to_string(),
                         BetUseCaseError::AmountMustBePositive,
                     ),
+                    RepositoryError::InsufficientShares => (
+                        "保有している株数が不足しています".to_string(),
+                        BetUseCaseError::InsufficientShares,
+                    ),
+                    RepositoryError::InvalidArgument(s) => (
+                        if s.contains("shares") {
+                            "株数が多すぎるため売買できません".to_string()
+                        } else {
+                            "賭けるポイントが大きすぎます".to_string()
+                        },
+                        BetUseCaseError::InvalidArgument,
+                    ),
+                    RepositoryError::InvalidTransition(e) => (
+                        transition_error_message(&e),
+                        BetUseCaseError::InvalidTransition(e),
+                    ),
                     _ => (
-                        "予期せぬエラーが発生しました",
+                        "予期せぬエラーが発生しました".to_string(),
                         BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
                     ),
                 };
                 self.repositories
                     .message_traq_repository()
-                    .create(NewMessage::new(
-                        channel_id,
-                        error_with_message.0.to_string(),
-                        true,
-                    ))
+                    .create(NewMessage::new(channel_id, error_with_message.0, true))
                     .await
-                    .map_err(|e| match e {
-                        _ => BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-                    })?;
-                return Err(error_with_message.1);
+                    .map_err(|e| BetUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+                Err(error_with_message.1)
             }
         }
     }
@@ -112,20 +558,37 @@ impl<R: RepositoriesModuleExt> BetUseCase<R> {
 
 use thiserror::Error;
 
-use crate::model::bet::CreateBet;
+use crate::model::bet::{
+    CancelBet, CashOutBet, ChangeBet, CreateBet, CreateGuessBet, CreateOrderedBet, TradeShares,
+};
+use crate::usecase::{parse_hundredths, transition_error_message, AMBIGUOUS_MATCH_MESSAGE};
 
 #[derive(Error, Debug)]
 pub enum BetUseCaseError {
     #[error("Amount must be positive")]
     AmountMustBePositive,
+    #[error("Invalid argument")]
+    InvalidArgument,
+    #[error("Invalid order")]
+    InvalidOrder,
+    #[error("Bet does not fit the market")]
+    InvalidMarket,
+    #[error("Insufficient shares")]
+    InsufficientShares,
     #[error("Bet already exists")]
     EnabledBetAlreadyExists,
+    #[error("Bet not found")]
+    BetNotFound,
+    #[error("Match number is required")]
+    AmbiguousMatch,
     #[error("Candidate not found")]
     CandidateNotFound,
     #[error("User not found")]
     UserNotFound,
     #[error("Enabled match not found")]
     EnabledMatchNotFound,
+    #[error("Invalid transition: {0}")]
+    InvalidTransition(TransitionError),
     #[error("Unexpected error: {0}")]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -0,0 +1,300 @@
+use std::sync::Arc;
+
+use adapter::modules::RepositoriesModuleExt;
+use derive_new::new;
+use kernel::model::challenge::{
+    AcceptChallengeForLatest, Challenge, DeclineChallengeForLatest, ResolveChallengeForLatest,
+};
+use kernel::model::channel::Channel;
+use kernel::model::message::{Message, NewMessage};
+use kernel::model::stamp::{NewStamp, StampType};
+use kernel::model::user::User;
+use kernel::model::Id;
+use kernel::repository::challenge::ChallengeRepository;
+use kernel::repository::error::RepositoryError;
+use kernel::repository::user::UserRepository;
+use kernel::traq::message::MessageTraqRepository;
+use kernel::traq::stamp::StampTraqRepository;
+
+#[derive(new)]
+pub struct ChallengeUseCase<R: RepositoriesModuleExt> {
+    repositories: Arc<R>,
+}
+
+// メッセージでメンションするためにユーザーの traQ ID を取得する
+fn mention(users: &[User], user_id: &Id<User>) -> String {
+    users
+        .iter()
+        .find(|u| u.id.value == user_id.value)
+        .map(|u| format!("@{}", u.traq_display_id))
+        .unwrap_or("unknown".to_string())
+}
+
+impl<R: RepositoriesModuleExt> ChallengeUseCase<R> {
+    pub async fn create_challenge(
+        &self,
+        source: CreateChallenge,
+    ) -> Result<Challenge, ChallengeUseCaseError> {
+        let message_id = Id::new(source.message_id.clone());
+        let channel_id = Id::new(source.channel_id.clone());
+        let mentioned = &source.mentioned_traq_ids;
+        let has_invalid_user = mentioned.is_empty()
+            || mentioned.len() > 2
+            || mentioned.contains(&source.traq_id)
+            || mentioned.get(1) == mentioned.first();
+        if has_invalid_user || source.amount <= 0 || source.title.is_empty() {
+            self.send(
+                channel_id,
+                "引数が不正です\n相手をメンションし、賭けるポイントは正の整数、賭けの内容を指定してください\n`@BOT_bookmaker challenge @相手 500 \"PRX wins\"`の形式で指定できます\n`@BOT_bookmaker challenge @相手 500 \"PRX wins\" @審判`のように当事者以外のユーザーを審判に指定することもできます".to_string(),
+            )
+            .await?;
+            return Err(ChallengeUseCaseError::InvalidArgument);
+        }
+
+        let result = self
+            .repositories
+            .challenge_repository()
+            .insert(source.into())
+            .await;
+        let challenge = self.reply(channel_id, message_id, result).await?;
+
+        let users = self.users(&challenge.channel_id).await?;
+        let resolver = match &challenge.arbiter_id {
+            Some(arbiter_id) => format!("勝敗は{}さんが決めます", mention(&users, arbiter_id)),
+            None => "審判はいないため、負けた人が相手を勝者に指定して決着します".to_string(),
+        };
+        self.send(
+            Id::new(challenge.channel_id.value.clone()),
+            format!(
+                "{}さん、{}さんから「{}」の勝負を{}ptで挑まれました\n{}\n`@BOT_bookmaker accept`で受けるか、`@BOT_bookmaker decline`で断ってください",
+                mention(&users, &challenge.opponent_id),
+                mention(&users, &challenge.challenger_id),
+                challenge.title,
+                challenge.amount,
+                resolver
+            ),
+        )
+        .await?;
+
+        Ok(challenge)
+    }
+    pub async fn accept_challenge(
+        &self,
+        source: AcceptChallenge,
+    ) -> Result<Challenge, ChallengeUseCaseError> {
+        let message_id = Id::new(source.message_id);
+        let channel_id = Id::new(source.channel_id.clone());
+        let result = self
+            .repositories
+            .challenge_repository()
+            .accept_for_latest(AcceptChallengeForLatest::new(
+                Id::new(source.channel_id),
+                source.traq_id,
+                chrono::Utc::now(),
+            ))
+            .await;
+        let challenge = self.reply(channel_id, message_id, result).await?;
+
+        let users = self.users(&challenge.channel_id).await?;
+        self.send(
+            Id::new(challenge.channel_id.value.clone()),
+            format!(
+                "{}さんと{}さんの「{}」の勝負が成立しました\n両者から{}ptずつ預かり、勝者が{}ptを受け取ります\n決着したら`@BOT_bookmaker resolve @勝者`で勝者を指定してください",
+                mention(&users, &challenge.challenger_id),
+                mention(&users, &challenge.opponent_id),
+                challenge.title,
+                challenge.amount,
+                challenge.amount * 2
+            ),
+        )
+        .await?;
+
+        Ok(challenge)
+    }
+    pub async fn decline_challenge(
+        &self,
+        source: DeclineChallenge,
+    ) -> Result<Challenge, ChallengeUseCaseError> {
+        let message_id = Id::new(source.message_id);
+        let channel_id = Id::new(source.channel_id.clone());
+        let result = self
+            .repositories
+            .challenge_repository()
+            .decline_for_latest(DeclineChallengeForLatest::new(
+                Id::new(source.channel_id),
+                source.traq_id,
+                chrono::Utc::now(),
+            ))
+            .await;
+        let challenge = self.reply(channel_id, message_id, result).await?;
+
+        let users = self.users(&challenge.channel_id).await?;
+        self.send(
+            Id::new(challenge.channel_id.value.clone()),
+            format!(
+                "「{}」の勝負は成立しませんでした\n{}さんに{}ptを返却しました",
+                challenge.title,
+                mention(&users, &challenge.challenger_id),
+                challenge.amount
+            ),
+        )
+        .await?;
+
+        Ok(challenge)
+    }
+    pub async fn resolve_challenge(
+        &self,
+        source: ResolveChallenge,
+    ) -> Result<Challenge, ChallengeUseCaseError> {
+        let message_id = Id::new(source.message_id);
+        let channel_id = Id::new(source.channel_id.clone());
+        let Some(winner_traq_id) = source.winner_traq_id else {
+            self.send(
+                channel_id,
+                "勝者をメンションしてください\n`@BOT_bookmaker resolve @勝者`の形式で指定できます"
+                    .to_string(),
+            )
+            .await?;
+            return Err(ChallengeUseCaseError::InvalidArgument);
+        };
+
+        let result = self
+            .repositories
+            .challenge_repository()
+            .resolve_for_latest(ResolveChallengeForLatest::new(
+                Id::new(source.channel_id),
+                source.traq_id,
+                winner_traq_id,
+                chrono::Utc::now(),
+            ))
+            .await;
+        let challenge = self.reply(channel_id, message_id, result).await?;
+
+        let users = self.users(&challenge.channel_id).await?;
+        let winner = challenge
+            .winner_id
+            .as_ref()
+            .map(|winner_id| mention(&users, winner_id))
+            .unwrap_or("unknown".to_string());
+        self.send(
+            Id::new(challenge.channel_id.value.clone()),
+            format!(
+                "「{}」の勝負は{}さんの勝ちです\n{}さんに{}ptを払い戻しました",
+                challenge.title,
+                winner,
+                winner,
+                challenge.amount * 2
+            ),
+        )
+        .await?;
+
+        Ok(challenge)
+    }
+    async fn users(&self, channel_id: &Id<Channel>) -> Result<Vec<User>, ChallengeUseCaseError> {
+        self.repositories
+            .user_repository()
+            .select_by_channel_id(Id::new(channel_id.value.clone()))
+            .await
+            .map_err(|e| ChallengeUseCaseError::UnexpectedError(anyhow::anyhow!(e)))
+    }
+    async fn send(
+        &self,
+        channel_id: Id<Channel>,
+        content: String,
+    ) -> Result<(), ChallengeUseCaseError> {
+        self.repositories
+            .message_traq_repository()
+            .create(NewMessage::new(channel_id, content, true))
+            .await
+            .map_err(|e| ChallengeUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        Ok(())
+    }
+    // 成功した場合はスタンプを付け、失敗した場合はエラーの内容をチャンネルに送信する
+    async fn reply<T>(
+        &self,
+        channel_id: Id<Channel>,
+        message_id: Id<Message>,
+        result: Result<T, RepositoryError>,
+    ) -> Result<T, ChallengeUseCaseError> {
+        match result {
+            Ok(challenge) => {
+                self.repositories
+                    .stamp_repository()
+                    .create(NewStamp::new(message_id, StampType::WhiteCheckMark))
+                    .await
+                    .map_err(|e| ChallengeUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                Ok(challenge)
+            }
+            Err(e) => {
+                let error_with_message = match e {
+                    RepositoryError::RecordNotFound(s) => {
+                        if s.contains("Challenge") {
+                            (
+                                "対象の勝負が見つかりませんでした".to_string(),
+                                ChallengeUseCaseError::ChallengeNotFound,
+                            )
+                        } else if s.contains("Opponent") {
+                            (
+                                "相手のユーザーがこのチャンネルで登録していません".to_string(),
+                                ChallengeUseCaseError::UserNotFound,
+                            )
+                        } else if s.contains("Arbiter") {
+                            (
+                                "審判のユーザーがこのチャンネルで登録していません".to_string(),
+                                ChallengeUseCaseError::UserNotFound,
+                            )
+                        } else if s.contains("Winner") {
+                            (
+                                "勝者に指定したユーザーがこのチャンネルで登録していません"
+                                    .to_string(),
+                                ChallengeUseCaseError::UserNotFound,
+                            )
+                        } else {
+                            (
+                                "ユーザー登録していません\n`@BOT_bookmaker reg`で先に登録してください".to_string(),
+                                ChallengeUseCaseError::UserNotFound,
+                            )
+                        }
+                    }
+                    RepositoryError::PermissionDenied(_) => (
+                        "審判がいる勝負は審判だけが、いない勝負は負けた人だけが相手を勝者に指定できます".to_string(),
+                        ChallengeUseCaseError::PermissionDenied,
+                    ),
+                    RepositoryError::InsufficientBalance => (
+                        "ポイントが不足しています".to_string(),
+                        ChallengeUseCaseError::InsufficientBalance,
+                    ),
+                    _ => (
+                        "予期せぬエラーが発生しました".to_string(),
+                        ChallengeUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
+                    ),
+                };
+                self.send(channel_id, error_with_message.0).await?;
+                Err(error_with_message.1)
+            }
+        }
+    }
+}
+
+use thiserror::Error;
+
+use crate::model::challenge::{
+    AcceptChallenge, CreateChallenge, DeclineChallenge, ResolveChallenge,
+};
+
+#[derive(Error, Debug)]
+pub enum ChallengeUseCaseError {
+    #[error("Invalid argument")]
+    InvalidArgument,
+    #[error("Challenge not found")]
+    ChallengeNotFound,
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Permission denied")]
+    PermissionDenied,
+    #[error("Insufficient balance")]
+    InsufficientBalance,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(#[from] anyhow::Error),
+}
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use adapter::modules::RepositoriesModuleExt;
+use derive_new::new;
+use kernel::model::channel::{Channel, UpdateChannel, MAX_MATCH_TTL_HOURS};
+use kernel::model::message::NewMessage;
+use kernel::model::settlement::{NoWinnerPolicy, RoundingPolicy};
+use kernel::model::Id;
+use kernel::repository::channel::ChannelRepository;
+use kernel::traq::message::MessageTraqRepository;
+
+use crate::model::channel::ConfigureChannel;
+
+#[derive(new)]
+pub struct ChannelUseCase<R: RepositoriesModuleExt> {
+    repositories: Arc<R>,
+}
+
+fn parse_rounding_policy(value: &str) -> Option<RoundingPolicy> {
+    match value {
+        "largest" => Some(RoundingPolicy::LargestRemainder),
+        "house" => Some(RoundingPolicy::House),
+        "earliest" => Some(RoundingPolicy::EarliestBettor),
+        _ => None,
+    }
+}
+
+fn parse_no_winner_policy(value: &str) -> Option<NoWinnerPolicy> {
+    match value {
+        "refund" => Some(NoWinnerPolicy::Refund),
+        "jackpot" => Some(NoWinnerPolicy::Jackpot),
+        "house" => Some(NoWinnerPolicy::House),
+        _ => None,
+    }
+}
+
+// 手数料は 0% から 100% 未満で指定する
+fn parse_commission_percent(value: &str) -> Option<i32> {
+    value
+        .trim_end_matches('%')
+        .parse::<i32>()
+        .ok()
+        .filter(|percent| (0..100).contains(percent))
+}
+
+// 放置された match を無効にするまでの時間。off の場合は無効にしないので 0 とする
+fn parse_match_ttl_hours(value: &str) -> Option<i32> {
+    if value == "off" {
+        return Some(0);
+    }
+    value
+        .trim_end_matches('h')
+        .parse::<i32>()
+        .ok()
+        .filter(|hours| (1..=MAX_MATCH_TTL_HOURS).contains(hours))
+}
+
+fn match_ttl_label(hours: i32) -> String {
+    if hours <= 0 {
+        "無効にしない (off)".to_string()
+    } else {
+        format!("{}時間", hours)
+    }
+}
+
+fn no_winner_policy_label(policy: NoWinnerPolicy) -> &'static str {
+    match policy {
+        NoWinnerPolicy::Refund => "賭けられたポイントを全て返却 (refund)",
+        NoWinnerPolicy::Jackpot => "次の賭けに持ち越し (jackpot)",
+        NoWinnerPolicy::House => "ハウスが受け取る (house)",
+    }
+}
+
+fn rounding_policy_label(policy: RoundingPolicy) -> &'static str {
+    match policy {
+        RoundingPolicy::LargestRemainder => "端数の大きい bet から1ptずつ配分 (largest)",
+        RoundingPolicy::House => "ハウスが受け取る (house)",
+        RoundingPolicy::EarliestBettor => "最も早く bet したユーザーが受け取る (earliest)",
+    }
+}
+
+impl<R: RepositoriesModuleExt> ChannelUseCase<R> {
+    pub async fn configure_channel(
+        &self,
+        source: ConfigureChannel,
+    ) -> Result<Channel, ChannelUseCaseError> {
+        let channel_id = Id::new(source.channel_id.clone());
+        let update = match (source.key.as_deref(), source.value.as_deref()) {
+            (None, _) => None,
+            (Some("rounding"), Some(value)) if parse_rounding_policy(value).is_some() => {
+                Some(UpdateChannel::new(
+                    Id::new(source.channel_id.clone()),
+                    parse_rounding_policy(value),
+                    None,
+                    None,
+                    None,
+                ))
+            }
+            (Some("no-winner"), Some(value)) if parse_no_winner_policy(value).is_some() => {
+                Some(UpdateChannel::new(
+                    Id::new(source.channel_id.clone()),
+                    None,
+                    parse_no_winner_policy(value),
+                    None,
+                    None,
+                ))
+            }
+            (Some("commission"), Some(value)) if parse_commission_percent(value).is_some() => {
+                Some(UpdateChannel::new(
+                    Id::new(source.channel_id.clone()),
+                    None,
+                    None,
+                    parse_commission_percent(value),
+                    None,
+                ))
+            }
+            (Some("ttl"), Some(value)) if parse_match_ttl_hours(value).is_some() => {
+                Some(UpdateChannel::new(
+                    Id::new(source.channel_id.clone()),
+                    None,
+                    None,
+                    None,
+                    parse_match_ttl_hours(value),
+                ))
+            }
+            _ => {
+                self.repositories
+                    .message_traq_repository()
+                    .create(NewMessage::new(
+                        channel_id,
+                        "引数が不正です\n`@BOT_bookmaker config rounding largest|house|earliest`\n`@BOT_bookmaker config no-winner refund|jackpot|house`\n`@BOT_bookmaker config commission 0-99`\n`@BOT_bookmaker config ttl 1-8760|off`\nの形式で指定できます".to_string(),
+                        true,
+                    ))
+                    .await
+                    .map_err(|e| ChannelUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+                return Err(ChannelUseCaseError::InvalidArgument);
+            }
+        };
+
+        let channel = match update {
+            Some(update) => self.repositories.channel_repository().upsert(update).await,
+            None => {
+                self.repositories
+                    .channel_repository()
+                    .find(channel_id)
+                    .await
+            }
+        }
+        .map_err(|e| ChannelUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        self.repositories
+            .message_traq_repository()
+            .create(NewMessage::new(
+                Id::new(source.channel_id),
+                format!(
+                    "### このチャンネルの設定\n- 端数の扱い: {}\n- 勝者に誰も賭けていない場合: {}\n- 手数料: {}%\n- 終了しない賭けを無効にするまでの時間: {}\n- ハウスのポイント: {}pt\n- 持ち越されているポイント: {}pt",
+                    rounding_policy_label(channel.rounding_policy),
+                    no_winner_policy_label(channel.no_winner_policy),
+                    channel.commission_percent,
+                    match_ttl_label(channel.match_ttl_hours),
+                    channel.house_balance,
+                    channel.jackpot
+                ),
+                true,
+            ))
+            .await
+            .map_err(|e| ChannelUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        Ok(channel)
+    }
+}
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ChannelUseCaseError {
+    #[error("Invalid argument")]
+    InvalidArgument,
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(#[from] anyhow::Error),
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use adapter::modules::RepositoriesModuleExt;
+use derive_new::new;
+use kernel::model::job::{
+    Job, JobKind, JobLease, NewJob, DEFAULT_MAX_ATTEMPTS, EXPIRE_MATCHES_INTERVAL_SECS,
+};
+use kernel::model::Id;
+use kernel::repository::job::JobRepository;
+
+#[derive(new)]
+pub struct JobUseCase<R: RepositoriesModuleExt> {
+    repositories: Arc<R>,
+}
+
+impl<R: RepositoriesModuleExt> JobUseCase<R> {
+    // 放置された match を無効にするジョブを登録する。既に登録されていれば何もしない
+    pub async fn schedule_expire_matches(&self) -> Result<Job, JobUseCaseError> {
+        let now = chrono::Utc::now();
+        self.repositories
+            .job_repository()
+            .insert_unless_scheduled(NewJob::new(
+                Id::gen(),
+                JobKind::ExpireMatches,
+                "".to_string(),
+                now,
+                Some(EXPIRE_MATCHES_INTERVAL_SECS),
+                DEFAULT_MAX_ATTEMPTS,
+                now,
+            ))
+            .await
+            .map_err(|e| JobUseCaseError::UnexpectedError(anyhow::anyhow!(e)))
+    }
+    // 実行時刻を過ぎたジョブを limit 件まで取得し、実行中にする
+    pub async fn claim_due_jobs(&self, limit: u64) -> Result<Vec<Job>, JobUseCaseError> {
+        self.repositories
+            .job_repository()
+            .claim_due(chrono::Utc::now(), limit)
+            .await
+            .map_err(|e| JobUseCaseError::UnexpectedError(anyhow::anyhow!(e)))
+    }
+    pub async fn complete_job(&self, lease: JobLease) -> Result<Job, JobUseCaseError> {
+        self.repositories
+            .job_repository()
+            .complete(lease, chrono::Utc::now())
+            .await
+            .map_err(|e| JobUseCaseError::UnexpectedError(anyhow::anyhow!(e)))
+    }
+    // 失敗した理由を記録し、上限に達するまでは間隔を空けて再実行する
+    pub async fn fail_job(&self, lease: JobLease, error: String) -> Result<Job, JobUseCaseError> {
+        self.repositories
+            .job_repository()
+            .fail(lease, error, chrono::Utc::now())
+            .await
+            .map_err(|e| JobUseCaseError::UnexpectedError(anyhow::anyhow!(e)))
+    }
+}
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum JobUseCaseError {
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(#[from] anyhow::Error),
+}
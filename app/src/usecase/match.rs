@@ -2,27 +2,98 @@ use std::sync::Arc;
 
 use adapter::modules::RepositoriesModuleExt;
 use derive_new::new;
+use kernel::model::bet::{Bet, BetType};
 use kernel::model::candidate::NewCandidate;
 use kernel::model::channel::Channel;
+use kernel::model::lmsr::DEFAULT_LIQUIDITY;
 use kernel::model::message::NewMessage;
-use kernel::model::r#match::Match;
+use kernel::model::parlay::{LegResult, ParlaySlip, ParlayStatus};
+use kernel::model::r#match::{
+    over_under_winners, parse_deadline, MarketType, Match, MatchStatus, NewMatch, Participation,
+    RefundedMatch, TransitionError, GUESS_CANDIDATE_NAME, OVER_CANDIDATE_NAME,
+    UNDER_CANDIDATE_NAME,
+};
+use kernel::model::settlement::NoWinnerPolicy;
+use kernel::model::settlement::SettleMatchForLatest;
 use kernel::model::stamp::{NewStamp, StampType};
-use kernel::model::user::UpdateBalance;
-use kernel::model::{statistic, Id};
+use kernel::model::Id;
 use kernel::repository::error::RepositoryError;
 use kernel::repository::{
-    bet::BetRepository, candidate::CandidateRepository, r#match::MatchRepository,
-    user::UserRepository,
+    bet::BetRepository, candidate::CandidateRepository, channel::ChannelRepository,
+    r#match::MatchRepository, user::UserRepository,
 };
 use kernel::traq::{message::MessageTraqRepository, stamp::StampTraqRepository};
 
-use crate::model::r#match::{CloseMatch, CreateMatch, FinishMatch};
+use crate::model::r#match::{CancelMatch, CloseMatch, CreateMatch, FinishMatch, VoidMatch};
+use crate::usecase::{
+    format_hundredths, parse_hundredths, transition_error_message, AMBIGUOUS_MATCH_MESSAGE,
+};
 
 #[derive(new)]
 pub struct MatchUseCase<R: RepositoriesModuleExt> {
     repositories: Arc<R>,
 }
 
+// `候補名:1.8` の形式で倍率が指定されていれば、100 倍した整数に変換して返す
+fn parse_candidate(source: &str) -> (String, Option<f64>) {
+    match source.rsplit_once(':') {
+        Some((name, odds)) if !name.is_empty() => match odds.parse::<f64>() {
+            Ok(odds) => (name.to_string(), Some(odds)),
+            Err(_) => (source.to_string(), None),
+        },
+        _ => (source.to_string(), None),
+    }
+}
+
+// `候補名 -3.5` のように候補名の直後に符号付きの数値があれば、その候補のハンディキャップとして扱う
+fn parse_handicaps(sources: Vec<String>) -> (Vec<String>, Vec<Option<i32>>) {
+    let mut names: Vec<String> = Vec::new();
+    let mut handicaps: Vec<Option<i32>> = Vec::new();
+    for source in sources {
+        let handicap = if source.starts_with('+') || source.starts_with('-') {
+            parse_hundredths(&source)
+        } else {
+            None
+        };
+        match (handicap, handicaps.last_mut()) {
+            (Some(handicap), Some(last @ None)) => *last = Some(handicap),
+            _ => {
+                names.push(source);
+                handicaps.push(None);
+            }
+        }
+    }
+    (names, handicaps)
+}
+
+// `--min-bettors 3` のようなフラグとその値を取り除き、値を返す。値が正の整数でなければ Some(None) を返す
+fn take_threshold(sources: Vec<String>, flag: &str) -> (Vec<String>, Option<Option<i32>>) {
+    match sources.iter().position(|s| s == flag) {
+        Some(i) => {
+            let threshold = sources
+                .get(i + 1)
+                .and_then(|value| value.parse::<i32>().ok())
+                .filter(|n| *n > 0);
+            let rest = sources
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i && *j != i + 1)
+                .map(|(_, s)| s.clone())
+                .collect::<Vec<_>>();
+            (rest, Some(threshold))
+        }
+        None => (sources, None),
+    }
+}
+
+fn odds_to_hundredths(odds: f64) -> Option<i32> {
+    if odds.is_finite() && odds > 1.0 && odds <= 1000.0 {
+        Some((odds * 100.0).round() as i32)
+    } else {
+        None
+    }
+}
+
 struct BalanceDiff {
     pub traq_display_id: String,
     pub diff: i32,
@@ -35,12 +106,89 @@ impl<R: RepositoriesModuleExt> MatchUseCase<R> {
         match_source: CreateMatch,
         candidates_source: Vec<String>,
     ) -> Result<Match, MatchUseCaseError> {
-        if candidates_source.len() < 2 {
+        // `--until 19:55` か `--in 30m` が指定された場合は、その時刻に bet を自動で締め切る
+        let (candidates_source, close_at) = match candidates_source
+            .iter()
+            .position(|s| s == "--until" || s == "--in")
+        {
+            Some(i) => {
+                let close_at = candidates_source.get(i + 1).and_then(|value| {
+                    parse_deadline(&candidates_source[i], value, chrono::Utc::now())
+                });
+                let rest = candidates_source
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i && *j != i + 1)
+                    .map(|(_, s)| s.clone())
+                    .collect::<Vec<_>>();
+                (rest, Some(close_at))
+            }
+            None => (candidates_source, None),
+        };
+        if close_at == Some(None) {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    Id::new(match_source.channel_id),
+                    "締め切りは`--until 19:55`のように JST の時刻で指定するか、`--in 30m`や`--in 1h30m`のように今からの時間で指定してください".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(MatchUseCaseError::InvalidDeadline);
+        }
+        // `--min-bettors` と `--min-candidates` が指定された場合は、締め切り時に条件を満たさなければ無効にする
+        let (candidates_source, min_bettors) = take_threshold(candidates_source, "--min-bettors");
+        let (candidates_source, min_candidates) =
+            take_threshold(candidates_source, "--min-candidates");
+        // 候補の後に `--lmsr` が指定された場合は株を売買する賭けにする。続けて流動性を指定できる
+        let (candidates_source, liquidity) =
+            match candidates_source.iter().position(|s| s == "--lmsr") {
+                Some(i) => {
+                    let liquidity = match candidates_source.get(i + 1) {
+                        Some(value) => value.parse::<i32>().ok().filter(|b| *b > 0),
+                        None => Some(DEFAULT_LIQUIDITY),
+                    };
+                    (candidates_source[..i].to_vec(), Some(liquidity))
+                }
+                None => (candidates_source, None),
+            };
+        if liquidity == Some(None) {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    Id::new(match_source.channel_id),
+                    "流動性は正の整数で指定してください\n`@BOT_bookmaker start 賭け名 候補A 候補B --lmsr 100`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(MatchUseCaseError::InvalidLiquidity);
+        }
+        // `--closest` が指定された場合は候補の代わりに数値を予想する賭けにする
+        let is_closest_guess = candidates_source.as_slice() == ["--closest"];
+        // `--over-under 24.5` の形式で基準値が指定された場合は Over と Under を候補にする
+        let (candidates_source, over_under_line) = match candidates_source.as_slice() {
+            _ if is_closest_guess => (vec![GUESS_CANDIDATE_NAME.to_string()], None),
+            [flag, line] if flag == "--over-under" => match parse_hundredths(line) {
+                Some(line) => (
+                    vec![
+                        OVER_CANDIDATE_NAME.to_string(),
+                        UNDER_CANDIDATE_NAME.to_string(),
+                    ],
+                    Some(line),
+                ),
+                None => (Vec::new(), None),
+            },
+            _ => (candidates_source, None),
+        };
+        let (candidates_source, handicaps) = parse_handicaps(candidates_source);
+        if candidates_source.len() < 2 && !is_closest_guess {
             self.repositories
                 .message_traq_repository()
                 .create(NewMessage::new(
                     Id::new(match_source.channel_id),
-                    "賭けの対象となる候補を2つ以上指定してください\n`@BOT_bookmaker start 賭け名 候補A 候補B`の形式で指定できます".to_string(),
+                    "賭けの対象となる候補を2つ以上指定してください\n`@BOT_bookmaker start 賭け名 候補A 候補B`の形式で指定できます\nover/under の賭けは`@BOT_bookmaker start 賭け名 --over-under 24.5`の形式で基準値を指定できます\n数値を予想する賭けは`@BOT_bookmaker start 賭け名 --closest`の形式で開始できます".to_string(),
                     true,
                 ))
                 .await
@@ -50,23 +198,107 @@ impl<R: RepositoriesModuleExt> MatchUseCase<R> {
             return Err(MatchUseCaseError::CandidateMustNotBeEmpty);
         }
 
+        let parsed = candidates_source
+            .iter()
+            .map(|source| parse_candidate(source))
+            .collect::<Vec<_>>();
+        let odds = parsed
+            .iter()
+            .filter_map(|(_, odds)| odds.map(odds_to_hundredths))
+            .collect::<Vec<_>>();
+        // 倍率は全ての候補に指定するか、どの候補にも指定しないかのどちらか
+        if !odds.is_empty() && (odds.len() != parsed.len() || odds.iter().any(|o| o.is_none())) {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    Id::new(match_source.channel_id),
+                    "倍率は全ての候補に1より大きい値で指定してください\n`@BOT_bookmaker start 賭け名 候補A:1.8 候補B:2.1`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(MatchUseCaseError::InvalidOdds);
+        }
+        // LMSR の賭けでは株の価格が倍率を表すので、倍率やハンディキャップは指定できない
+        if liquidity.is_some() && (!odds.is_empty() || handicaps.iter().any(|h| h.is_some())) {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    Id::new(match_source.channel_id),
+                    "`--lmsr`を指定した賭けでは倍率やハンディキャップは指定できません".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(MatchUseCaseError::InvalidLiquidity);
+        }
+        // ハンディキャップは 2 つの候補の対戦にだけ指定できる
+        if handicaps.iter().any(|h| h.is_some()) && (parsed.len() != 2 || !odds.is_empty()) {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    Id::new(match_source.channel_id),
+                    "ハンディキャップは候補が2つの賭けにだけ指定できます\n`@BOT_bookmaker start 賭け名 候補A -3.5 候補B`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(MatchUseCaseError::InvalidHandicap);
+        }
+        // 数値を予想する賭けには候補が 1 つしか無いので、候補の数は指定できない
+        let invalid_min_candidates = match min_candidates {
+            Some(Some(min)) => is_closest_guess || min as usize > parsed.len(),
+            Some(None) => true,
+            None => false,
+        };
+        if min_bettors == Some(None) || invalid_min_candidates {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    Id::new(match_source.channel_id),
+                    "`--min-bettors`には bet する人数の下限を、`--min-candidates`には bet される候補の数の下限を正の整数で指定してください\n候補の数は候補の総数以下で指定し、数値を予想する賭けには指定できません\n`@BOT_bookmaker start 賭け名 候補A 候補B --min-bettors 3 --min-candidates 2`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(MatchUseCaseError::InvalidThreshold);
+        }
+
+        let mut new_match: NewMatch = match_source.into();
+        if !odds.is_empty() {
+            new_match.market_type = MarketType::FixedOdds;
+        }
+        new_match.over_under_line = over_under_line;
+        new_match.close_at = close_at.flatten();
+        new_match.min_bettors = min_bettors.flatten();
+        new_match.min_candidates = min_candidates.flatten();
+        if is_closest_guess {
+            new_match.market_type = MarketType::ClosestGuess;
+        }
+        if let Some(liquidity) = liquidity {
+            new_match.market_type = MarketType::Lmsr;
+            new_match.liquidity = liquidity;
+        }
         let match_ = self
             .repositories
             .match_repository()
-            .insert(match_source.into())
+            .insert(new_match)
             .await
-            .map_err(|e| match e {
-                RepositoryError::DuplicatedRecord(_) => {
-                    MatchUseCaseError::EnabledMatchAlreadyExists
-                }
-                _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-            })?;
+            .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
 
         let match_id_str = match_.id.value.clone();
-        let new_candidates = candidates_source
-            .clone()
+        let new_candidates = parsed
             .into_iter()
-            .map(|name| NewCandidate::new(Id::gen(), name, Id::new(match_id_str.clone())))
+            .zip(handicaps)
+            .map(|((name, odds), handicap)| {
+                NewCandidate::new(
+                    Id::gen(),
+                    name,
+                    Id::new(match_id_str.clone()),
+                    odds.and_then(odds_to_hundredths),
+                    handicap,
+                )
+            })
             .collect::<Vec<_>>();
 
         self.repositories
@@ -80,216 +312,665 @@ impl<R: RepositoriesModuleExt> MatchUseCase<R> {
         Ok(match_)
     }
     pub async fn close_match(&self, source: CloseMatch) -> Result<Match, MatchUseCaseError> {
-        let message_id = Id::new(source.message_id.clone());
-        let match_ = self
+        let message_id = source.message_id.clone().map(Id::new);
+        let channel_id = Id::new(source.channel_id.clone());
+        let participation = match self
             .repositories
             .match_repository()
             .update_for_latest(source.into())
             .await
-            .map_err(|e| match e {
-                RepositoryError::RecordNotFound(_) => MatchUseCaseError::EnabledMatchNotFound,
-                _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-            })?;
+        {
+            Ok(participation) => participation,
+            Err(e) => return Err(self.reply_error(channel_id, e).await),
+        };
+        let match_ = match &participation {
+            Participation::Met(match_) => match_,
+            Participation::Voided(voided) => &voided.r#match,
+        };
 
-        self.repositories
-            .stamp_repository()
-            .create(NewStamp::new(message_id, StampType::WhiteCheckMark))
-            .await
-            .map_err(|e| match e {
-                _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-            })?;
+        if let Some(message_id) = message_id {
+            self.repositories
+                .stamp_repository()
+                .create(NewStamp::new(message_id, StampType::WhiteCheckMark))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        } else {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    format!(
+                        "#{} 「{}」は締め切り時刻になったため、bet を締め切りました",
+                        match_.number, match_.title
+                    ),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        }
 
-        Ok(match_)
+        match participation {
+            Participation::Met(match_) => Ok(match_),
+            Participation::Voided(voided) => self.send_below_threshold(*voided).await,
+        }
+    }
+    // 参加の条件を満たさずに無効になった match について、返却したポイントを通知する
+    async fn send_below_threshold(
+        &self,
+        voided: RefundedMatch,
+    ) -> Result<Match, MatchUseCaseError> {
+        let match_ = &voided.r#match;
+        let channel_id = Id::new(match_.channel_id.value.clone());
+        let conditions = [
+            match_.min_bettors.map(|min| format!("{}人以上が bet", min)),
+            match_
+                .min_candidates
+                .map(|min| format!("{}つ以上の候補に bet", min)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join("、");
+        self.send_refunds(
+            channel_id,
+            &voided,
+            &format!(
+                "参加の条件 ({}) を満たさなかったため、無効にしました",
+                conditions
+            ),
+        )
+        .await?;
+        self.send_parlays(&voided.parlays).await?;
+
+        Ok(voided.r#match)
+    }
+    // 締め切り時刻になった match を締め切る。既に締め切られているなどで受付中でなければ何もしない
+    pub async fn close_match_by_deadline(
+        &self,
+        match_id: Id<Match>,
+    ) -> Result<Option<Match>, MatchUseCaseError> {
+        let match_ = self
+            .repositories
+            .match_repository()
+            .find(match_id)
+            .await
+            .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        match match_ {
+            Some(m) if m.status == MatchStatus::Open => self
+                .close_match(CloseMatch::new(m.channel_id.value, Some(m.number), None))
+                .await
+                .map(Some),
+            _ => Ok(None),
+        }
     }
     pub async fn finish_match(&self, source: FinishMatch) -> Result<Match, MatchUseCaseError> {
         let channel_id = Id::new(source.channel_id.clone());
-        let winner_candidate_name = source.winner_candidate_name.clone();
-        let match_result = self
+        let placing = source.placing_candidate_names();
+        let has_invalid_placing = placing
+            .iter()
+            .enumerate()
+            .any(|(i, name)| name.is_empty() || placing[..i].contains(name));
+        if source.winner_candidate_names.is_empty() || has_invalid_placing {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    "勝者を1つ以上指定してください\n`@BOT_bookmaker finish 勝者名`の形式で指定できます\n同着の場合は`@BOT_bookmaker finish 勝者A 勝者B`のように複数指定できます\n着順は`@BOT_bookmaker finish 1着>2着>3着`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(MatchUseCaseError::CandidateMustNotBeEmpty);
+        }
+        let winner_candidate_names = match placing.first() {
+            Some(winner) => format!("勝者は{}です\n着順: {}", winner, placing.join(" > ")),
+            None => format!("勝者は{}です", source.winner_candidate_names.join("、")),
+        };
+        // match が定まらない場合のエラーは精算する際に返す
+        let latest = match self
             .repositories
             .match_repository()
-            .update_for_latest(source.into())
+            .find_latest(Id::new(channel_id.value.clone()), source.match_number)
+            .await
+        {
+            Ok(latest) => latest,
+            Err(RepositoryError::AmbiguousMatch) => None,
+            Err(e) => return Err(MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e))),
+        };
+        let over_under_line = latest.as_ref().and_then(|m| m.over_under_line);
+        let is_closest_guess = latest
+            .as_ref()
+            .is_some_and(|m| m.market_type == MarketType::ClosestGuess);
+        let result_value = match source.winner_candidate_names.as_slice() {
+            [value] => parse_hundredths(value),
+            _ => None,
+        };
+        if is_closest_guess && result_value.is_none() {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    "数値を予想する賭けでは結果の数値を指定してください\n`@BOT_bookmaker finish 26`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(MatchUseCaseError::CandidateMustNotBeEmpty);
+        }
+        // ハンディキャップのある賭けでは、全ての候補の得点から勝者を決める
+        let candidates = match &latest {
+            Some(m) => self
+                .repositories
+                .candidate_repository()
+                .select_by_match_id(Id::new(m.id.value.clone()))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?,
+            None => Vec::new(),
+        };
+        let is_handicap = candidates.iter().any(|c| c.handicap.is_some());
+        let scores = source.scores().filter(|scores| {
+            scores.len() == candidates.len()
+                && candidates
+                    .iter()
+                    .all(|c| scores.iter().any(|(name, _)| name == &c.name))
+        });
+        if is_handicap && scores.is_none() {
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    channel_id,
+                    "ハンディキャップのある賭けでは全ての候補の得点を指定してください\n`@BOT_bookmaker finish 候補A 3 候補B 1`の形式で指定できます".to_string(),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            return Err(MatchUseCaseError::InvalidScores);
+        }
+        let mut settle: SettleMatchForLatest = source.into();
+        let winner_candidate_names = match (over_under_line, result_value) {
+            // 勝者は精算の際にハンディキャップを加えた得点から決まるので、精算後に表示を作る
+            _ if is_handicap => {
+                settle.winner_candidate_names = Vec::new();
+                settle.scores = scores.unwrap_or_default();
+                "".to_string()
+            }
+            // 数値を予想する賭けは候補ではなく予想との差で勝者を決める
+            (_, Some(value)) if is_closest_guess => {
+                settle.winner_candidate_names = Vec::new();
+                settle.result_value = Some(value);
+                format!("結果は{}です", format_hundredths(value))
+            }
+            // 結果が基準値と等しい場合は引き分け (push) として、全ての bet を返却する
+            (Some(line), Some(value)) if value == line => {
+                settle.winner_candidate_names = Vec::new();
+                settle.result_value = Some(value);
+                format!(
+                    "結果は引き分けです\n結果: {} (基準値: {})\n基準値と同じため、賭けられたポイントを全て返却しました",
+                    format_hundredths(value),
+                    format_hundredths(line)
+                )
+            }
+            // over/under の賭けで結果の値が指定された場合は、基準値と比べて勝者を決める
+            (Some(line), Some(value)) => {
+                settle.winner_candidate_names = over_under_winners(line, value);
+                settle.result_value = Some(value);
+                format!(
+                    "勝者は{}です\n結果: {} (基準値: {})",
+                    settle.winner_candidate_names.join("、"),
+                    format_hundredths(value),
+                    format_hundredths(line)
+                )
+            }
+            _ => winner_candidate_names,
+        };
+        let settle_result = self
+            .repositories
+            .match_repository()
+            .settle_latest(settle)
             .await;
 
-        let match_ = match match_result {
-            Ok(match_) => match_,
-            Err(e) => {
-                let error_with_message = {
-                    match e {
-                        RepositoryError::RecordNotFound(_) => (
-                            "有効な賭けが見つかりませんでした",
-                            MatchUseCaseError::EnabledMatchNotFound,
-                        ),
-                        RepositoryError::DuplicatedRecord(_) => (
-                            "賭けの勝者は既に設定されています",
-                            MatchUseCaseError::WinnerCandidateAlreadySet,
-                        ),
-                        _ => (
-                            "予期せぬエラーが発生しました",
-                            MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-                        ),
-                    }
-                };
-                self.repositories
-                    .message_traq_repository()
-                    .create(NewMessage::new(
-                        channel_id,
-                        error_with_message.0.to_string(),
-                        true,
-                    ))
-                    .await
-                    .map_err(|e| match e {
-                        _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-                    })?;
-                return Err(error_with_message.1);
-            }
+        let settled = match settle_result {
+            Ok(Participation::Met(settled)) => settled,
+            // start で指定された参加の条件を満たしていない場合は、精算せずに無効にしてポイントを返却している
+            Ok(Participation::Voided(voided)) => return self.send_below_threshold(*voided).await,
+            Err(e) => return Err(self.reply_error(channel_id, e).await),
         };
 
-        // ポイントの増減計算
-        let candidates = self
-            .repositories
-            .candidate_repository()
-            .select_by_match_id(Id::new(match_.id.value.clone()))
-            .await
-            .map_err(|e| match e {
-                _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-            })?;
-        let bets = self
-            .repositories
-            .bet_repository()
-            .select_by_match_id(Id::new(match_.id.value.clone()))
-            .await
-            .map_err(|e| match e {
-                _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-            })?;
-        let statistics = statistic::new_statistics(bets, candidates);
+        let winner_candidate_names = if is_handicap {
+            let candidates = self
+                .repositories
+                .candidate_repository()
+                .select_by_match_id(Id::new(settled.r#match.id.value.clone()))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+            let result = candidates
+                .iter()
+                .map(|c| match c.handicap {
+                    Some(handicap) if handicap > 0 => format!(
+                        "{} {} (+{})",
+                        c.name,
+                        c.score.unwrap_or_default(),
+                        format_hundredths(handicap)
+                    ),
+                    Some(handicap) => format!(
+                        "{} {} ({})",
+                        c.name,
+                        c.score.unwrap_or_default(),
+                        format_hundredths(handicap)
+                    ),
+                    None => format!("{} {}", c.name, c.score.unwrap_or_default()),
+                })
+                .collect::<Vec<_>>()
+                .join(" - ");
+            match candidates.iter().find(|c| c.is_winner == Some(true)) {
+                Some(winner) => format!("勝者は{}です\n結果: {}", winner.name, result),
+                None => format!(
+                    "結果は引き分けです\n結果: {}\nハンディキャップを加えると同点のため、賭けられたポイントを全て返却しました",
+                    result
+                ),
+            }
+        } else {
+            winner_candidate_names
+        };
 
-        // ポイントの増減処理
+        // 払い戻しは精算のトランザクション内で反映済みなので、ここでは結果の表示だけを行う
         let users = self
             .repositories
             .user_repository()
             .select_by_channel_id(Id::new(channel_id.value.clone()))
             .await
-            .map_err(|e| match e {
-                _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-            })?;
-        let mut diffs = Vec::new();
-        let mut update_balance_vec = Vec::new();
-        for statistic in statistics {
-            statistic.bets.into_iter().for_each(|bet| {
+            .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        let distribution = &settled.distribution;
+        let diffs = distribution
+            .payouts
+            .iter()
+            .filter_map(|payout| {
                 users
                     .iter()
-                    .find(|u| u.id.value == bet.user_id.value)
-                    .map(|user| {
-                        let is_correct = statistic.candidate.name == winner_candidate_name;
-                        // 内部的には bet したタイミングでポイントを差し引いているので、プラスだけを考えるが送信するメッセージにはマイナスも見せるようにする
-                        let mut plus = 0.0;
-
-                        if is_correct {
-                            plus = bet.amount as f64 * statistic.rate;
-                            update_balance_vec.push(UpdateBalance::new(
-                                Id::new(user.id.value.clone()),
-                                user.balance + plus as i32,
-                            ));
-                        }
-
-                        diffs.push(BalanceDiff {
-                            traq_display_id: user.traq_display_id.clone(),
-                            diff: plus as i32 - bet.amount,
-                            result: user.balance + plus as i32,
-                        });
-                    });
-            });
-        }
-        self.repositories
-            .user_repository()
-            .update_balance(update_balance_vec)
-            .await
-            .map_err(|e| match e {
-                _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-            })?;
+                    .find(|u| u.id.value == payout.user_id.value)
+                    .map(|user| BalanceDiff {
+                        traq_display_id: user.traq_display_id.clone(),
+                        diff: payout.amount - payout.stake,
+                        result: user.balance,
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let policy_content = match distribution.no_winner_policy {
+            // 固定オッズや LMSR ではハウスが払い戻しを負担するので、ハウスの収支を表示する
+            None if matches!(
+                settled.r#match.market_type,
+                MarketType::FixedOdds | MarketType::Lmsr
+            ) =>
+            {
+                format!("\nハウスの収支: {:+}pt", distribution.house)
+            }
+            None => {
+                let mut content = "".to_string();
+                if distribution.jackpot > 0 {
+                    content += &format!(
+                        "\n持ち越された{}ptがプールに上乗せされました",
+                        distribution.jackpot
+                    );
+                }
+                if distribution.commission > 0 {
+                    content += &format!(
+                        "\n手数料として{}ptをハウスが受け取りました",
+                        distribution.commission
+                    );
+                }
+                content
+            }
+            Some(NoWinnerPolicy::Refund) => {
+                "\n勝者に賭けた人がいなかったため、賭けられたポイントを全て返却しました".to_string()
+            }
+            Some(NoWinnerPolicy::Jackpot) => format!(
+                "\n勝者に賭けた人がいなかったため、{}ptを次の賭けに持ち越します",
+                distribution.rollover
+            ),
+            Some(NoWinnerPolicy::House) => format!(
+                "\n勝者に賭けた人がいなかったため、{}ptはハウスが受け取りました",
+                distribution.house
+            ),
+        };
+
+        // 数値を予想する賭けでは、結果に近い順に予想を並べる
+        let ranking_content = match settled.r#match.result_value {
+            Some(result) if settled.r#match.market_type == MarketType::ClosestGuess => {
+                let mut bets = self
+                    .repositories
+                    .bet_repository()
+                    .select_by_match_id(Id::new(settled.r#match.id.value.clone()))
+                    .await
+                    .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?
+                    .into_iter()
+                    .filter(|b| b.bet_type == BetType::Guess)
+                    .collect::<Vec<_>>();
+                let distance =
+                    |bet: &Bet| (bet.guess.unwrap_or_default() as i64 - result as i64).abs();
+                bets.sort_by_key(distance);
+                bets.iter()
+                    .enumerate()
+                    .fold("\n".to_string(), |acc, (i, bet)| {
+                        // 差が同じ予想は同じ順位にする
+                        let rank = bets
+                            .iter()
+                            .position(|b| distance(b) == distance(bet))
+                            .unwrap_or(i)
+                            + 1;
+                        let payout = distribution
+                            .payouts
+                            .iter()
+                            .find(|p| p.bet_id.value == bet.id.value)
+                            .map(|p| p.amount)
+                            .unwrap_or_default();
+                        format!(
+                            "{}\n{}位 :@{}: 予想 {} (差 {}) {}pt bet → {}pt",
+                            acc,
+                            rank,
+                            users
+                                .iter()
+                                .find(|u| u.id.value == bet.user_id.value)
+                                .map(|u| u.traq_display_id.clone())
+                                .unwrap_or("unknown".to_string()),
+                            format_hundredths(bet.guess.unwrap_or_default()),
+                            format_hundredths(distance(bet) as i32),
+                            bet.amount,
+                            payout
+                        )
+                    })
+            }
+            _ => "".to_string(),
+        };
 
-        let channel_id = Id::new(match_.channel_id.value.clone());
         self.repositories
             .message_traq_repository()
             .create(NewMessage::new(
                 channel_id,
                 format!(
-                    "### 「{}」の勝者は{}です\n{}",
-                    match_.title,
-                    winner_candidate_name,
+                    "### #{} 「{}」の{}{}{}\n{}",
+                    settled.r#match.number,
+                    settled.r#match.title,
+                    winner_candidate_names,
+                    policy_content,
+                    ranking_content,
                     diffs.iter().fold("".to_string(), |acc, diff| {
                         format!(
-                            "{}\n::@{}: {}pt({}pt)",
-                            acc,
-                            diff.traq_display_id,
-                            diff.diff.abs(),
-                            diff.result
+                            "{}\n:@{}: {:+}pt({}pt)",
+                            acc, diff.traq_display_id, diff.diff, diff.result
                         )
                     })
                 ),
                 true,
             ))
             .await
-            .map_err(|e| match e {
-                _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-            })?;
+            .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        self.send_parlays(&settled.parlays).await?;
 
-        Ok(match_)
+        Ok(settled.r#match)
     }
-    pub async fn delete_match(&self, channel_id: Id<Channel>) -> Result<(), MatchUseCaseError> {
-        let delete_result = self
+    pub async fn cancel_match(&self, source: CancelMatch) -> Result<Match, MatchUseCaseError> {
+        let channel_id = Id::new(source.channel_id.clone());
+        let cancel_result = self
             .repositories
             .match_repository()
-            .delete_latest(Id::new(channel_id.value.clone()))
+            .cancel_latest(source.into())
             .await;
-        if let Err(e) = delete_result {
-            match e {
-                RepositoryError::RecordNotFound(_) => {
-                    self.repositories
-                        .message_traq_repository()
-                        .create(NewMessage::new(
-                            channel_id,
-                            "有効な賭けが見つかりませんでした".to_string(),
-                            true,
-                        ))
-                        .await
-                        .map_err(|e| match e {
-                            _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-                        })?;
-                    return Err(MatchUseCaseError::EnabledMatchNotFound);
-                }
-                _ => {
-                    return Err(MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)));
-                }
+
+        let cancelled = match cancel_result {
+            Ok(cancelled) => cancelled,
+            Err(e) => return Err(self.reply_error(channel_id, e).await),
+        };
+
+        self.send_refunds(channel_id, &cancelled, "キャンセルしました")
+            .await?;
+        self.send_parlays(&cancelled.parlays).await?;
+
+        Ok(cancelled.r#match)
+    }
+    pub async fn void_match(&self, source: VoidMatch) -> Result<Match, MatchUseCaseError> {
+        let channel_id = Id::new(source.channel_id.clone());
+        let void_result = self
+            .repositories
+            .match_repository()
+            .void_latest(source.into())
+            .await;
+
+        let voided = match void_result {
+            Ok(voided) => voided,
+            Err(e) => return Err(self.reply_error(channel_id, e).await),
+        };
+
+        self.send_refunds(channel_id, &voided, "無効にしました")
+            .await?;
+        self.send_parlays(&voided.parlays).await?;
+
+        Ok(voided.r#match)
+    }
+    // チャンネルの設定した時間が経っても終了していない match を無効にし、理由と返却したポイントを送信する
+    // match ごとに無効にするので、途中で失敗した match があっても残りの match は無効にし、失敗をまとめて返す
+    pub async fn void_expired_matches(
+        &self,
+    ) -> Result<(Vec<Match>, Vec<MatchUseCaseError>), MatchUseCaseError> {
+        let expired = self
+            .repositories
+            .match_repository()
+            .find_expired(chrono::Utc::now())
+            .await
+            .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        let mut matches = Vec::new();
+        let mut errors = Vec::new();
+        for match_ in expired {
+            match self.void_expired_match(match_.id).await {
+                Ok(Some(match_)) => matches.push(match_),
+                Ok(None) => {}
+                Err(e) => errors.push(e),
             }
         }
 
+        Ok((matches, errors))
+    }
+    async fn void_expired_match(
+        &self,
+        match_id: Id<Match>,
+    ) -> Result<Option<Match>, MatchUseCaseError> {
+        let Some(voided) = self
+            .repositories
+            .match_repository()
+            .void_expired(match_id, chrono::Utc::now())
+            .await
+            .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?
+        else {
+            return Ok(None);
+        };
+
+        let channel_id = Id::new(voided.r#match.channel_id.value.clone());
+        let channel = self
+            .repositories
+            .channel_repository()
+            .find(Id::new(channel_id.value.clone()))
+            .await
+            .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        let since = match voided.r#match.close_at {
+            Some(_) => "締め切り",
+            None => "作成",
+        };
+        self.send_refunds(
+            channel_id,
+            &voided,
+            &format!(
+                "{}から{}時間経っても終了しなかったため、無効にしました\n時間は`@BOT_bookmaker config ttl`で変更できます",
+                since, channel.match_ttl_hours
+            ),
+        )
+        .await?;
+        self.send_parlays(&voided.parlays).await?;
+
+        Ok(Some(voided.r#match))
+    }
+    // 返却したポイントの一覧をチャンネルに送信する
+    async fn send_refunds(
+        &self,
+        channel_id: Id<Channel>,
+        refunded: &RefundedMatch,
+        action: &str,
+    ) -> Result<(), MatchUseCaseError> {
+        let users = self
+            .repositories
+            .user_repository()
+            .select_by_channel_id(Id::new(channel_id.value.clone()))
+            .await
+            .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        let refunds_content = if refunded.refunds.is_empty() {
+            "\n返却するポイントはありません".to_string()
+        } else {
+            refunded.refunds.iter().fold("".to_string(), |acc, refund| {
+                // 売却で利益が出ていた LMSR の株は、利益の分を回収する
+                let amount = if refund.amount < 0 {
+                    format!("{}pt回収", -refund.amount)
+                } else {
+                    format!("{}pt返却", refund.amount)
+                };
+                format!(
+                    "{}\n:@{}: {}({}pt)",
+                    acc,
+                    users
+                        .iter()
+                        .find(|u| u.id.value == refund.user_id.value)
+                        .map(|u| u.traq_display_id.clone())
+                        .unwrap_or("unknown".to_string()),
+                    amount,
+                    refund.balance
+                )
+            })
+        };
+
         self.repositories
             .message_traq_repository()
             .create(NewMessage::new(
                 channel_id,
-                "最新の賭けをキャンセルしました".to_string(),
+                format!(
+                    "### #{} 「{}」を{}\n{}",
+                    refunded.r#match.number, refunded.r#match.title, action, refunds_content
+                ),
                 true,
             ))
             .await
-            .map_err(|e| match e {
-                _ => MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
-            })?;
+            .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
 
         Ok(())
     }
+    // 全ての leg の結果が出たパーレーの結果を、パーレーを作成したチャンネルに送信する
+    async fn send_parlays(&self, parlays: &[ParlaySlip]) -> Result<(), MatchUseCaseError> {
+        for slip in parlays {
+            let parlay = &slip.parlay;
+            let display_id = self
+                .repositories
+                .user_repository()
+                .select_by_channel_id(Id::new(parlay.channel_id.value.clone()))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?
+                .into_iter()
+                .find(|u| u.id.value == parlay.user_id.value)
+                .map(|u| u.traq_display_id)
+                .unwrap_or("unknown".to_string());
+            let payout = parlay.payout.unwrap_or_default();
+            let result_content = match parlay.status {
+                ParlayStatus::Won => {
+                    let odds = slip
+                        .legs
+                        .iter()
+                        .filter(|l| l.result == LegResult::Won)
+                        .map(|l| format!("x{}", format_hundredths(l.odds.unwrap_or(100))))
+                        .collect::<Vec<_>>()
+                        .join(" × ");
+                    format!("的中しました！ {}ptを払い戻しました ({})", payout, odds)
+                }
+                ParlayStatus::Lost => format!("外れました ({}pt)", parlay.amount),
+                ParlayStatus::Refunded => {
+                    format!("全ての賭けが無効になったため、{}ptを返却しました", payout)
+                }
+                ParlayStatus::Open => continue,
+            };
+            self.repositories
+                .message_traq_repository()
+                .create(NewMessage::new(
+                    Id::new(parlay.channel_id.value.clone()),
+                    format!("### パーレーの結果\n:@{}: {}", display_id, result_content),
+                    true,
+                ))
+                .await
+                .map_err(|e| MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        }
+        Ok(())
+    }
+    // repository のエラーを usecase のエラーに変換し、その内容をチャンネルに送信する
+    async fn reply_error(&self, channel_id: Id<Channel>, e: RepositoryError) -> MatchUseCaseError {
+        let (content, error) = match e {
+            RepositoryError::RecordNotFound(s) if s.contains("Candidate") => (
+                "指定した候補が見つかりませんでした".to_string(),
+                MatchUseCaseError::CandidateNotFound,
+            ),
+            RepositoryError::RecordNotFound(_) => (
+                "有効な賭けが見つかりませんでした".to_string(),
+                MatchUseCaseError::EnabledMatchNotFound,
+            ),
+            RepositoryError::AmbiguousMatch => (
+                AMBIGUOUS_MATCH_MESSAGE.to_string(),
+                MatchUseCaseError::AmbiguousMatch,
+            ),
+            RepositoryError::InvalidTransition(e) => (
+                transition_error_message(&e),
+                MatchUseCaseError::InvalidTransition(e),
+            ),
+            RepositoryError::DuplicatedRecord(_) => (
+                "この賭けは既に精算されています".to_string(),
+                MatchUseCaseError::AlreadySettled,
+            ),
+            _ => (
+                "予期せぬエラーが発生しました".to_string(),
+                MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
+            ),
+        };
+        if let Err(e) = self
+            .repositories
+            .message_traq_repository()
+            .create(NewMessage::new(channel_id, content, true))
+            .await
+        {
+            return MatchUseCaseError::UnexpectedError(anyhow::anyhow!(e));
+        }
+        error
+    }
 }
 
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum MatchUseCaseError {
-    #[error("Winner candidate already set")]
-    WinnerCandidateAlreadySet,
+    #[error("Invalid transition: {0}")]
+    InvalidTransition(TransitionError),
+    #[error("Candidate not found")]
+    CandidateNotFound,
+    #[error("Match already settled")]
+    AlreadySettled,
     #[error("Candidates must not be empty")]
     CandidateMustNotBeEmpty,
-    #[error("Enabled match already exists")]
-    EnabledMatchAlreadyExists,
+    #[error("Invalid odds")]
+    InvalidOdds,
+    #[error("Invalid handicap")]
+    InvalidHandicap,
+    #[error("Invalid scores")]
+    InvalidScores,
+    #[error("Invalid liquidity")]
+    InvalidLiquidity,
+    #[error("Invalid deadline")]
+    InvalidDeadline,
+    #[error("Invalid participation threshold")]
+    InvalidThreshold,
+    #[error("Match number is required")]
+    AmbiguousMatch,
     #[error("Enabled match not found")]
     EnabledMatchNotFound,
     #[error("Unexpected error: {0}")]
@@ -1,17 +1,39 @@
 use adapter::modules::RepositoriesModuleExt;
+use kernel::model::bet::{Bet, BetType};
 use kernel::model::message::{NewMessage, UpdateMessage};
-use kernel::model::r#match::UpdateMatch;
-use kernel::model::{statistic, Id};
+use kernel::model::r#match::{MarketType, MatchStatus, UpdateMatch};
+use kernel::model::statistic::Statistic;
+use kernel::model::user::User;
+use kernel::model::{lmsr, statistic, Id};
 use kernel::repository::{
-    bet::BetRepository, candidate::CandidateRepository, r#match::MatchRepository,
-    user::UserRepository,
+    bet::BetRepository, candidate::CandidateRepository, channel::ChannelRepository,
+    position::PositionRepository, r#match::MatchRepository, user::UserRepository,
 };
 use kernel::traq::{error::TraqRepositoryError, message::MessageTraqRepository};
 
-use crate::model::message::r#match::UpsertMatchMessage;
+use crate::model::message::r#match::{SendMatchReminder, UpsertMatchMessage};
+
+use crate::usecase::{format_deadline, format_hundredths, status_label};
 
 use super::{MessageUseCase, MessageUseCaseError};
 
+fn bet_type_label(bet_type: BetType) -> &'static str {
+    match bet_type {
+        BetType::Win => "単勝",
+        BetType::Exacta => "連単",
+        BetType::Trifecta => "3連単",
+        BetType::Guess => "予想",
+    }
+}
+
+// 固定オッズの場合は match の開始時に決めた倍率を、それ以外は手数料を差し引いた倍率を返す
+fn statistic_rate(statistic: &Statistic, payout_ratio: f64) -> f64 {
+    match statistic.candidate.odds {
+        Some(odds) => odds as f64 / 100.0,
+        None => statistic.rate * payout_ratio,
+    }
+}
+
 impl<R: RepositoriesModuleExt> MessageUseCase<R> {
     pub async fn upsert_match_message(
         &self,
@@ -45,31 +67,239 @@ impl<R: RepositoriesModuleExt> MessageUseCase<R> {
             .await
             .map_err(|e| MessageUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
 
-        let statistics = statistic::new_statistics(bets, candidates);
+        let channel = self
+            .repositories
+            .channel_repository()
+            .find(Id::new(source.channel_id.value.clone()))
+            .await
+            .map_err(|e| MessageUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        // 着順を当てる bet は bet_type ごとのプールで倍率を計算する
+        let ordered_content = [BetType::Exacta, BetType::Trifecta].into_iter().fold(
+            "".to_string(),
+            |acc, bet_type| {
+                let bets = bets
+                    .iter()
+                    .filter(|b| b.bet_type == bet_type)
+                    .collect::<Vec<_>>();
+                let pool = bets.iter().map(|b| b.amount).sum::<i32>();
+                let mut orders: Vec<(String, Vec<&Bet>)> = Vec::new();
+                for bet in bets {
+                    let order = bet
+                        .order()
+                        .iter()
+                        .map(|id| {
+                            candidates
+                                .iter()
+                                .find(|c| c.id.value == id.value)
+                                .map(|c| c.name.clone())
+                                .unwrap_or("unknown".to_string())
+                        })
+                        .collect::<Vec<_>>()
+                        .join(">");
+                    match orders.iter_mut().find(|(o, _)| *o == order) {
+                        Some((_, bets)) => bets.push(bet),
+                        None => orders.push((order, vec![bet])),
+                    }
+                }
+                orders.into_iter().fold(acc, |acc, (order, bets)| {
+                    let amount = bets.iter().map(|b| b.amount).sum::<i32>();
+                    format!(
+                        "{}- {} {}: {:.2}倍({}pt)\n  - {}\n",
+                        acc,
+                        bet_type_label(bet_type),
+                        order,
+                        pool as f64 / amount as f64 * channel.payout_ratio(),
+                        amount,
+                        bets.iter().fold("".to_string(), |acc, bet| {
+                            format!(
+                                ":@{}:{}pt {}",
+                                users
+                                    .iter()
+                                    .find(|u| u.id.value == bet.user_id.value)
+                                    .map(|u| u.traq_display_id.clone())
+                                    .unwrap_or("unknown".to_string()),
+                                bet.amount,
+                                acc
+                            )
+                        })
+                    )
+                })
+            },
+        );
 
+        // 数値を予想する bet は予想の小さい順に並べる
+        let mut guesses = bets
+            .iter()
+            .filter(|b| b.bet_type == BetType::Guess)
+            .collect::<Vec<_>>();
+        guesses.sort_by_key(|b| b.guess);
+        let guess_content = guesses.iter().fold("".to_string(), |acc, bet| {
+            format!(
+                "{}- :@{}: {}: {}({}pt)\n",
+                acc,
+                users
+                    .iter()
+                    .find(|u| u.id.value == bet.user_id.value)
+                    .map(|u| u.traq_display_id.clone())
+                    .unwrap_or("unknown".to_string()),
+                bet_type_label(bet.bet_type),
+                format_hundredths(bet.guess.unwrap_or_default()),
+                bet.amount
+            )
+        });
+
+        // LMSR の賭けでは候補ごとの株の価格を、その候補が勝つ確率として表示する
+        let lmsr_content = match match_.market_type {
+            MarketType::Lmsr => {
+                let positions = self
+                    .repositories
+                    .position_repository()
+                    .select_by_match_id(Id::new(match_.id.value.clone()))
+                    .await
+                    .map_err(|e| MessageUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+                let quantities = candidates
+                    .iter()
+                    .map(|c| {
+                        positions
+                            .iter()
+                            .filter(|p| p.candidate_id.value == c.id.value)
+                            .map(|p| p.shares)
+                            .sum::<i32>()
+                    })
+                    .collect::<Vec<_>>();
+                let prices = lmsr::prices(
+                    &quantities,
+                    match_.liquidity.unwrap_or(lmsr::DEFAULT_LIQUIDITY),
+                );
+                candidates
+                    .iter()
+                    .zip(prices)
+                    .fold("".to_string(), |acc, (candidate, price)| {
+                        format!(
+                            "{}- {}: {:.1}%\n  - {}\n",
+                            acc,
+                            candidate.name,
+                            price * 100.0,
+                            positions
+                                .iter()
+                                .filter(|p| {
+                                    p.candidate_id.value == candidate.id.value && p.shares > 0
+                                })
+                                .fold("".to_string(), |acc, position| {
+                                    format!(
+                                        ":@{}:{}株 {}",
+                                        users
+                                            .iter()
+                                            .find(|u| u.id.value == position.user_id.value)
+                                            .map(|u| u.traq_display_id.clone())
+                                            .unwrap_or("unknown".to_string()),
+                                        position.shares,
+                                        acc
+                                    )
+                                })
+                        )
+                    })
+            }
+            _ => "".to_string(),
+        };
+
+        // 数値を予想する賭けや LMSR の賭けには候補に対する bet が無いので、候補ごとの集計は表示しない
+        let statistics = match match_.market_type {
+            MarketType::ClosestGuess | MarketType::Lmsr => Vec::new(),
+            _ => statistic::new_statistics(bets, candidates),
+        };
+
+        // 同じチャンネルの他の賭けと区別できるように、コマンドで指定する番号を添える
+        let title = match match_.status {
+            MatchStatus::Open => format!(
+                "### #{} 「{}」が作成されました",
+                match_.number, match_.title
+            ),
+            MatchStatus::Void => format!(
+                "### #{} 「{}」は無効になりました\n賭けられたポイントは全て返却されました",
+                match_.number, match_.title
+            ),
+            status => format!(
+                "### #{} 「{}」({})",
+                match_.number,
+                match_.title,
+                status_label(status)
+            ),
+        };
+        let title = match (match_.over_under_line, match_.result_value) {
+            (Some(line), Some(value)) => format!(
+                "{}\n基準値: {} / 結果: {}",
+                title,
+                format_hundredths(line),
+                format_hundredths(value)
+            ),
+            (Some(line), None) => format!("{}\n基準値: {}", title, format_hundredths(line)),
+            _ => title,
+        };
+        let title = match (match_.status, match_.close_at) {
+            (MatchStatus::Open, Some(close_at)) => {
+                format!("{}\n締め切り: {}", title, format_deadline(close_at))
+            }
+            _ => title,
+        };
+        // 締め切り時に満たしていなければ無効になる条件を表示する
+        let title = match (match_.status, match_.min_bettors, match_.min_candidates) {
+            (MatchStatus::Open, Some(bettors), Some(candidates)) => format!(
+                "{}\n成立条件: {}人以上が bet、{}つ以上の候補に bet",
+                title, bettors, candidates
+            ),
+            (MatchStatus::Open, Some(bettors), None) => {
+                format!("{}\n成立条件: {}人以上が bet", title, bettors)
+            }
+            (MatchStatus::Open, None, Some(candidates)) => {
+                format!("{}\n成立条件: {}つ以上の候補に bet", title, candidates)
+            }
+            _ => title,
+        };
         let content = format!(
-            "### 「{}」が作成されました\n{}",
-            match_.title,
+            "{}\n{}{}{}{}",
+            title,
             statistics.iter().fold("".to_string(), |acc, statistic| {
+                let rate = statistic_rate(statistic, channel.payout_ratio());
+                // ハンディキャップがある場合は候補名の横に表示する
+                let name = match statistic.candidate.handicap {
+                    Some(handicap) if handicap > 0 => format!(
+                        "{} (+{})",
+                        statistic.candidate.name,
+                        format_hundredths(handicap)
+                    ),
+                    Some(handicap) => format!(
+                        "{} ({})",
+                        statistic.candidate.name,
+                        format_hundredths(handicap)
+                    ),
+                    None => statistic.candidate.name.clone(),
+                };
                 format!(
-                    "- {}\n{}: {}倍({}pt)\n  - {}\n",
+                    "{}- {}: {:.2}倍({}pt)\n  - {}\n",
                     acc,
-                    statistic.candidate.name,
-                    statistic.rate,
+                    name,
+                    rate,
                     statistic.amount,
+                    // 1 人が複数の候補に bet できるので、候補ごとに賭けたポイントも表示する
                     statistic.bets.iter().fold("".to_string(), |acc, bet| {
                         format!(
-                            ":@{}:{}",
+                            ":@{}:{}pt {}",
                             users
                                 .iter()
                                 .find(|u| u.id.value == bet.user_id.value)
                                 .map(|u| u.traq_display_id.clone())
                                 .unwrap_or("unknown".to_string()),
+                            bet.amount,
                             acc
                         )
                     })
                 )
-            })
+            }),
+            ordered_content,
+            guess_content,
+            lmsr_content
         );
 
         match match_.message_id {
@@ -105,6 +335,166 @@ impl<R: RepositoriesModuleExt> MessageUseCase<R> {
             }
         }
 
+        Ok(())
+    }
+    // 締め切りが近い match の現在の倍率を送り、まだ bet していないユーザーに bet を促す
+    // リマインドを受け取らない設定にしたユーザーはメンションしない
+    pub async fn send_match_reminder(
+        &self,
+        source: SendMatchReminder,
+    ) -> Result<(), MessageUseCaseError> {
+        let Some(match_) = self
+            .repositories
+            .match_repository()
+            .find(source.match_id)
+            .await
+            .map_err(|e| MessageUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?
+        else {
+            return Ok(());
+        };
+        // 既に締め切られた match や、締め切り時刻を過ぎた match にはリマインドを送らない
+        let Some(close_at) = match_.close_at else {
+            return Ok(());
+        };
+        if match_.status != MatchStatus::Open || close_at <= chrono::Utc::now() {
+            return Ok(());
+        }
+
+        let candidates = self
+            .repositories
+            .candidate_repository()
+            .select_by_match_id(Id::new(match_.id.value.clone()))
+            .await
+            .map_err(|e| MessageUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        let bets = self
+            .repositories
+            .bet_repository()
+            .select_by_match_id(Id::new(match_.id.value.clone()))
+            .await
+            .map_err(|e| MessageUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        let users = self
+            .repositories
+            .user_repository()
+            .select_by_channel_id(Id::new(match_.channel_id.value.clone()))
+            .await
+            .map_err(|e| MessageUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        let channel = self
+            .repositories
+            .channel_repository()
+            .find(Id::new(match_.channel_id.value.clone()))
+            .await
+            .map_err(|e| MessageUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+        // LMSR の賭けでは株を持っているユーザーを bet 済みとして扱う
+        let mut bettor_ids = bets
+            .iter()
+            .map(|b| b.user_id.value.clone())
+            .collect::<Vec<_>>();
+        let odds_content = match match_.market_type {
+            MarketType::Lmsr => {
+                let positions = self
+                    .repositories
+                    .position_repository()
+                    .select_by_match_id(Id::new(match_.id.value.clone()))
+                    .await
+                    .map_err(|e| MessageUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+                let quantities = candidates
+                    .iter()
+                    .map(|c| {
+                        positions
+                            .iter()
+                            .filter(|p| p.candidate_id.value == c.id.value)
+                            .map(|p| p.shares)
+                            .sum::<i32>()
+                    })
+                    .collect::<Vec<_>>();
+                bettor_ids.extend(
+                    positions
+                        .iter()
+                        .filter(|p| p.shares > 0)
+                        .map(|p| p.user_id.value.clone()),
+                );
+                let prices = lmsr::prices(
+                    &quantities,
+                    match_.liquidity.unwrap_or(lmsr::DEFAULT_LIQUIDITY),
+                );
+                candidates
+                    .iter()
+                    .zip(prices)
+                    .fold("".to_string(), |acc, (candidate, price)| {
+                        format!("{}- {}: {:.1}%\n", acc, candidate.name, price * 100.0)
+                    })
+            }
+            // 数値を予想する賭けには倍率が無い
+            MarketType::ClosestGuess => "".to_string(),
+            _ => statistic::new_statistics(
+                bets.into_iter()
+                    .filter(|b| b.bet_type == BetType::Win)
+                    .collect(),
+                candidates,
+            )
+            .iter()
+            .fold("".to_string(), |acc, statistic| {
+                format!(
+                    "{}- {}: {:.2}倍({}pt)\n",
+                    acc,
+                    statistic.candidate.name,
+                    statistic_rate(statistic, channel.payout_ratio()),
+                    statistic.amount
+                )
+            }),
+        };
+
+        let (bettors, non_bettors): (Vec<_>, Vec<_>) = users
+            .iter()
+            .filter(|u| u.notify)
+            .partition(|u| bettor_ids.contains(&u.id.value));
+        let mentions = |users: Vec<&User>| {
+            users
+                .iter()
+                .map(|u| format!("@{}", u.traq_display_id))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let mut content = format!(
+            "### #{} 「{}」の締め切りまであと{}分です\n締め切り: {}\n{}",
+            match_.number,
+            match_.title,
+            (close_at - chrono::Utc::now()).num_minutes().max(1),
+            format_deadline(close_at),
+            odds_content
+        );
+        if !non_bettors.is_empty() {
+            content = format!(
+                "{}\nまだ bet していません: {}",
+                content,
+                mentions(non_bettors)
+            );
+        }
+        if !bettors.is_empty() {
+            content = format!(
+                "{}\n締め切りまで bet を変更できます: {}",
+                content,
+                mentions(bettors)
+            );
+        }
+        content = format!(
+            "{}\n`@BOT_bookmaker notify off`でリマインドを停止できます",
+            content
+        );
+
+        self.repositories
+            .message_traq_repository()
+            .create(NewMessage::new(
+                Id::new(match_.channel_id.value),
+                content,
+                true,
+            ))
+            .await
+            .map_err(|e| match e {
+                TraqRepositoryError::UnexpectedError(e) => MessageUseCaseError::UnexpectedError(e),
+            })?;
+
         Ok(())
     }
 }
@@ -1,8 +1,17 @@
 pub mod bet;
+pub mod challenge;
+pub mod channel;
+pub mod job;
 pub mod r#match;
 pub mod message;
+pub mod parlay;
 pub mod user;
 
+use kernel::model::{
+    r#match::{MatchEvent, MatchStatus, TransitionError, DEADLINE_UTC_OFFSET_SECS},
+    DateTimeUtc,
+};
+
 fn escape_arg(arg: &str) -> String {
     // space が入っている場合はダブルクォーテーションで囲んで、ダブルクォーテーションの前にバックスラッシュを挿入する
     if arg.contains(' ') {
@@ -11,3 +20,60 @@ fn escape_arg(arg: &str) -> String {
         arg.to_string()
     }
 }
+
+pub(crate) fn status_label(status: MatchStatus) -> &'static str {
+    match status {
+        MatchStatus::Open => "受付中",
+        MatchStatus::Closed => "締め切り済み",
+        MatchStatus::Settled => "終了済み",
+        MatchStatus::Cancelled => "キャンセル済み",
+        MatchStatus::Void => "無効",
+    }
+}
+
+fn event_label(event: MatchEvent) -> &'static str {
+    match event {
+        MatchEvent::Bet => "bet",
+        MatchEvent::Close => "締め切り",
+        MatchEvent::Settle => "終了",
+        MatchEvent::Cancel => "キャンセル",
+        MatchEvent::Void => "無効に",
+        MatchEvent::CashOut => "cash out",
+        MatchEvent::Expire => "無効に",
+    }
+}
+
+// channel で複数の賭けが進行中で、対象の賭けが指定されなかった場合のメッセージ
+pub(crate) const AMBIGUOUS_MATCH_MESSAGE: &str =
+    "複数の賭けが進行中です\n`#番号`で対象の賭けを指定してください\n`@BOT_bookmaker bet #2 候補A ポイント数`のように指定できます";
+
+pub(crate) fn transition_error_message(e: &TransitionError) -> String {
+    format!(
+        "この賭けは{}のため{}できません",
+        status_label(e.status),
+        event_label(e.event)
+    )
+}
+
+// 100 倍した整数で保存している値を表示用の数値に戻す
+pub(crate) fn format_hundredths(value: i32) -> String {
+    (value as f64 / 100.0).to_string()
+}
+
+// 締め切りの時刻を JST で表示する
+pub(crate) fn format_deadline(close_at: DateTimeUtc) -> String {
+    let offset = chrono::FixedOffset::east_opt(DEADLINE_UTC_OFFSET_SECS).unwrap();
+    close_at
+        .with_timezone(&offset)
+        .format("%m/%d %H:%M")
+        .to_string()
+}
+
+// 数値を 100 倍した整数に変換する。小数第 3 位以下は四捨五入する
+pub(crate) fn parse_hundredths(value: &str) -> Option<i32> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && v.abs() < 10_000_000.0)
+        .map(|v| (v * 100.0).round() as i32)
+}
@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use adapter::modules::RepositoriesModuleExt;
+use derive_new::new;
+use kernel::model::channel::Channel;
+use kernel::model::message::{Message, NewMessage};
+use kernel::model::parlay::{ParlaySlip, MAX_PARLAY_LEGS, MIN_PARLAY_LEGS};
+use kernel::model::r#match::{MarketType, TransitionError};
+use kernel::model::stamp::{NewStamp, StampType};
+use kernel::model::Id;
+use kernel::repository::error::RepositoryError;
+use kernel::repository::parlay::ParlayRepository;
+use kernel::repository::r#match::MatchRepository;
+use kernel::traq::message::MessageTraqRepository;
+use kernel::traq::stamp::StampTraqRepository;
+
+#[derive(new)]
+pub struct ParlayUseCase<R: RepositoriesModuleExt> {
+    repositories: Arc<R>,
+}
+
+impl<R: RepositoriesModuleExt> ParlayUseCase<R> {
+    pub async fn create_parlay(
+        &self,
+        source: CreateParlay,
+    ) -> Result<ParlaySlip, ParlayUseCaseError> {
+        let message_id = Id::new(source.message_id.clone());
+        let channel_id = Id::new(source.channel_id.clone());
+        let legs = &source.legs;
+        let has_invalid_leg = !(MIN_PARLAY_LEGS..=MAX_PARLAY_LEGS).contains(&legs.len())
+            || legs.iter().enumerate().any(|(i, leg)| {
+                leg.candidate_name.is_empty()
+                    || legs[..i].iter().any(|l| l.channel_id == leg.channel_id)
+            });
+        if has_invalid_leg || source.amount <= 0 {
+            self.send(
+                channel_id,
+                format!(
+                    "引数が不正です\n賭けるポイントは正の整数を指定し、異なるチャンネルの賭けから候補を{}〜{}つ選んでください\n`@BOT_bookmaker parlay ポイント数 #チャンネルA 候補A #チャンネルB 候補B`の形式で指定できます\nチャンネルで複数の賭けが進行中の場合は`#チャンネルA #2 候補A`のように番号を指定してください",
+                    MIN_PARLAY_LEGS, MAX_PARLAY_LEGS
+                ),
+            )
+            .await?;
+            return Err(ParlayUseCaseError::InvalidArgument);
+        }
+
+        // 払い戻しの倍率は 1 着を当てる bet から決めるので、パリミュチュエルか固定オッズの賭けに限る
+        let mut titles = Vec::new();
+        for leg in legs.iter() {
+            let latest = match self
+                .repositories
+                .match_repository()
+                .find_latest(Id::new(leg.channel_id.clone()), leg.match_number)
+                .await
+            {
+                Ok(latest) => latest,
+                Err(RepositoryError::AmbiguousMatch) => {
+                    self.send(channel_id, AMBIGUOUS_MATCH_MESSAGE.to_string())
+                        .await?;
+                    return Err(ParlayUseCaseError::AmbiguousMatch);
+                }
+                Err(e) => return Err(ParlayUseCaseError::UnexpectedError(anyhow::anyhow!(e))),
+            };
+            match latest {
+                Some(m)
+                    if matches!(
+                        m.market_type,
+                        MarketType::Parimutuel | MarketType::FixedOdds
+                    ) =>
+                {
+                    titles.push(m.title)
+                }
+                Some(m) => {
+                    self.send(
+                        channel_id,
+                        format!("「{}」はパーレーに含められない種類の賭けです", m.title),
+                    )
+                    .await?;
+                    return Err(ParlayUseCaseError::InvalidMarket);
+                }
+                None => {
+                    self.send(
+                        channel_id,
+                        "選んだチャンネルに有効な賭けが見つかりませんでした".to_string(),
+                    )
+                    .await?;
+                    return Err(ParlayUseCaseError::EnabledMatchNotFound);
+                }
+            }
+        }
+
+        let amount = source.amount;
+        let candidate_names = legs
+            .iter()
+            .map(|l| l.candidate_name.clone())
+            .collect::<Vec<_>>();
+        let insert_result = self
+            .repositories
+            .parlay_repository()
+            .insert(source.into())
+            .await;
+        let slip = self
+            .reply(Id::new(channel_id.value.clone()), message_id, insert_result)
+            .await?;
+
+        let legs_content = titles
+            .iter()
+            .zip(candidate_names.iter())
+            .fold("".to_string(), |acc, (title, name)| {
+                format!("{}\n- 「{}」: {}", acc, title, name)
+            });
+        self.send(
+            channel_id,
+            format!(
+                "パーレーに{}pt賭けました{}\n全ての賭けの結果が出ると、的中した倍率の積で払い戻します",
+                amount, legs_content
+            ),
+        )
+        .await?;
+
+        Ok(slip)
+    }
+    async fn send(
+        &self,
+        channel_id: Id<Channel>,
+        content: String,
+    ) -> Result<(), ParlayUseCaseError> {
+        self.repositories
+            .message_traq_repository()
+            .create(NewMessage::new(channel_id, content, true))
+            .await
+            .map_err(|e| ParlayUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        Ok(())
+    }
+    // 成功した場合はスタンプを付け、失敗した場合はエラーの内容をチャンネルに送信する
+    async fn reply<T>(
+        &self,
+        channel_id: Id<Channel>,
+        message_id: Id<Message>,
+        result: Result<T, RepositoryError>,
+    ) -> Result<T, ParlayUseCaseError> {
+        match result {
+            Ok(slip) => {
+                self.repositories
+                    .stamp_repository()
+                    .create(NewStamp::new(message_id, StampType::WhiteCheckMark))
+                    .await
+                    .map_err(|e| ParlayUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+
+                Ok(slip)
+            }
+            Err(e) => {
+                let error_with_message = match e {
+                    RepositoryError::RecordNotFound(s) => {
+                        if s.contains("Match") {
+                            (
+                                "選んだチャンネルに有効な賭けが見つかりませんでした".to_string(),
+                                ParlayUseCaseError::EnabledMatchNotFound,
+                            )
+                        } else if s.contains("Candidate") {
+                            (
+                                "指定した候補が見つかりませんでした".to_string(),
+                                ParlayUseCaseError::CandidateNotFound,
+                            )
+                        } else {
+                            (
+                                "ユーザー登録していません\n`@BOT_bookmaker reg`で先に登録してください".to_string(),
+                                ParlayUseCaseError::UserNotFound,
+                            )
+                        }
+                    }
+                    RepositoryError::DuplicatedRecord(_) => (
+                        "同じ賭けを複数選ぶことはできません".to_string(),
+                        ParlayUseCaseError::InvalidArgument,
+                    ),
+                    RepositoryError::AmbiguousMatch => (
+                        AMBIGUOUS_MATCH_MESSAGE.to_string(),
+                        ParlayUseCaseError::AmbiguousMatch,
+                    ),
+                    RepositoryError::InsufficientBalance => (
+                        "ポイントが不足しています".to_string(),
+                        ParlayUseCaseError::InsufficientBalance,
+                    ),
+                    RepositoryError::InvalidTransition(e) => (
+                        transition_error_message(&e),
+                        ParlayUseCaseError::InvalidTransition(e),
+                    ),
+                    _ => (
+                        "予期せぬエラーが発生しました".to_string(),
+                        ParlayUseCaseError::UnexpectedError(anyhow::anyhow!(e)),
+                    ),
+                };
+                self.send(channel_id, error_with_message.0).await?;
+                Err(error_with_message.1)
+            }
+        }
+    }
+}
+
+use thiserror::Error;
+
+use crate::model::parlay::CreateParlay;
+use crate::usecase::{transition_error_message, AMBIGUOUS_MATCH_MESSAGE};
+
+#[derive(Error, Debug)]
+pub enum ParlayUseCaseError {
+    #[error("Invalid argument")]
+    InvalidArgument,
+    #[error("Parlay does not fit the market")]
+    InvalidMarket,
+    #[error("Candidate not found")]
+    CandidateNotFound,
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Enabled match not found")]
+    EnabledMatchNotFound,
+    #[error("Match number is required")]
+    AmbiguousMatch,
+    #[error("Insufficient balance")]
+    InsufficientBalance,
+    #[error("Invalid transition: {0}")]
+    InvalidTransition(TransitionError),
+    #[error("Unexpected error: {0}")]
+    UnexpectedError(#[from] anyhow::Error),
+}
@@ -3,12 +3,17 @@ use std::sync::Arc;
 use adapter::modules::RepositoriesModuleExt;
 use derive_new::new;
 use kernel::{
-    model::{message::NewMessage, user::User, Id},
-    repository::user::UserRepository,
-    traq::message::MessageTraqRepository,
+    model::{
+        message::NewMessage,
+        stamp::{NewStamp, StampType},
+        user::{UpdateNotify, User},
+        Id,
+    },
+    repository::{error::RepositoryError, user::UserRepository},
+    traq::{message::MessageTraqRepository, stamp::StampTraqRepository},
 };
 
-use crate::model::user::CreateUser;
+use crate::model::user::{ConfigureNotify, CreateUser};
 
 #[derive(new)]
 pub struct UserUseCase<R: RepositoriesModuleExt> {
@@ -50,12 +55,75 @@ impl<R: RepositoriesModuleExt> UserUseCase<R> {
             })?;
         Ok(users)
     }
+    // 締め切りのリマインドでメンションされるかを切り替える
+    pub async fn configure_notify(
+        &self,
+        source: ConfigureNotify,
+    ) -> Result<User, UserUseCaseError> {
+        let notify = match source.value.as_deref() {
+            Some("on") => true,
+            Some("off") => false,
+            _ => {
+                self.send(
+                    source.channel_id,
+                    "引数が不正です\n`@BOT_bookmaker notify off`の形式で指定してください"
+                        .to_string(),
+                )
+                .await?;
+                return Err(UserUseCaseError::InvalidArgument);
+            }
+        };
+        let user = match self
+            .repositories
+            .user_repository()
+            .update_notify(UpdateNotify::new(
+                source.traq_id,
+                Id::new(source.channel_id.clone()),
+                notify,
+            ))
+            .await
+        {
+            Ok(user) => user,
+            Err(RepositoryError::RecordNotFound(_)) => {
+                self.send(
+                    source.channel_id,
+                    "ユーザー登録していません\n`@BOT_bookmaker reg`で先に登録してください"
+                        .to_string(),
+                )
+                .await?;
+                return Err(UserUseCaseError::UserNotFound);
+            }
+            Err(e) => return Err(UserUseCaseError::UnexpectedError(anyhow::anyhow!(e))),
+        };
+
+        self.repositories
+            .stamp_repository()
+            .create(NewStamp::new(
+                Id::new(source.message_id),
+                StampType::WhiteCheckMark,
+            ))
+            .await
+            .map_err(|e| UserUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        Ok(user)
+    }
+    async fn send(&self, channel_id: String, content: String) -> Result<(), UserUseCaseError> {
+        self.repositories
+            .message_traq_repository()
+            .create(NewMessage::new(Id::new(channel_id), content, true))
+            .await
+            .map_err(|e| UserUseCaseError::UnexpectedError(anyhow::anyhow!(e)))?;
+        Ok(())
+    }
 }
 
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum UserUseCaseError {
+    #[error("Invalid argument")]
+    InvalidArgument,
+    #[error("User not found")]
+    UserNotFound,
     #[error("Unexpected error: {0}")]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -1,4 +1,5 @@
 pub mod model;
 pub mod module;
 pub mod routes;
+pub mod scheduler;
 pub mod startup;
@@ -5,7 +5,8 @@ use adapter::{
     persistence::mariadb::Db,
 };
 use app::usecase::{
-    bet::BetUseCase, message::MessageUseCase, r#match::MatchUseCase, user::UserUseCase,
+    bet::BetUseCase, challenge::ChallengeUseCase, channel::ChannelUseCase, job::JobUseCase,
+    message::MessageUseCase, parlay::ParlayUseCase, r#match::MatchUseCase, user::UserUseCase,
 };
 
 pub struct Modules {
@@ -13,6 +14,10 @@ pub struct Modules {
     user_use_case: UserUseCase<RepositoriesModule>,
     match_use_case: MatchUseCase<RepositoriesModule>,
     bet_use_case: BetUseCase<RepositoriesModule>,
+    channel_use_case: ChannelUseCase<RepositoriesModule>,
+    challenge_use_case: ChallengeUseCase<RepositoriesModule>,
+    parlay_use_case: ParlayUseCase<RepositoriesModule>,
+    job_use_case: JobUseCase<RepositoriesModule>,
     message_use_case: MessageUseCase<RepositoriesModule>,
 }
 
@@ -24,6 +29,10 @@ pub trait ModulesExt {
     fn user_use_case(&self) -> &UserUseCase<Self::RepositoriesModule>;
     fn match_use_case(&self) -> &MatchUseCase<Self::RepositoriesModule>;
     fn bet_use_case(&self) -> &BetUseCase<Self::RepositoriesModule>;
+    fn channel_use_case(&self) -> &ChannelUseCase<Self::RepositoriesModule>;
+    fn challenge_use_case(&self) -> &ChallengeUseCase<Self::RepositoriesModule>;
+    fn parlay_use_case(&self) -> &ParlayUseCase<Self::RepositoriesModule>;
+    fn job_use_case(&self) -> &JobUseCase<Self::RepositoriesModule>;
     fn message_use_case(&self) -> &MessageUseCase<Self::RepositoriesModule>;
 }
 
@@ -43,6 +52,18 @@ impl ModulesExt for Modules {
     fn bet_use_case(&self) -> &BetUseCase<Self::RepositoriesModule> {
         &self.bet_use_case
     }
+    fn channel_use_case(&self) -> &ChannelUseCase<Self::RepositoriesModule> {
+        &self.channel_use_case
+    }
+    fn challenge_use_case(&self) -> &ChallengeUseCase<Self::RepositoriesModule> {
+        &self.challenge_use_case
+    }
+    fn parlay_use_case(&self) -> &ParlayUseCase<Self::RepositoriesModule> {
+        &self.parlay_use_case
+    }
+    fn job_use_case(&self) -> &JobUseCase<Self::RepositoriesModule> {
+        &self.job_use_case
+    }
     fn message_use_case(&self) -> &MessageUseCase<Self::RepositoriesModule> {
         &self.message_use_case
     }
@@ -62,6 +83,14 @@ impl Modules {
 
         let bet_use_case = BetUseCase::new(repositories_module.clone());
 
+        let channel_use_case = ChannelUseCase::new(repositories_module.clone());
+
+        let challenge_use_case = ChallengeUseCase::new(repositories_module.clone());
+
+        let parlay_use_case = ParlayUseCase::new(repositories_module.clone());
+
+        let job_use_case = JobUseCase::new(repositories_module.clone());
+
         let message_use_case = MessageUseCase::new(repositories_module.clone());
 
         Self {
@@ -69,6 +98,10 @@ impl Modules {
             user_use_case,
             match_use_case,
             bet_use_case,
+            channel_use_case,
+            challenge_use_case,
+            parlay_use_case,
+            job_use_case,
             message_use_case,
         }
     }
@@ -1,28 +1,72 @@
 use derive_new::new;
-use kernel::model::Id;
+use kernel::model::{bet::BetType, Id};
 use std::sync::Arc;
 
-use app::model::{bet::CreateBet, message::r#match::UpsertMatchMessage};
+use app::model::{
+    bet::{CreateBet, CreateGuessBet, CreateOrderedBet, CreateStake, TradeShares},
+    message::r#match::UpsertMatchMessage,
+};
 
 use crate::module::{Modules, ModulesExt};
 
 #[derive(new)]
 pub struct BetArg {
     pub traq_id: String,
-    pub candidate_name: String,
-    pub amount: i32,
+    // (候補名, ポイント数) の組
+    pub stakes: Vec<(String, i32)>,
     pub channel_id: String,
+    pub match_number: Option<i32>,
     pub message_id: String,
 }
 
 pub async fn handle(modules: Arc<Modules>, arg: BetArg) -> anyhow::Result<()> {
-    let bet = modules
+    let bets = modules
         .bet_use_case()
         .create_bet(CreateBet::new(
             arg.channel_id.clone(),
+            arg.match_number,
             arg.message_id,
             arg.traq_id,
-            arg.candidate_name,
+            arg.stakes
+                .into_iter()
+                .map(|(candidate_name, amount)| CreateStake::new(candidate_name, amount))
+                .collect(),
+        ))
+        .await?;
+
+    modules
+        .message_use_case()
+        .upsert_match_message(UpsertMatchMessage::new(
+            Id::new(arg.channel_id),
+            Id::new(bets[0].match_id.value.clone()),
+        ))
+        .await?;
+
+    Ok(())
+}
+
+#[derive(new)]
+pub struct OrderedBetArg {
+    pub traq_id: String,
+    pub bet_type: BetType,
+    // 1 着から順に並べた候補名
+    pub candidate_names: Vec<String>,
+    pub amount: i32,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+}
+
+pub async fn handle_ordered(modules: Arc<Modules>, arg: OrderedBetArg) -> anyhow::Result<()> {
+    let bet = modules
+        .bet_use_case()
+        .create_ordered_bet(CreateOrderedBet::new(
+            arg.channel_id.clone(),
+            arg.match_number,
+            arg.message_id,
+            arg.traq_id,
+            arg.bet_type,
+            arg.candidate_names,
             arg.amount,
         ))
         .await?;
@@ -37,3 +81,72 @@ pub async fn handle(modules: Arc<Modules>, arg: BetArg) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[derive(new)]
+pub struct GuessBetArg {
+    pub traq_id: String,
+    pub guess: String,
+    pub amount: i32,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+}
+
+pub async fn handle_guess(modules: Arc<Modules>, arg: GuessBetArg) -> anyhow::Result<()> {
+    let bet = modules
+        .bet_use_case()
+        .create_guess_bet(CreateGuessBet::new(
+            arg.channel_id.clone(),
+            arg.match_number,
+            arg.message_id,
+            arg.traq_id,
+            arg.guess,
+            arg.amount,
+        ))
+        .await?;
+
+    modules
+        .message_use_case()
+        .upsert_match_message(UpsertMatchMessage::new(
+            Id::new(arg.channel_id),
+            bet.match_id,
+        ))
+        .await?;
+
+    Ok(())
+}
+
+#[derive(new)]
+pub struct TradeArg {
+    pub traq_id: String,
+    pub candidate_name: String,
+    // 購入なら正、売却なら負の株数
+    pub shares: i32,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+}
+
+pub async fn handle_trade(modules: Arc<Modules>, arg: TradeArg) -> anyhow::Result<()> {
+    let trade = modules
+        .bet_use_case()
+        .trade_shares(TradeShares::new(
+            arg.channel_id.clone(),
+            arg.match_number,
+            arg.message_id,
+            arg.traq_id,
+            arg.candidate_name,
+            arg.shares,
+        ))
+        .await?;
+
+    modules
+        .message_use_case()
+        .upsert_match_message(UpsertMatchMessage::new(
+            Id::new(arg.channel_id),
+            trade.position.match_id,
+        ))
+        .await?;
+
+    Ok(())
+}
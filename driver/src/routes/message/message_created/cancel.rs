@@ -1,18 +1,20 @@
 use derive_new::new;
-use kernel::model::Id;
 use std::sync::Arc;
 
+use app::model::r#match::CancelMatch;
+
 use crate::module::{Modules, ModulesExt};
 
 #[derive(new)]
 pub struct CancelArg {
     pub channel_id: String,
+    pub match_number: Option<i32>,
 }
 
 pub async fn handle(modules: Arc<Modules>, arg: CancelArg) -> anyhow::Result<()> {
     modules
         .match_use_case()
-        .delete_match(Id::new(arg.channel_id))
+        .cancel_match(CancelMatch::new(arg.channel_id, arg.match_number))
         .await?;
 
     Ok(())
@@ -0,0 +1,46 @@
+use derive_new::new;
+use kernel::model::Id;
+use std::sync::Arc;
+
+use app::model::{bet::CashOutBet, message::r#match::UpsertMatchMessage};
+
+use crate::module::{Modules, ModulesExt};
+
+#[derive(new)]
+pub struct CashOutArg {
+    pub traq_id: String,
+    pub candidate_name: Option<String>,
+    pub confirmed: bool,
+    pub accepted_value: Option<i32>,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+}
+
+pub async fn handle(modules: Arc<Modules>, arg: CashOutArg) -> anyhow::Result<()> {
+    let cash_out = modules
+        .bet_use_case()
+        .cash_out_bet(CashOutBet::new(
+            arg.channel_id.clone(),
+            arg.match_number,
+            arg.message_id,
+            arg.traq_id,
+            arg.candidate_name,
+            arg.confirmed,
+            arg.accepted_value,
+        ))
+        .await?;
+
+    // 提示しただけの場合はプールが変わらないので、賭けのメッセージを更新しない
+    if cash_out.confirmed {
+        modules
+            .message_use_case()
+            .upsert_match_message(UpsertMatchMessage::new(
+                Id::new(arg.channel_id),
+                cash_out.bet.match_id,
+            ))
+            .await?;
+    }
+
+    Ok(())
+}
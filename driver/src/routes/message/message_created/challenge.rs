@@ -0,0 +1,95 @@
+use derive_new::new;
+use std::sync::Arc;
+
+use app::model::challenge::{AcceptChallenge, CreateChallenge, DeclineChallenge, ResolveChallenge};
+
+use crate::module::{Modules, ModulesExt};
+
+#[derive(new)]
+pub struct ChallengeArg {
+    pub traq_id: String,
+    // メンションされたユーザーの traQ のユーザー ID。1 人目が相手で、2 人目がいれば審判になる
+    pub mentioned_traq_ids: Vec<String>,
+    pub amount: i32,
+    pub title: String,
+    pub channel_id: String,
+    pub message_id: String,
+}
+
+pub async fn handle(modules: Arc<Modules>, arg: ChallengeArg) -> anyhow::Result<()> {
+    modules
+        .challenge_use_case()
+        .create_challenge(CreateChallenge::new(
+            arg.channel_id,
+            arg.message_id,
+            arg.traq_id,
+            arg.mentioned_traq_ids,
+            arg.amount,
+            arg.title,
+        ))
+        .await?;
+
+    Ok(())
+}
+
+#[derive(new)]
+pub struct AcceptArg {
+    pub traq_id: String,
+    pub channel_id: String,
+    pub message_id: String,
+}
+
+pub async fn handle_accept(modules: Arc<Modules>, arg: AcceptArg) -> anyhow::Result<()> {
+    modules
+        .challenge_use_case()
+        .accept_challenge(AcceptChallenge::new(
+            arg.channel_id,
+            arg.message_id,
+            arg.traq_id,
+        ))
+        .await?;
+
+    Ok(())
+}
+
+#[derive(new)]
+pub struct DeclineArg {
+    pub traq_id: String,
+    pub channel_id: String,
+    pub message_id: String,
+}
+
+pub async fn handle_decline(modules: Arc<Modules>, arg: DeclineArg) -> anyhow::Result<()> {
+    modules
+        .challenge_use_case()
+        .decline_challenge(DeclineChallenge::new(
+            arg.channel_id,
+            arg.message_id,
+            arg.traq_id,
+        ))
+        .await?;
+
+    Ok(())
+}
+
+#[derive(new)]
+pub struct ResolveArg {
+    pub traq_id: String,
+    pub winner_traq_id: Option<String>,
+    pub channel_id: String,
+    pub message_id: String,
+}
+
+pub async fn handle_resolve(modules: Arc<Modules>, arg: ResolveArg) -> anyhow::Result<()> {
+    modules
+        .challenge_use_case()
+        .resolve_challenge(ResolveChallenge::new(
+            arg.channel_id,
+            arg.message_id,
+            arg.traq_id,
+            arg.winner_traq_id,
+        ))
+        .await?;
+
+    Ok(())
+}
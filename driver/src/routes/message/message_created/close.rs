@@ -1,21 +1,35 @@
 use derive_new::new;
+use kernel::model::{r#match::MatchStatus, Id};
 use std::sync::Arc;
 
-use app::model::r#match::CloseMatch;
+use app::model::{message::r#match::UpsertMatchMessage, r#match::CloseMatch};
 
 use crate::module::{Modules, ModulesExt};
 
 #[derive(new)]
 pub struct CloseArg {
     pub channel_id: String,
+    pub match_number: Option<i32>,
     pub message_id: String,
 }
 
 pub async fn handle(modules: Arc<Modules>, arg: CloseArg) -> anyhow::Result<()> {
-    modules
+    let match_ = modules
         .match_use_case()
-        .close_match(CloseMatch::new(arg.channel_id, arg.message_id))
+        .close_match(CloseMatch::new(
+            arg.channel_id.clone(),
+            arg.match_number,
+            Some(arg.message_id),
+        ))
         .await?;
 
+    // 参加の条件を満たさずに無効になった場合は、賭けのメッセージにも反映する
+    if match_.status == MatchStatus::Void {
+        modules
+            .message_use_case()
+            .upsert_match_message(UpsertMatchMessage::new(Id::new(arg.channel_id), match_.id))
+            .await?;
+    }
+
     Ok(())
 }
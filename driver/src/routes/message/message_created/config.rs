@@ -0,0 +1,22 @@
+use derive_new::new;
+use std::sync::Arc;
+
+use app::model::channel::ConfigureChannel;
+
+use crate::module::{Modules, ModulesExt};
+
+#[derive(new)]
+pub struct ConfigArg {
+    pub channel_id: String,
+    pub key: Option<String>,
+    pub value: Option<String>,
+}
+
+pub async fn handle(modules: Arc<Modules>, arg: ConfigArg) -> anyhow::Result<()> {
+    modules
+        .channel_use_case()
+        .configure_channel(ConfigureChannel::new(arg.channel_id, arg.key, arg.value))
+        .await?;
+
+    Ok(())
+}
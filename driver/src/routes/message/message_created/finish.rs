@@ -1,5 +1,6 @@
-use app::model::r#match::FinishMatch;
+use app::model::{message::r#match::UpsertMatchMessage, r#match::FinishMatch};
 use derive_new::new;
+use kernel::model::{r#match::MatchStatus, Id};
 use std::sync::Arc;
 
 use crate::module::{Modules, ModulesExt};
@@ -7,14 +8,27 @@ use crate::module::{Modules, ModulesExt};
 #[derive(new)]
 pub struct FinishArg {
     pub channel_id: String,
-    pub winner_candidate_name: String,
+    pub match_number: Option<i32>,
+    pub winner_candidate_names: Vec<String>,
 }
 
 pub async fn handle(modules: Arc<Modules>, arg: FinishArg) -> anyhow::Result<()> {
-    modules
+    let match_ = modules
         .match_use_case()
-        .finish_match(FinishMatch::new(arg.channel_id, arg.winner_candidate_name))
+        .finish_match(FinishMatch::new(
+            arg.channel_id.clone(),
+            arg.match_number,
+            arg.winner_candidate_names,
+        ))
         .await?;
 
+    // 参加の条件を満たさずに無効になった場合は、賭けのメッセージにも反映する
+    if match_.status == MatchStatus::Void {
+        modules
+            .message_use_case()
+            .upsert_match_message(UpsertMatchMessage::new(Id::new(arg.channel_id), match_.id))
+            .await?;
+    }
+
     Ok(())
 }
@@ -20,12 +20,61 @@ pub async fn handle(modules: Arc<Modules>, channel_id: String) -> anyhow::Result
                     "bet".to_string(),
                     "あなたのポイントを賭けます。参加賞で1000ptもらえます".to_string(),
                 ),
+                CommandSummary::new(
+                    "buy".to_string(),
+                    "株を売買する賭けで候補の株を購入します".to_string(),
+                ),
+                CommandSummary::new(
+                    "sell".to_string(),
+                    "株を売買する賭けで保有している株を売却します".to_string(),
+                ),
+                CommandSummary::new(
+                    "unbet".to_string(),
+                    "締め切り前の bet を取り消します".to_string(),
+                ),
+                CommandSummary::new(
+                    "cashout".to_string(),
+                    "締め切り前の bet を取り下げてポイントを受け取ります".to_string(),
+                ),
+                CommandSummary::new(
+                    "parlay".to_string(),
+                    "複数のチャンネルの賭けを組み合わせて bet します".to_string(),
+                ),
+                CommandSummary::new(
+                    "rebet".to_string(),
+                    "締め切り前の bet を別の候補に変更します".to_string(),
+                ),
                 CommandSummary::new("cancel".to_string(), "賭けをキャンセルします".to_string()),
+                CommandSummary::new(
+                    "void".to_string(),
+                    "締め切り済みの賭けを無効にし、ポイントを返却します".to_string(),
+                ),
                 CommandSummary::new(
                     "finish".to_string(),
                     "賭けを終了しポイントを配分します".to_string(),
                 ),
+                CommandSummary::new(
+                    "config".to_string(),
+                    "チャンネルの設定を変更します".to_string(),
+                ),
                 CommandSummary::new("info".to_string(), "ポイントを表示します".to_string()),
+                CommandSummary::new(
+                    "notify".to_string(),
+                    "締め切りのリマインドでメンションされるかを切り替えます".to_string(),
+                ),
+                CommandSummary::new(
+                    "challenge".to_string(),
+                    "他のユーザーに個人的な勝負を挑みます".to_string(),
+                ),
+                CommandSummary::new("accept".to_string(), "挑まれた勝負を受けます".to_string()),
+                CommandSummary::new(
+                    "decline".to_string(),
+                    "挑まれた勝負を断るか、挑んだ勝負を取り下げます".to_string(),
+                ),
+                CommandSummary::new(
+                    "resolve".to_string(),
+                    "成立した勝負の勝者を指定します".to_string(),
+                ),
             ],
         ))
         .await?;
@@ -1,5 +1,6 @@
 use app::model::message::help::{Command, SendHelpMessage};
 use derive_new::new;
+use kernel::model::bet::BetType;
 use std::sync::Arc;
 
 use crate::{
@@ -11,12 +12,20 @@ use self::close::CloseArg;
 
 mod bet;
 mod cancel;
+mod cashout;
+mod challenge;
 mod close;
+mod config;
 mod finish;
 mod help;
 mod info;
+mod notify;
+mod parlay;
+mod rebet;
 mod reg;
 mod start;
+mod unbet;
+mod void;
 
 #[derive(new)]
 pub struct ParseState {
@@ -69,6 +78,22 @@ fn parse_args(text: &str) -> Vec<String> {
         .collect()
 }
 
+// `#3` のような引数を賭けの番号として読む
+fn parse_match_number(arg: &str) -> Option<i32> {
+    arg.strip_prefix('#')?
+        .parse::<i32>()
+        .ok()
+        .filter(|number| *number > 0)
+}
+
+// 先頭の引数が賭けの番号であれば取り出し、残りの引数と組にして返す
+fn split_match_number(args: &[String]) -> (Option<i32>, Vec<String>) {
+    match args.first().and_then(|arg| parse_match_number(arg)) {
+        Some(number) => (Some(number), args[1..].to_vec()),
+        None => (None, args.to_vec()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +119,23 @@ mod tests {
         assert_eq!(parse_args("     "), Vec::<String>::new());
         assert_eq!(parse_args(" \"  \" "), vec!["  "]);
     }
+
+    #[test]
+    fn test_split_match_number() {
+        let args = |text: &str| parse_args(text);
+        assert_eq!(
+            split_match_number(&args("#3 Gen.G 500")),
+            (Some(3), args("Gen.G 500"))
+        );
+        assert_eq!(
+            split_match_number(&args("Gen.G 500")),
+            (None, args("Gen.G 500"))
+        );
+        // 番号として読めない場合は候補名として扱う
+        assert_eq!(split_match_number(&args("#PRX")), (None, args("#PRX")));
+        assert_eq!(split_match_number(&args("#0")), (None, args("#0")));
+        assert_eq!(split_match_number(&args("")), (None, Vec::new()));
+    }
 }
 
 fn is_help_command(args: &[String]) -> bool {
@@ -120,6 +162,14 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
         .unwrap_or_default();
     let args = args.iter().skip(1).cloned().collect::<Vec<_>>();
     let channel_id = event.message.channel_id;
+    // BOT 以外にメンションされたユーザーを、メッセージ中の順番で取得する
+    let mentioned_traq_ids = event
+        .message
+        .embedded
+        .iter()
+        .filter(|e| e.r#type == "user" && e.id != modules.bot_user_id())
+        .map(|e| e.id.clone())
+        .collect::<Vec<_>>();
     match command_name {
         "help" | "--help" | "-h" => help::handle(modules, channel_id).await?,
         "reg" => {
@@ -138,7 +188,7 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
                         Command::new(
                             "start".to_string(),
                             "賭けの開始".to_string(),
-                            "賭けを開始します\n進行中の賭けはチャンネルごとに1つのみです"
+                            "賭けを開始します\n1つのチャンネルで複数の賭けを同時に進行でき、賭けには作成した順に`#1`、`#2`のような番号が付きます\n複数の賭けが進行中の場合は、`bet`や`finish`などのコマンドで`@BOT_bookmaker finish #2 勝者名`のように最初の引数に番号を指定してください\n`候補名:1.8`のように全ての候補に倍率を指定すると、固定オッズで賭けを開始します\n`@BOT_bookmaker start 賭け名 --over-under 24.5`のように基準値を指定すると、Over と Under を候補とする賭けを開始します\n`@BOT_bookmaker start 賭け名 --closest`のように指定すると、数値を予想して結果に最も近い予想をした人がポイントを受け取る賭けを開始します\n`@BOT_bookmaker start 賭け名 候補A 候補B --lmsr 100`のように指定すると、候補の株を`buy`と`sell`で売買する賭けを開始します。数値は流動性で、大きいほど売買による価格の変化が緩やかになります (省略すると100)\n`@BOT_bookmaker start 賭け名 Gen.G -3.5 PRX`のように候補名の後に数値を指定すると、その候補の得点に加えるハンディキャップになります\n`--until 19:55`のように JST の時刻を、または`--in 30m`のように今からの時間を指定すると、その時刻に bet を自動で締め切ります。締め切りの10分前には、現在の倍率とまだ bet していない人へのメンションを送ります\n`--min-bettors 3`のように bet する人数の下限を、`--min-candidates 2`のように bet される候補の数の下限を指定すると、締め切り時や終了時に条件を満たしていない賭けを無効にし、賭けられたポイントを返却します"
                                 .to_string(),
                             "@BOT_bookmaker start \"VCT PACIFIC\" Gen.G PRX".to_string(),
                         ),
@@ -167,7 +217,7 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
                         Command::new(
                             "close".to_string(),
                             "bet の締め切り".to_string(),
-                            "bet を締め切ります。\nこの時点でレートは確定し、bet は受け付けられなくなります"
+                            "bet を締め切ります。\nこの時点でレートは確定し、bet は受け付けられなくなります\n`start`で締め切りを指定した賭けは、その時刻に自動で締め切られます"
                                 .to_string(),
                             "@BOT_bookmaker close".to_string(),
                         ),
@@ -175,7 +225,12 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
                     .await?;
                 return Ok(());
             }
-            close::handle(modules, CloseArg::new(channel_id, event.message.id)).await?
+            let (match_number, _) = split_match_number(&args);
+            close::handle(
+                modules,
+                CloseArg::new(channel_id, match_number, event.message.id),
+            )
+            .await?
         }
         "bet" => {
             if is_help_command(&args) {
@@ -186,7 +241,7 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
                         Command::new(
                             "bet".to_string(),
                             "賭け".to_string(),
-                            "賭けを行います\n賭けの対象となる候補を指定し、賭けるポイントは正の整数を指定してください\n参加賞として1000ptもらえます\n`@BOT_bookmaker bet 候補A ポイント数`の形式で指定できます"
+                            "賭けを行います\n賭けの対象となる候補を指定し、賭けるポイントは正の整数を指定してください\n参加賞として1000ptもらえます\n既に bet している候補に bet するとポイントが追加されます\n`@BOT_bookmaker bet 候補A ポイント数 候補B ポイント数`のように複数の候補に分けて賭けることもできます\n`@BOT_bookmaker bet exacta 候補A>候補B ポイント数`で1着と2着を、`@BOT_bookmaker bet trifecta 候補A>候補B>候補C ポイント数`で1着から3着までを順番通りに当てる bet ができます\n数値を予想する賭けでは`@BOT_bookmaker bet guess 予想 ポイント数`の形式で予想できます。もう一度 bet すると予想が更新され、ポイントが追加されます\nチャンネルで複数の賭けが進行中の場合は`@BOT_bookmaker bet #2 候補A ポイント数`のように番号を指定してください\n`@BOT_bookmaker bet 候補A ポイント数`の形式で指定できます"
                                 .to_string(),
                             "@BOT_bookmaker bet 候補A 1000".to_string(),
                         ),
@@ -194,17 +249,209 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
                     .await?;
                 return Ok(());
             }
+            let (match_number, args) = split_match_number(&args);
+            if args.first().map(|s| s.as_str()) == Some("guess") {
+                bet::handle_guess(
+                    modules,
+                    bet::GuessBetArg::new(
+                        event.message.user.id,
+                        args.get(1).cloned().unwrap_or_default(),
+                        args.get(2)
+                            .and_then(|s| s.parse::<i32>().ok())
+                            .unwrap_or_default(),
+                        channel_id,
+                        match_number,
+                        event.message.id,
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+            let bet_type = match args.first().map(|s| s.as_str()) {
+                Some("exacta") => Some(BetType::Exacta),
+                Some("trifecta") => Some(BetType::Trifecta),
+                _ => None,
+            };
+            if let Some(bet_type) = bet_type {
+                bet::handle_ordered(
+                    modules,
+                    bet::OrderedBetArg::new(
+                        event.message.user.id,
+                        bet_type,
+                        args.get(1)
+                            .map(|order| order.split('>').map(|s| s.to_string()).collect())
+                            .unwrap_or_default(),
+                        args.get(2)
+                            .and_then(|s| s.parse::<i32>().ok())
+                            .unwrap_or_default(),
+                        channel_id,
+                        match_number,
+                        event.message.id,
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
             bet::handle(
                 modules,
                 bet::BetArg::new(
                     event.message.user.id,
-                    args.first()
-                        .and_then(|s| Some(s.to_string()))
-                        .unwrap_or_default(),
-                    args.get(1)
-                        .and_then(|s| s.parse::<i32>().ok())
-                        .unwrap_or_default(),
+                    // 候補名とポイント数の組を繰り返し指定できる
+                    args.chunks(2)
+                        .map(|pair| {
+                            (
+                                pair[0].to_string(),
+                                pair.get(1)
+                                    .and_then(|s| s.parse::<i32>().ok())
+                                    .unwrap_or_default(),
+                            )
+                        })
+                        .collect(),
+                    channel_id,
+                    match_number,
+                    event.message.id,
+                ),
+            )
+            .await?
+        }
+        "buy" | "sell" => {
+            if is_help_command(&args) {
+                modules
+                    .message_use_case()
+                    .send_help_message(SendHelpMessage::new(
+                        channel_id,
+                        Command::new(
+                            command_name.to_string(),
+                            "株の売買".to_string(),
+                            "`--lmsr`を指定して開始した賭けで、候補の株を売買します\n株の価格は売買されるたびに変わり、その候補が勝つ確率として賭けのメッセージに表示されます\n精算時には勝った候補の株 1 株につき 1pt が払い戻されます\n締め切り前であれば`@BOT_bookmaker sell 候補A 株数`で株を売り、その時点の価格でポイントを受け取れます\n`@BOT_bookmaker buy 候補A 株数`の形式で指定できます"
+                                .to_string(),
+                            "@BOT_bookmaker buy 候補A 10".to_string(),
+                        ),
+                    ))
+                    .await?;
+                return Ok(());
+            }
+            let (match_number, args) = split_match_number(&args);
+            let shares = args
+                .get(1)
+                .and_then(|s| s.parse::<i32>().ok())
+                .filter(|shares| *shares > 0)
+                .unwrap_or_default();
+            bet::handle_trade(
+                modules,
+                bet::TradeArg::new(
+                    event.message.user.id,
+                    args.first().cloned().unwrap_or_default(),
+                    if command_name == "buy" {
+                        shares
+                    } else {
+                        -shares
+                    },
+                    channel_id,
+                    match_number,
+                    event.message.id,
+                ),
+            )
+            .await?
+        }
+        "unbet" => {
+            if is_help_command(&args) {
+                modules
+                    .message_use_case()
+                    .send_help_message(SendHelpMessage::new(
+                        channel_id,
+                        Command::new(
+                            "unbet".to_string(),
+                            "bet の取り消し".to_string(),
+                            "締め切り前の bet を取り消します\n候補を指定した場合はその候補への bet だけを取り消します\n賭けたポイントは返却されますが、全ての bet を取り消した場合は参加賞の1000ptも取り消されます\n`@BOT_bookmaker unbet [候補名]`の形式で指定できます"
+                                .to_string(),
+                            "@BOT_bookmaker unbet".to_string(),
+                        ),
+                    ))
+                    .await?;
+                return Ok(());
+            }
+            let (match_number, args) = split_match_number(&args);
+            unbet::handle(
+                modules,
+                unbet::UnbetArg::new(
+                    event.message.user.id,
+                    args.first().cloned(),
+                    channel_id,
+                    match_number,
+                    event.message.id,
+                ),
+            )
+            .await?
+        }
+        "cashout" => {
+            if is_help_command(&args) {
+                modules
+                    .message_use_case()
+                    .send_help_message(SendHelpMessage::new(
+                        channel_id,
+                        Command::new(
+                            "cashout".to_string(),
+                            "bet の cash out".to_string(),
+                            "受付中のパリミュチュエルの賭けで、1 着を当てる bet を取り下げてポイントを受け取ります\n締め切り後は cash out できません\n受け取れるポイントは、現在のプールで候補が勝った場合の払い戻しに、あなたの bet を除いたプールに占める候補の割合を掛け、そこから10%を差し引いた額です。他に誰も bet していない候補では受け取れるポイントはありません\n`@BOT_bookmaker cashout [候補名]`で受け取れるポイントを確認し、`@BOT_bookmaker cashout [候補名] accept 提示されたポイント`で確定します\n確定時に受け取れるポイントが提示されたポイントより下がっていた場合は確定せず、新しいポイントを提示します\n参加賞の取り消し分が残高に足りない場合も cash out できません\n複数の候補に bet している場合は候補を指定してください"
+                                .to_string(),
+                            "@BOT_bookmaker cashout 候補A accept 411".to_string(),
+                        ),
+                    ))
+                    .await?;
+                return Ok(());
+            }
+            let (match_number, args) = split_match_number(&args);
+            // `accept` の後には確定する提示額を続ける
+            let accept = args.iter().position(|arg| arg == "accept");
+            let accepted_value = accept
+                .and_then(|i| args.get(i + 1))
+                .and_then(|value| value.parse::<i32>().ok());
+            cashout::handle(
+                modules,
+                cashout::CashOutArg::new(
+                    event.message.user.id,
+                    args.first().filter(|arg| *arg != "accept").cloned(),
+                    accept.is_some(),
+                    accepted_value,
                     channel_id,
+                    match_number,
+                    event.message.id,
+                ),
+            )
+            .await?
+        }
+        "rebet" => {
+            if is_help_command(&args) {
+                modules
+                    .message_use_case()
+                    .send_help_message(SendHelpMessage::new(
+                        channel_id,
+                        Command::new(
+                            "rebet".to_string(),
+                            "bet の変更".to_string(),
+                            "締め切り前の bet を別の候補に変更します\n賭けたポイントはそのまま移ります\n複数の候補に bet している場合は変更元の候補も指定してください\n`@BOT_bookmaker rebet [変更元の候補] 変更先の候補`の形式で指定できます"
+                                .to_string(),
+                            "@BOT_bookmaker rebet 候補B".to_string(),
+                        ),
+                    ))
+                    .await?;
+                return Ok(());
+            }
+            let (match_number, args) = split_match_number(&args);
+            rebet::handle(
+                modules,
+                rebet::RebetArg::new(
+                    event.message.user.id,
+                    // 引数が 2 つの場合は 1 つ目が変更元の候補
+                    if args.len() >= 2 {
+                        args.first().cloned()
+                    } else {
+                        None
+                    },
+                    args.last().cloned().unwrap_or_default(),
+                    channel_id,
+                    match_number,
                     event.message.id,
                 ),
             )
@@ -219,7 +466,7 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
                         Command::new(
                             "cancel".to_string(),
                             "賭けのキャンセル".to_string(),
-                            "賭けをキャンセルします\nこのチャンネルで最新のまだ勝者が決まっていない賭けのみが有効です\n`@BOT_bookmaker cancel`の形式で指定できます"
+                            "賭けをキャンセルします\nまだ勝者が決まっていない賭けのみが有効です\n賭けられたポイントは全て返却され、賭けはキャンセル済みとして残ります\n`@BOT_bookmaker cancel [#番号]`の形式で指定できます"
                                 .to_string(),
                             "@BOT_bookmaker cancel".to_string(),
                         ),
@@ -227,7 +474,28 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
                     .await?;
                 return Ok(());
             }
-            cancel::handle(modules, cancel::CancelArg::new(event.message.user.id)).await?
+            let (match_number, _) = split_match_number(&args);
+            cancel::handle(modules, cancel::CancelArg::new(channel_id, match_number)).await?
+        }
+        "void" => {
+            if is_help_command(&args) {
+                modules
+                    .message_use_case()
+                    .send_help_message(SendHelpMessage::new(
+                        channel_id,
+                        Command::new(
+                            "void".to_string(),
+                            "賭けの無効".to_string(),
+                            "締め切り済みの賭けを無効にします\n試合が中止になった場合や結果に問題がある場合に使います\n賭けられたポイントは全て返却され、賭けは無効として残ります\n`@BOT_bookmaker void [#番号]`の形式で指定できます"
+                                .to_string(),
+                            "@BOT_bookmaker void".to_string(),
+                        ),
+                    ))
+                    .await?;
+                return Ok(());
+            }
+            let (match_number, _) = split_match_number(&args);
+            void::handle(modules, void::VoidArg::new(channel_id, match_number)).await?
         }
         "finish" => {
             if is_help_command(&args) {
@@ -238,7 +506,7 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
                         Command::new(
                             "finish".to_string(),
                             "賭けの終了".to_string(),
-                            "賭けを終了しポイントを分配します\n既に勝者が決まっている賭けではエラーになります\n丸め処理を適当にやっているため前後で総ポイント数が増減する可能性があります\n`@BOT_bookmaker finish 勝者名`の形式で指定できます"
+                            "賭けを終了しポイントを分配します\n既に勝者が決まっている賭けではエラーになり、同じ賭けが二重に精算されることはありません\n賭けられたポイントの合計を勝者に賭けた額の比率で分配し、総ポイント数は増減しません\n1pt 未満の端数は`config rounding`で設定した方法で配分されます\n同着の場合は勝者を複数指定でき、負けた候補に賭けられたポイントを勝者の数で等分して分配します\n`@BOT_bookmaker finish 1着>2着>3着`のように着順を指定すると、1着を勝者として精算し、着順を当てる bet は着順が一致した bet で分配します\nover/under の賭けでは`@BOT_bookmaker finish 26`のように結果の値を指定すると、基準値と比べて勝者を決めます。基準値と等しい場合は賭けたポイントを返却します\n数値を予想する賭けでは`@BOT_bookmaker finish 26`のように結果の値を指定すると、最も近い予想をした人で分配します。同じだけ近い予想が複数ある場合は賭けた額の比率で分配します\nハンディキャップのある賭けでは`@BOT_bookmaker finish Gen.G 3 PRX 1`のように全ての候補の得点を指定すると、ハンディキャップを加えた得点で勝者を決めます。同点の場合は賭けたポイントを返却します\n`@BOT_bookmaker finish 勝者名`の形式で指定できます"
                                 .to_string(),
                             "@BOT_bookmaker finish 勝者名".to_string(),
                         ),
@@ -246,14 +514,33 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
                     .await?;
                 return Ok(());
             }
+            let (match_number, args) = split_match_number(&args);
             finish::handle(
                 modules,
-                finish::FinishArg::new(
-                    event.message.user.id,
-                    args.first()
-                        .and_then(|s| Some(s.to_string()))
-                        .unwrap_or_default(),
-                ),
+                finish::FinishArg::new(channel_id, match_number, args),
+            )
+            .await?
+        }
+        "config" => {
+            if is_help_command(&args) {
+                modules
+                    .message_use_case()
+                    .send_help_message(SendHelpMessage::new(
+                        channel_id,
+                        Command::new(
+                            "config".to_string(),
+                            "チャンネルの設定".to_string(),
+                            "チャンネルごとの設定を変更します\n引数を指定しない場合は現在の設定を表示します\n`rounding`: 払い戻しの端数の扱い。`largest`(端数の大きい順に配分)、`house`(ハウスが受け取る)、`earliest`(最も早く bet したユーザーが受け取る)から選べます\n`no-winner`: 勝者に誰も賭けていなかった場合の扱い。`refund`(全て返却)、`jackpot`(次の賭けに持ち越し)、`house`(ハウスが受け取る)から選べます\n`commission`: 精算時にプールから差し引いてハウスが受け取る手数料(%)。0から99の整数で指定できます\n`ttl`: 締め切り (締め切りが無い場合は作成) からこの時間が経っても終了しない賭けを無効にし、賭けられたポイントを返却します。1から8760の時間で指定でき、`off`で無効にしなくなります (デフォルトは`off`)\n`@BOT_bookmaker config rounding largest`の形式で指定できます"
+                                .to_string(),
+                            "@BOT_bookmaker config rounding largest".to_string(),
+                        ),
+                    ))
+                    .await?;
+                return Ok(());
+            }
+            config::handle(
+                modules,
+                config::ConfigArg::new(channel_id, args.first().cloned(), args.get(1).cloned()),
             )
             .await?
         }
@@ -276,6 +563,165 @@ pub async fn handle(modules: Arc<Modules>, event: MessageCreatedEvent) -> anyhow
             }
             info::handle(modules, info::InfoArg::new(channel_id)).await?
         }
+        "notify" => {
+            if is_help_command(&args) {
+                modules
+                    .message_use_case()
+                    .send_help_message(SendHelpMessage::new(
+                        channel_id,
+                        Command::new(
+                            "notify".to_string(),
+                            "リマインドの設定".to_string(),
+                            "締め切りが指定された賭けでは、締め切りの10分前に bet を促すリマインドが送られます\n`off`を指定するとリマインドでメンションされなくなり、`on`を指定すると再びメンションされます\n設定はチャンネルごとに保存されます\n`@BOT_bookmaker notify off`の形式で指定できます"
+                                .to_string(),
+                            "@BOT_bookmaker notify off".to_string(),
+                        ),
+                    ))
+                    .await?;
+                return Ok(());
+            }
+            notify::handle(
+                modules,
+                notify::NotifyArg::new(
+                    event.message.user.id,
+                    channel_id,
+                    event.message.id,
+                    args.first().cloned(),
+                ),
+            )
+            .await?
+        }
+        "parlay" => {
+            if is_help_command(&args) {
+                modules
+                    .message_use_case()
+                    .send_help_message(SendHelpMessage::new(
+                        channel_id,
+                        Command::new(
+                            "parlay".to_string(),
+                            "複数の賭けを組み合わせた bet".to_string(),
+                            "他のチャンネルで受付中の賭けから候補を2〜8つ選び、まとめて1つの bet にします\n全ての候補が的中した場合に、それぞれの倍率の積で払い戻します\n倍率はその候補に bet した場合に実際に払い戻された割合で決まります\n1つでも外れるとポイントは戻りません。キャンセルや無効になった賭けは除いて計算します\nポイントはこのチャンネルで差し引かれ、全ての賭けの結果が出るとこのチャンネルで払い戻されます\nチャンネルで複数の賭けが進行中の場合は`#チャンネルA #2 候補A`のようにチャンネルの後に番号を指定してください\n`@BOT_bookmaker parlay ポイント数 #チャンネルA 候補A #チャンネルB 候補B`の形式で指定できます"
+                                .to_string(),
+                            "@BOT_bookmaker parlay 500 #gps/times/alice PRX #gps/times/bob ZETA"
+                                .to_string(),
+                        ),
+                    ))
+                    .await?;
+                return Ok(());
+            }
+            // 埋め込まれたチャンネルと、チャンネル以外の引数を候補として順に組にする
+            // チャンネルの直後に`#2`のような番号があれば、そのチャンネルの賭けの番号とする
+            let channel_ids = event
+                .message
+                .embedded
+                .iter()
+                .filter(|e| e.r#type == "channel")
+                .map(|e| e.id.clone())
+                .collect::<Vec<_>>();
+            let mut match_numbers = Vec::new();
+            let mut candidate_names = Vec::new();
+            for arg in args.iter().skip(1) {
+                if let Some(number) = parse_match_number(arg) {
+                    if let Some(last) = match_numbers.last_mut() {
+                        *last = Some(number);
+                    }
+                } else if arg.starts_with('#') || arg.starts_with("!{") {
+                    match_numbers.push(None);
+                } else {
+                    candidate_names.push(arg.clone());
+                }
+            }
+            let legs = if channel_ids.len() == candidate_names.len()
+                && channel_ids.len() == match_numbers.len()
+            {
+                channel_ids
+                    .into_iter()
+                    .zip(match_numbers)
+                    .zip(candidate_names)
+                    .map(|((channel_id, match_number), candidate_name)| {
+                        (channel_id, match_number, candidate_name)
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            parlay::handle(
+                modules,
+                parlay::ParlayArg::new(
+                    event.message.user.id,
+                    args.first()
+                        .and_then(|s| s.parse::<i32>().ok())
+                        .unwrap_or_default(),
+                    legs,
+                    channel_id,
+                    event.message.id,
+                ),
+            )
+            .await?
+        }
+        "challenge" => {
+            if is_help_command(&args) {
+                modules
+                    .message_use_case()
+                    .send_help_message(SendHelpMessage::new(
+                        channel_id,
+                        Command::new(
+                            "challenge".to_string(),
+                            "個人間の勝負".to_string(),
+                            "メンションしたユーザーに個人的な勝負を挑みます\n挑んだ時点であなたのポイントを預かり、相手が`@BOT_bookmaker accept`で受けると相手のポイントも預かります\n相手は`@BOT_bookmaker decline`で断ることができ、挑んだ側も受けられる前であれば同じコマンドで取り下げられます\n決着したら`@BOT_bookmaker resolve @勝者`で勝者を指定すると、勝者が両者の分のポイントを受け取ります\n審判がいない場合は負けた人が相手を勝者に指定します\n`@BOT_bookmaker challenge @相手 500 \"PRX wins\" @審判`のように2人目をメンションすると、その人だけが勝者を指定できる審判になります\n`@BOT_bookmaker challenge @相手 ポイント数 内容`の形式で指定できます"
+                                .to_string(),
+                            "@BOT_bookmaker challenge @alice 500 \"PRX wins\"".to_string(),
+                        ),
+                    ))
+                    .await?;
+                return Ok(());
+            }
+            // メンションを除いた引数をポイント数と内容として扱う
+            let rest = args
+                .iter()
+                .filter(|s| !s.starts_with('@'))
+                .collect::<Vec<_>>();
+            challenge::handle(
+                modules,
+                challenge::ChallengeArg::new(
+                    event.message.user.id,
+                    mentioned_traq_ids,
+                    rest.first()
+                        .and_then(|s| s.parse::<i32>().ok())
+                        .unwrap_or_default(),
+                    rest.get(1).map(|s| s.to_string()).unwrap_or_default(),
+                    channel_id,
+                    event.message.id,
+                ),
+            )
+            .await?
+        }
+        "accept" => {
+            challenge::handle_accept(
+                modules,
+                challenge::AcceptArg::new(event.message.user.id, channel_id, event.message.id),
+            )
+            .await?
+        }
+        "decline" => {
+            challenge::handle_decline(
+                modules,
+                challenge::DeclineArg::new(event.message.user.id, channel_id, event.message.id),
+            )
+            .await?
+        }
+        "resolve" => {
+            challenge::handle_resolve(
+                modules,
+                challenge::ResolveArg::new(
+                    event.message.user.id,
+                    mentioned_traq_ids.first().cloned(),
+                    channel_id,
+                    event.message.id,
+                ),
+            )
+            .await?
+        }
         _ => {
             return Err(anyhow::anyhow!("Unknown command: {}", command_name));
         }
@@ -0,0 +1,28 @@
+use derive_new::new;
+use std::sync::Arc;
+
+use app::model::user::ConfigureNotify;
+
+use crate::module::{Modules, ModulesExt};
+
+#[derive(new)]
+pub struct NotifyArg {
+    pub traq_id: String,
+    pub channel_id: String,
+    pub message_id: String,
+    pub value: Option<String>,
+}
+
+pub async fn handle(modules: Arc<Modules>, arg: NotifyArg) -> anyhow::Result<()> {
+    modules
+        .user_use_case()
+        .configure_notify(ConfigureNotify::new(
+            arg.traq_id,
+            arg.channel_id,
+            arg.message_id,
+            arg.value,
+        ))
+        .await?;
+
+    Ok(())
+}
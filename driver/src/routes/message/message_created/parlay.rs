@@ -0,0 +1,36 @@
+use derive_new::new;
+use std::sync::Arc;
+
+use app::model::parlay::{CreateParlay, CreateParlayLeg};
+
+use crate::module::{Modules, ModulesExt};
+
+#[derive(new)]
+pub struct ParlayArg {
+    pub traq_id: String,
+    pub amount: i32,
+    // 埋め込まれたチャンネルの ID と、そのチャンネルの賭けの番号と、選んだ候補の組
+    pub legs: Vec<(String, Option<i32>, String)>,
+    pub channel_id: String,
+    pub message_id: String,
+}
+
+pub async fn handle(modules: Arc<Modules>, arg: ParlayArg) -> anyhow::Result<()> {
+    modules
+        .parlay_use_case()
+        .create_parlay(CreateParlay::new(
+            arg.channel_id,
+            arg.message_id,
+            arg.traq_id,
+            arg.amount,
+            arg.legs
+                .into_iter()
+                .map(|(channel_id, match_number, candidate_name)| {
+                    CreateParlayLeg::new(channel_id, match_number, candidate_name)
+                })
+                .collect(),
+        ))
+        .await?;
+
+    Ok(())
+}
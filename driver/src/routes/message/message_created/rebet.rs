@@ -0,0 +1,41 @@
+use derive_new::new;
+use kernel::model::Id;
+use std::sync::Arc;
+
+use app::model::{bet::ChangeBet, message::r#match::UpsertMatchMessage};
+
+use crate::module::{Modules, ModulesExt};
+
+#[derive(new)]
+pub struct RebetArg {
+    pub traq_id: String,
+    pub from_candidate_name: Option<String>,
+    pub candidate_name: String,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+}
+
+pub async fn handle(modules: Arc<Modules>, arg: RebetArg) -> anyhow::Result<()> {
+    let bet = modules
+        .bet_use_case()
+        .change_bet(ChangeBet::new(
+            arg.channel_id.clone(),
+            arg.match_number,
+            arg.message_id,
+            arg.traq_id,
+            arg.from_candidate_name,
+            arg.candidate_name,
+        ))
+        .await?;
+
+    modules
+        .message_use_case()
+        .upsert_match_message(UpsertMatchMessage::new(
+            Id::new(arg.channel_id),
+            bet.match_id,
+        ))
+        .await?;
+
+    Ok(())
+}
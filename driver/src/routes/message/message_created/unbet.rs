@@ -0,0 +1,39 @@
+use derive_new::new;
+use kernel::model::Id;
+use std::sync::Arc;
+
+use app::model::{bet::CancelBet, message::r#match::UpsertMatchMessage};
+
+use crate::module::{Modules, ModulesExt};
+
+#[derive(new)]
+pub struct UnbetArg {
+    pub traq_id: String,
+    pub candidate_name: Option<String>,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub message_id: String,
+}
+
+pub async fn handle(modules: Arc<Modules>, arg: UnbetArg) -> anyhow::Result<()> {
+    let bets = modules
+        .bet_use_case()
+        .cancel_bet(CancelBet::new(
+            arg.channel_id.clone(),
+            arg.match_number,
+            arg.message_id,
+            arg.traq_id,
+            arg.candidate_name,
+        ))
+        .await?;
+
+    modules
+        .message_use_case()
+        .upsert_match_message(UpsertMatchMessage::new(
+            Id::new(arg.channel_id),
+            Id::new(bets[0].match_id.value.clone()),
+        ))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use derive_new::new;
+use kernel::model::Id;
+use std::sync::Arc;
+
+use app::model::{message::r#match::UpsertMatchMessage, r#match::VoidMatch};
+
+use crate::module::{Modules, ModulesExt};
+
+#[derive(new)]
+pub struct VoidArg {
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+}
+
+pub async fn handle(modules: Arc<Modules>, arg: VoidArg) -> anyhow::Result<()> {
+    let match_ = modules
+        .match_use_case()
+        .void_match(VoidMatch::new(arg.channel_id.clone(), arg.match_number))
+        .await?;
+
+    // 固定されている賭けのメッセージにも無効になったことを反映する
+    modules
+        .message_use_case()
+        .upsert_match_message(UpsertMatchMessage::new(Id::new(arg.channel_id), match_.id))
+        .await?;
+
+    Ok(())
+}
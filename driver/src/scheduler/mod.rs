@@ -0,0 +1,113 @@
+use std::{sync::Arc, time::Duration};
+
+use app::model::message::r#match::{SendMatchReminder, UpsertMatchMessage};
+use kernel::model::{
+    job::{Job, JobKind},
+    Id,
+};
+use tracing::warn;
+
+use crate::module::{Modules, ModulesExt};
+
+// 実行時刻を過ぎたジョブがあるか確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+// 1 回の確認で取得するジョブの数
+const CLAIM_LIMIT: u64 = 10;
+
+// ジョブは DB に保存しているので、再起動しても次の確認で実行される
+// 実行中に落ちたジョブは期限を過ぎると再び取得されるので、同じジョブが複数回実行されることがある
+pub async fn run(modules: Arc<Modules>) {
+    // 繰り返すジョブは起動するたびに登録を確認する
+    if let Err(e) = modules.job_use_case().schedule_expire_matches().await {
+        warn!("Failed to schedule jobs: {:?}", e);
+    }
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let jobs = match modules.job_use_case().claim_due_jobs(CLAIM_LIMIT).await {
+            Ok(jobs) => jobs,
+            Err(e) => {
+                warn!("Failed to claim jobs: {:?}", e);
+                continue;
+            }
+        };
+        for job in jobs {
+            let lease = job.lease();
+            let result = match execute(modules.clone(), job).await {
+                Ok(_) => modules.job_use_case().complete_job(lease).await,
+                Err(e) => {
+                    warn!("Failed to execute job {}: {:?}", lease.job_id.value, e);
+                    modules
+                        .job_use_case()
+                        .fail_job(lease, format!("{:?}", e))
+                        .await
+                }
+            };
+            if let Err(e) = result {
+                warn!("Failed to update job: {:?}", e);
+            }
+        }
+    }
+}
+
+async fn execute(modules: Arc<Modules>, job: Job) -> anyhow::Result<()> {
+    match job.kind {
+        JobKind::CloseMatch => close_match(modules, job.payload).await,
+        JobKind::RemindMatch => {
+            modules
+                .message_use_case()
+                .send_match_reminder(SendMatchReminder::new(Id::new(job.payload)))
+                .await?;
+            Ok(())
+        }
+        JobKind::ExpireMatches => expire_matches(modules).await,
+    }
+}
+
+// `close` コマンドと同じ手順で締め切り、賭けのメッセージを更新する
+async fn close_match(modules: Arc<Modules>, match_id: String) -> anyhow::Result<()> {
+    let Some(match_) = modules
+        .match_use_case()
+        .close_match_by_deadline(Id::new(match_id))
+        .await?
+    else {
+        return Ok(());
+    };
+
+    modules
+        .message_use_case()
+        .upsert_match_message(UpsertMatchMessage::new(
+            Id::new(match_.channel_id.value),
+            match_.id,
+        ))
+        .await?;
+
+    Ok(())
+}
+
+// 無効にした match のメッセージを更新する
+// 無効にできなかった match があればジョブを失敗させ、次の実行で再び無効にする
+async fn expire_matches(modules: Arc<Modules>) -> anyhow::Result<()> {
+    let (matches, errors) = modules.match_use_case().void_expired_matches().await?;
+    let mut errors = errors
+        .into_iter()
+        .map(anyhow::Error::from)
+        .collect::<Vec<_>>();
+    for match_ in matches {
+        if let Err(e) = modules
+            .message_use_case()
+            .upsert_match_message(UpsertMatchMessage::new(
+                Id::new(match_.channel_id.value),
+                match_.id,
+            ))
+            .await
+        {
+            errors.push(e.into());
+        }
+    }
+
+    if !errors.is_empty() {
+        anyhow::bail!("Failed to expire {} matches: {:?}", errors.len(), errors);
+    }
+    Ok(())
+}
@@ -1,8 +1,10 @@
-use crate::{module::Modules, routes::message::post_message};
+use crate::{module::Modules, routes::message::post_message, scheduler};
 use axum::{routing::post, Router};
 use std::sync::Arc;
 
 pub async fn startup(modules: Arc<Modules>) {
+    tokio::spawn(scheduler::run(modules.clone()));
+
     let app = Router::new()
         .route("/message", post(post_message))
         .with_state(modules.clone());
@@ -1,21 +1,151 @@
 use derive_new::new;
 
-use super::{candidate::Candidate, r#match::Match, user::User, Id};
+use super::{candidate::Candidate, r#match::Match, user::User, DateTimeUtc, Id};
 
-#[derive(new, Debug)]
+#[derive(Debug)]
 pub struct Bet {
     pub id: Id<Bet>,
     pub user_id: Id<User>,
     pub match_id: Id<Match>,
+    // 着順を当てる bet の場合は 1 着の候補
     pub candidate_id: Id<Candidate>,
     pub amount: i32,
+    pub created_at: DateTimeUtc,
+    // 固定オッズの場合に bet した時点の倍率を 100 倍した整数
+    pub odds: Option<i32>,
+    pub bet_type: BetType,
+    // 着順を当てる bet の場合の 2 着以降の候補
+    pub following_candidate_ids: Vec<Id<Candidate>>,
+    // 数値を予想する bet の場合の予想を 100 倍した整数
+    pub guess: Option<i32>,
+}
+
+impl Bet {
+    // 1 着から順に並べた、この bet が当てようとしている着順
+    pub fn order(&self) -> Vec<&Id<Candidate>> {
+        std::iter::once(&self.candidate_id)
+            .chain(self.following_candidate_ids.iter())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BetType {
+    // 1 着を当てる
+    Win,
+    // 1 着と 2 着を順番通りに当てる
+    Exacta,
+    // 1 着から 3 着までを順番通りに当てる
+    Trifecta,
+    // 数値を予想する
+    Guess,
+}
+
+impl BetType {
+    // 当てる必要のある着順の数
+    pub fn placings(&self) -> usize {
+        match self {
+            BetType::Win => 1,
+            BetType::Exacta => 2,
+            BetType::Trifecta => 3,
+            BetType::Guess => 0,
+        }
+    }
 }
 
+// 1 回の bet で複数の候補にポイントを分けて賭けられる
 #[derive(new, Debug)]
 pub struct NewBetForLatestMatch {
+    pub traq_id: String,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub stakes: Vec<NewStake>,
+    pub created_at: DateTimeUtc,
+}
+
+// 候補ごとの賭け。既に同じ候補に bet している場合はポイントが追加される
+#[derive(new, Debug)]
+pub struct NewStake {
+    pub id: Id<Bet>,
+    pub candidate_name: String,
+    pub amount: i32,
+}
+
+// 着順を当てる bet。candidate_names には 1 着から順に候補を指定する
+// 既に同じ着順に bet している場合はポイントが追加される
+#[derive(Debug)]
+pub struct NewOrderedBetForLatestMatch {
+    pub id: Id<Bet>,
+    pub traq_id: String,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub bet_type: BetType,
+    pub candidate_names: Vec<String>,
+    pub amount: i32,
+    pub created_at: DateTimeUtc,
+}
+
+// 数値を予想する bet。1 人 1 つの予想だけができ、再び bet した場合は予想を更新してポイントを追加する
+#[derive(new, Debug)]
+pub struct NewGuessBetForLatestMatch {
     pub id: Id<Bet>,
     pub traq_id: String,
     pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub guess: i32,
+    pub amount: i32,
+    pub created_at: DateTimeUtc,
+}
+
+// 締め切り前の bet を取り消す。candidate_name が None の場合は全ての候補への bet を取り消す
+#[derive(new, Debug)]
+pub struct DeleteBetForLatestMatch {
+    pub traq_id: String,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub candidate_name: Option<String>,
+}
+
+// 締め切り前の bet の候補を変更する
+// 複数の候補に bet している場合は from_candidate_name で変更元を指定する
+#[derive(new, Debug)]
+pub struct UpdateBetForLatestMatch {
+    pub traq_id: String,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub from_candidate_name: Option<String>,
     pub candidate_name: String,
+}
+
+// 締め切り前に 1 着を当てる bet を取り下げ、提示額を受け取る
+// 複数の候補に bet している場合は candidate_name で指定する
+// accepted_value はユーザーが確定した提示額。None の場合は提示額を計算するだけで bet は変更しない
+// 確定時の提示額が accepted_value を下回る場合も確定せず、その時点の提示額を返す
+#[derive(new, Debug)]
+pub struct CashOutBetForLatestMatch {
+    pub traq_id: String,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub candidate_name: Option<String>,
+    pub accepted_value: Option<i32>,
+}
+
+// cash out の結果。balance は confirmed が false の場合は現在の残高
+#[derive(new, Debug)]
+pub struct CashOut {
+    pub bet: Bet,
+    pub value: i32,
+    // 最後の bet を cash out したことで取り消す参加賞
+    pub prize: i32,
+    pub balance: i32,
+    // bet を取り下げて提示額を支払ったか
+    pub confirmed: bool,
+}
+
+#[derive(new, Debug)]
+pub struct Refund {
+    pub user_id: Id<User>,
+    // 返却したポイント。LMSR の株の売却で出ていた利益を回収した場合は負になる
     pub amount: i32,
+    pub balance: i32,
 }
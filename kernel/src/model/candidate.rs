@@ -2,12 +2,28 @@ use derive_new::new;
 
 use super::{r#match::Match, Id};
 
-#[derive(new, Debug)]
+#[derive(Debug)]
 pub struct Candidate {
     pub id: Id<Candidate>,
     pub name: String,
     pub match_id: Id<Match>,
     pub is_winner: Option<bool>,
+    // 固定オッズの場合の倍率を 100 倍した整数。1.8 倍なら 180
+    pub odds: Option<i32>,
+    // 着順が記録された場合の順位。1 着なら 1
+    pub placing: Option<i32>,
+    // ハンディキャップを 100 倍した整数。-3.5 なら -350
+    pub handicap: Option<i32>,
+    // ハンディキャップのある賭けで記録された得点
+    pub score: Option<i32>,
+}
+
+impl Candidate {
+    // ハンディキャップを加えた得点を 100 倍した整数
+    pub fn adjusted_score(&self) -> Option<i32> {
+        self.score
+            .map(|score| score * 100 + self.handicap.unwrap_or_default())
+    }
 }
 
 #[derive(new, Debug)]
@@ -15,4 +31,6 @@ pub struct NewCandidate {
     pub id: Id<Candidate>,
     pub name: String,
     pub match_id: Id<Match>,
+    pub odds: Option<i32>,
+    pub handicap: Option<i32>,
 }
@@ -0,0 +1,130 @@
+use derive_new::new;
+
+use super::{channel::Channel, user::User, DateTimeUtc, Id};
+
+// 2 人のユーザーの間での個人的な賭け
+#[derive(Debug)]
+pub struct Challenge {
+    pub id: Id<Challenge>,
+    pub channel_id: Id<Channel>,
+    pub title: String,
+    // 1 人あたりが賭けるポイント。勝者は両者の分を受け取る
+    pub amount: i32,
+    pub challenger_id: Id<User>,
+    pub opponent_id: Id<User>,
+    // 指定された場合は当事者ではなくこのユーザーが勝者を決める
+    pub arbiter_id: Option<Id<User>>,
+    pub winner_id: Option<Id<User>>,
+    pub status: ChallengeStatus,
+    pub created_at: DateTimeUtc,
+    pub accepted_at: Option<DateTimeUtc>,
+    pub finished_at: Option<DateTimeUtc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengeStatus {
+    // 相手が受けるのを待っている。挑んだ側のポイントだけを預かっている
+    Pending,
+    // 相手が受けて勝負が成立した。両者のポイントを預かっている
+    Accepted,
+    // 相手が断ったか、挑んだ側が取り下げた
+    Declined,
+    Settled,
+}
+
+impl Challenge {
+    // 審判がいる場合は審判だけが勝者を決められる
+    // いない場合は当事者が相手の勝ちを認めることで決まり、自分を勝者にすることはできない
+    pub fn can_resolve(&self, user_id: &Id<User>, winner_id: &Id<User>) -> bool {
+        let is_party = |id: &Id<User>| {
+            id.value == self.challenger_id.value || id.value == self.opponent_id.value
+        };
+        if !is_party(winner_id) {
+            return false;
+        }
+        match &self.arbiter_id {
+            Some(arbiter_id) => arbiter_id.value == user_id.value,
+            None => is_party(user_id) && user_id.value != winner_id.value,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct NewChallenge {
+    pub id: Id<Challenge>,
+    pub channel_id: Id<Channel>,
+    pub title: String,
+    pub amount: i32,
+    pub challenger_traq_id: String,
+    pub opponent_traq_id: String,
+    pub arbiter_traq_id: Option<String>,
+    pub created_at: DateTimeUtc,
+}
+
+// traq_id のユーザーに挑まれている最新の勝負を受ける
+#[derive(new, Debug)]
+pub struct AcceptChallengeForLatest {
+    pub channel_id: Id<Channel>,
+    pub traq_id: String,
+    pub accepted_at: DateTimeUtc,
+}
+
+// traq_id のユーザーが当事者になっている、まだ受けられていない最新の勝負を断る
+#[derive(new, Debug)]
+pub struct DeclineChallengeForLatest {
+    pub channel_id: Id<Channel>,
+    pub traq_id: String,
+    pub declined_at: DateTimeUtc,
+}
+
+// traq_id のユーザーが勝者を決められる、成立済みの最新の勝負を精算する
+#[derive(new, Debug)]
+pub struct ResolveChallengeForLatest {
+    pub channel_id: Id<Channel>,
+    pub traq_id: String,
+    pub winner_traq_id: String,
+    pub resolved_at: DateTimeUtc,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge(arbiter_id: Option<&str>) -> Challenge {
+        Challenge {
+            id: Id::new("challenge".to_string()),
+            channel_id: Id::new("channel".to_string()),
+            title: "PRX wins".to_string(),
+            amount: 500,
+            challenger_id: Id::new("alice".to_string()),
+            opponent_id: Id::new("bob".to_string()),
+            arbiter_id: arbiter_id.map(|id| Id::new(id.to_string())),
+            winner_id: None,
+            status: ChallengeStatus::Accepted,
+            created_at: chrono::Utc::now(),
+            accepted_at: Some(chrono::Utc::now()),
+            finished_at: None,
+        }
+    }
+
+    fn id(value: &str) -> Id<User> {
+        Id::new(value.to_string())
+    }
+
+    #[test]
+    fn test_challenge_resolver() {
+        // 審判がいない場合は相手の勝ちを認めることしかできない
+        let c = challenge(None);
+        assert!(c.can_resolve(&id("alice"), &id("bob")));
+        assert!(c.can_resolve(&id("bob"), &id("alice")));
+        assert!(!c.can_resolve(&id("alice"), &id("alice")));
+        assert!(!c.can_resolve(&id("carol"), &id("alice")));
+
+        // 審判がいる場合は審判だけがどちらの勝ちにもできる
+        let c = challenge(Some("carol"));
+        assert!(c.can_resolve(&id("carol"), &id("alice")));
+        assert!(c.can_resolve(&id("carol"), &id("bob")));
+        assert!(!c.can_resolve(&id("alice"), &id("bob")));
+        assert!(!c.can_resolve(&id("carol"), &id("carol")));
+    }
+}
@@ -1,4 +1,55 @@
 use derive_new::new;
 
+use super::{
+    settlement::{NoWinnerPolicy, RoundingPolicy},
+    Id,
+};
+
+// 終了しないまま放置された match を無効にするまでの時間のデフォルト (時間)
+// 既存のチャンネルの match が突然無効にならないよう、デフォルトでは無効にせず `config ttl` で設定したチャンネルだけ無効にする
+pub const DEFAULT_MATCH_TTL_HOURS: i32 = 0;
+// 設定できる最大の時間 (時間)
+pub const MAX_MATCH_TTL_HOURS: i32 = 365 * 24;
+
+#[derive(new, Debug)]
+pub struct Channel {
+    pub id: Id<Channel>,
+    pub rounding_policy: RoundingPolicy,
+    pub house_balance: i32,
+    pub no_winner_policy: NoWinnerPolicy,
+    // 勝者不在で持ち越され、次の match のプールに上乗せされるポイント
+    pub jackpot: i32,
+    // 精算時にプールから差し引いてハウスが受け取る手数料 (%)
+    pub commission_percent: i32,
+    // 締め切り (無ければ作成) からこの時間が経っても終了しない match を無効にする。0 の場合は無効にしない
+    pub match_ttl_hours: i32,
+}
+
+impl Channel {
+    // 手数料を差し引いた後に払い戻しへ回る割合
+    pub fn payout_ratio(&self) -> f64 {
+        (100 - self.commission_percent) as f64 / 100.0
+    }
+
+    // まだ設定が保存されていないチャンネルの設定
+    pub fn default_for(id: Id<Channel>) -> Channel {
+        Channel::new(
+            id,
+            RoundingPolicy::LargestRemainder,
+            0,
+            NoWinnerPolicy::Refund,
+            0,
+            0,
+            DEFAULT_MATCH_TTL_HOURS,
+        )
+    }
+}
+
 #[derive(new, Debug)]
-pub struct Channel {}
+pub struct UpdateChannel {
+    pub id: Id<Channel>,
+    pub rounding_policy: Option<RoundingPolicy>,
+    pub no_winner_policy: Option<NoWinnerPolicy>,
+    pub commission_percent: Option<i32>,
+    pub match_ttl_hours: Option<i32>,
+}
@@ -0,0 +1,242 @@
+use derive_new::new;
+
+use super::{DateTimeUtc, Id};
+
+// 実行に失敗したジョブを再実行する回数の上限
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+// 放置された match を確認する間隔
+pub const EXPIRE_MATCHES_INTERVAL_SECS: i32 = 10 * 60;
+// 実行中のジョブがこの秒数を過ぎても完了しなければ、worker が落ちたものとして再び実行する
+pub const JOB_LEASE_SECS: i64 = 5 * 60;
+
+// 決まった時刻に worker が実行する処理。少なくとも 1 回は実行されるので、何度実行しても結果が変わらないようにする
+#[derive(Debug)]
+pub struct Job {
+    pub id: Id<Job>,
+    pub kind: JobKind,
+    // 処理の対象。CloseMatch と RemindMatch なら match の ID
+    pub payload: String,
+    pub run_at: DateTimeUtc,
+    // 設定されていれば、実行するたびにこの秒数だけ後に再び実行する
+    pub interval_secs: Option<i32>,
+    pub status: JobStatus,
+    // 今回の実行を含めて、続けて実行した回数
+    pub attempts: i32,
+    pub max_attempts: i32,
+    // 実行中のジョブは、この時刻を過ぎると再び実行できるようになる
+    pub locked_until: Option<DateTimeUtc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    // 締め切り時刻になった match の bet を締め切る
+    CloseMatch,
+    // 締め切りが近い match のリマインドを送る
+    RemindMatch,
+    // 終了しないまま放置された match を無効にする。繰り返し実行する
+    ExpireMatches,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done,
+    // 再実行の上限まで失敗した
+    Failed,
+}
+
+// ジョブの次の状態
+#[derive(Debug, PartialEq, Eq)]
+pub struct JobSchedule {
+    pub status: JobStatus,
+    pub run_at: DateTimeUtc,
+    pub attempts: i32,
+}
+
+// 今から実行するジョブを、他の worker が取得しないようにしておく期限
+pub fn lease_until(now: DateTimeUtc) -> DateTimeUtc {
+    now + chrono::Duration::seconds(JOB_LEASE_SECS)
+}
+
+// 失敗した回数に応じて再実行までの時間を延ばす。30 秒から倍々に延ばし、最大 1 時間とする
+pub fn retry_delay(attempts: i32) -> chrono::Duration {
+    let secs = 30_i64 << attempts.clamp(1, 8).saturating_sub(1);
+    chrono::Duration::seconds(secs.min(60 * 60))
+}
+
+// 繰り返すジョブの次の実行時刻。止まっていた間の実行は飛ばして、now より後の最初の時刻にする
+fn next_occurrence(run_at: DateTimeUtc, interval_secs: i32, now: DateTimeUtc) -> DateTimeUtc {
+    let interval = chrono::Duration::seconds(interval_secs.max(1) as i64);
+    let missed = (now - run_at).num_seconds().max(0) / interval.num_seconds();
+    run_at + interval * (missed as i32 + 1)
+}
+
+// ジョブが完了した後の状態。繰り返すジョブは次の実行時刻まで待機させる
+pub fn schedule_after_success(job: &Job, now: DateTimeUtc) -> JobSchedule {
+    match job.interval_secs {
+        Some(interval_secs) => JobSchedule {
+            status: JobStatus::Pending,
+            run_at: next_occurrence(job.run_at, interval_secs, now),
+            attempts: 0,
+        },
+        None => JobSchedule {
+            status: JobStatus::Done,
+            run_at: job.run_at,
+            attempts: job.attempts,
+        },
+    }
+}
+
+// ジョブが失敗した後の状態。上限に達するまでは間隔を空けて再実行する
+// 繰り返すジョブは上限に達した回を諦め、次の実行時刻から続ける
+pub fn schedule_after_failure(job: &Job, now: DateTimeUtc) -> JobSchedule {
+    if job.attempts < job.max_attempts {
+        return JobSchedule {
+            status: JobStatus::Pending,
+            run_at: now + retry_delay(job.attempts),
+            attempts: job.attempts,
+        };
+    }
+    match job.interval_secs {
+        Some(_) => schedule_after_success(job, now),
+        None => JobSchedule {
+            status: JobStatus::Failed,
+            run_at: job.run_at,
+            attempts: job.attempts,
+        },
+    }
+}
+
+// ジョブを取得した worker が持つ実行の権利。期限が切れて他の worker が取得し直した後は、結果を記録できない
+#[derive(new, Debug)]
+pub struct JobLease {
+    pub job_id: Id<Job>,
+    pub attempts: i32,
+    pub locked_until: Option<DateTimeUtc>,
+}
+
+impl Job {
+    pub fn lease(&self) -> JobLease {
+        JobLease::new(
+            Id::new(self.id.value.clone()),
+            self.attempts,
+            self.locked_until,
+        )
+    }
+}
+
+impl JobLease {
+    // 取得し直されておらず、まだこの権利でジョブの結果を記録できるか
+    pub fn is_held(&self, job: &Job) -> bool {
+        job.status == JobStatus::Running
+            && job.attempts == self.attempts
+            && job.locked_until == self.locked_until
+    }
+}
+
+#[derive(new, Debug)]
+pub struct NewJob {
+    pub id: Id<Job>,
+    pub kind: JobKind,
+    pub payload: String,
+    pub run_at: DateTimeUtc,
+    pub interval_secs: Option<i32>,
+    pub max_attempts: i32,
+    pub created_at: DateTimeUtc,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTimeUtc {
+        s.parse::<DateTimeUtc>().unwrap()
+    }
+
+    fn job(interval_secs: Option<i32>, attempts: i32) -> Job {
+        Job {
+            id: Id::gen(),
+            kind: JobKind::CloseMatch,
+            payload: "match".to_string(),
+            run_at: at("2024-06-30T12:00:00Z"),
+            interval_secs,
+            status: JobStatus::Running,
+            attempts,
+            max_attempts: 3,
+            locked_until: None,
+            last_error: None,
+            created_at: at("2024-06-30T00:00:00Z"),
+        }
+    }
+
+    #[test]
+    fn test_job_lease_is_held() {
+        let mut claimed = job(None, 1);
+        claimed.locked_until = Some(at("2024-06-30T12:05:00Z"));
+        let lease = claimed.lease();
+        assert!(lease.is_held(&claimed));
+        // 期限が切れて他の worker が取得し直した後は記録できない
+        claimed.attempts = 2;
+        claimed.locked_until = Some(at("2024-06-30T12:10:00Z"));
+        assert!(!lease.is_held(&claimed));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), chrono::Duration::seconds(30));
+        assert_eq!(retry_delay(2), chrono::Duration::seconds(60));
+        assert_eq!(retry_delay(4), chrono::Duration::seconds(240));
+        assert_eq!(retry_delay(100), chrono::Duration::seconds(60 * 60));
+    }
+
+    #[test]
+    fn test_schedule_after_success() {
+        let now = at("2024-06-30T12:00:05Z");
+        assert_eq!(
+            schedule_after_success(&job(None, 1), now),
+            JobSchedule {
+                status: JobStatus::Done,
+                run_at: at("2024-06-30T12:00:00Z"),
+                attempts: 1,
+            }
+        );
+        // 止まっていた間の実行は飛ばす
+        let now = at("2024-06-30T15:30:00Z");
+        assert_eq!(
+            schedule_after_success(&job(Some(3600), 1), now),
+            JobSchedule {
+                status: JobStatus::Pending,
+                run_at: at("2024-06-30T16:00:00Z"),
+                attempts: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_schedule_after_failure() {
+        let now = at("2024-06-30T12:00:05Z");
+        assert_eq!(
+            schedule_after_failure(&job(None, 2), now),
+            JobSchedule {
+                status: JobStatus::Pending,
+                run_at: at("2024-06-30T12:01:05Z"),
+                attempts: 2,
+            }
+        );
+        assert_eq!(
+            schedule_after_failure(&job(None, 3), now).status,
+            JobStatus::Failed
+        );
+        assert_eq!(
+            schedule_after_failure(&job(Some(3600), 3), now),
+            JobSchedule {
+                status: JobStatus::Pending,
+                run_at: at("2024-06-30T13:00:00Z"),
+                attempts: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,74 @@
+// 対数マーケットスコアリングルール (LMSR) による株の価格付け
+// 候補ごとの発行済み株数 q と流動性 b から、コスト関数 C(q) = b * ln(Σ exp(q_i / b)) を計算する
+// 株を売買するときの価格は売買前後のコスト関数の差で決まり、ハウスの損失は最大でも b * ln(候補の数) に収まる
+
+// 流動性が指定されなかった場合の値。大きいほど売買による価格の変化が緩やかになる
+pub const DEFAULT_LIQUIDITY: i32 = 100;
+// 1 回の売買で指定できる株数の上限
+pub const MAX_TRADE_SHARES: i32 = 1_000_000;
+
+fn cost(quantities: &[i32], liquidity: i32) -> f64 {
+    let b = liquidity as f64;
+    // exp が溢れないように最大値を括り出して計算する
+    let max = quantities.iter().copied().max().unwrap_or_default() as f64 / b;
+    let sum = quantities
+        .iter()
+        .map(|&q| (q as f64 / b - max).exp())
+        .sum::<f64>();
+    b * (max + sum.ln())
+}
+
+// 候補ごとの現在の価格。勝った候補の株は 1pt になるので、そのまま候補が勝つ確率とみなせる
+pub fn prices(quantities: &[i32], liquidity: i32) -> Vec<f64> {
+    let b = liquidity as f64;
+    let max = quantities.iter().copied().max().unwrap_or_default() as f64 / b;
+    let weights = quantities
+        .iter()
+        .map(|&q| (q as f64 / b - max).exp())
+        .collect::<Vec<_>>();
+    let sum = weights.iter().sum::<f64>();
+    weights.into_iter().map(|w| w / sum).collect()
+}
+
+// index の候補の株を shares 株買うのに必要なポイント。端数はハウスに有利になるように切り上げる
+// 発行済み株数が i32 に収まらなくなる場合は None を返す
+pub fn buy_cost(quantities: &[i32], liquidity: i32, index: usize, shares: i32) -> Option<i32> {
+    let mut after = quantities.to_vec();
+    after[index] = after[index].checked_add(shares)?;
+    Some((cost(&after, liquidity) - cost(quantities, liquidity)).ceil() as i32)
+}
+
+// index の候補の株を shares 株売って受け取るポイント。端数はハウスに有利になるように切り捨てる
+// 発行済み株数が i32 に収まらなくなる場合は None を返す
+pub fn sell_proceeds(quantities: &[i32], liquidity: i32, index: usize, shares: i32) -> Option<i32> {
+    let mut after = quantities.to_vec();
+    after[index] = after[index].checked_sub(shares)?;
+    Some((cost(quantities, liquidity) - cost(&after, liquidity)).floor() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lmsr_prices_and_trades() {
+        // 株が発行されていない場合は全ての候補が同じ価格になる
+        let prices = prices(&[0, 0], 100);
+        assert!((prices[0] - 0.5).abs() < 1e-9 && (prices[1] - 0.5).abs() < 1e-9);
+
+        // 買うと価格が上がり、b * ln(2) ≒ 69.3pt を超えて損失が出ることはない
+        let quantities = [0, 0];
+        assert_eq!(buy_cost(&quantities, 100, 0, 10), Some(6));
+        assert_eq!(buy_cost(&quantities, 100, 0, 1000), Some(931));
+        assert!(super::prices(&[10, 0], 100)[0] > 0.5);
+
+        // 買ってすぐ売っても、丸めの分だけ受け取るポイントは少なくなる
+        let bought = buy_cost(&quantities, 100, 1, 50).unwrap();
+        let sold = sell_proceeds(&[0, 50], 100, 1, 50).unwrap();
+        assert!(sold <= bought && bought - sold <= 1);
+
+        // 発行済み株数が溢れる売買は受け付けない
+        assert_eq!(buy_cost(&[i32::MAX - 5, 0], 100, 0, 10), None);
+        assert_eq!(sell_proceeds(&[i32::MIN + 5, 0], 100, 0, 10), None);
+    }
+}
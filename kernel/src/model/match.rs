@@ -1,31 +1,236 @@
 use derive_new::new;
+use thiserror::Error;
 
-use super::{candidate::Candidate, channel::Channel, message::Message, DateTimeUtc, Id};
+use super::{
+    bet::Refund, candidate::Candidate, channel::Channel, message::Message, parlay::ParlaySlip,
+    DateTimeUtc, Id,
+};
 
-#[derive(new, Debug)]
+#[derive(Debug)]
 pub struct Match {
     pub id: Id<Match>,
+    // channel ごとに作成した順に 1 から振られる番号。コマンドでは `#3` のように指定する
+    pub number: i32,
     pub title: String,
     pub channel_id: Id<Channel>,
     pub message_id: Option<Id<Message>>,
     pub created_at: DateTimeUtc,
+    // この時刻を過ぎると bet を自動で締め切る
+    pub close_at: Option<DateTimeUtc>,
     pub closed_at: Option<DateTimeUtc>,
     pub winner_candidate_id: Option<Id<Candidate>>,
+    pub cancelled_at: Option<DateTimeUtc>,
+    pub voided_at: Option<DateTimeUtc>,
+    pub status: MatchStatus,
+    pub market_type: MarketType,
+    // over/under の賭けの基準値を 100 倍した整数。24.5 なら 2450
+    pub over_under_line: Option<i32>,
+    // over/under の賭けで記録された結果の値を 100 倍した整数
+    pub result_value: Option<i32>,
+    // LMSR の賭けの流動性
+    pub liquidity: Option<i32>,
+    // 締め切り時に bet したユーザーがこの人数に満たなければ無効にする
+    pub min_bettors: Option<i32>,
+    // 締め切り時に bet された候補がこの数に満たなければ無効にする
+    pub min_candidates: Option<i32>,
 }
 
-#[derive(new, Debug)]
+// over/under の賭けで自動的に作成される候補の名前
+pub const OVER_CANDIDATE_NAME: &str = "Over";
+pub const UNDER_CANDIDATE_NAME: &str = "Under";
+
+// 数値を予想する賭けで、全ての予想の bet が紐づく候補の名前
+pub const GUESS_CANDIDATE_NAME: &str = "予想";
+
+// 結果の値から over/under の勝者を決める。基準値と等しい場合は引き分け (push) として勝者なしを返す
+pub fn over_under_winners(line: i32, value: i32) -> Vec<String> {
+    match value.cmp(&line) {
+        std::cmp::Ordering::Greater => vec![OVER_CANDIDATE_NAME.to_string()],
+        std::cmp::Ordering::Less => vec![UNDER_CANDIDATE_NAME.to_string()],
+        std::cmp::Ordering::Equal => Vec::new(),
+    }
+}
+
+// 締め切りの時刻は JST で指定する
+pub const DEADLINE_UTC_OFFSET_SECS: i32 = 9 * 60 * 60;
+// `--in` で指定できる締め切りまでの最大の時間 (分)
+pub const MAX_DEADLINE_MINUTES: i64 = 7 * 24 * 60;
+
+// `--until 19:55` は JST の時刻として、`--in 1h30m` は now からの時間として締め切りを求める
+// 時刻が now 以前の場合は翌日のその時刻とする
+pub fn parse_deadline(flag: &str, value: &str, now: DateTimeUtc) -> Option<DateTimeUtc> {
+    match flag {
+        "--until" => {
+            let time = chrono::NaiveTime::parse_from_str(value, "%H:%M").ok()?;
+            let offset = chrono::FixedOffset::east_opt(DEADLINE_UTC_OFFSET_SECS)?;
+            let deadline = now
+                .with_timezone(&offset)
+                .date_naive()
+                .and_time(time)
+                .and_local_timezone(offset)
+                .single()?
+                .with_timezone(&chrono::Utc);
+            if deadline <= now {
+                Some(deadline + chrono::Duration::days(1))
+            } else {
+                Some(deadline)
+            }
+        }
+        "--in" => {
+            let mut minutes = 0_i64;
+            let mut digits = String::new();
+            for c in value.chars() {
+                if c.is_ascii_digit() {
+                    digits.push(c);
+                    continue;
+                }
+                let n = digits.parse::<i64>().ok()?;
+                digits.clear();
+                let n = match c {
+                    'h' => n.checked_mul(60)?,
+                    'm' => n,
+                    _ => return None,
+                };
+                minutes = minutes.checked_add(n)?;
+            }
+            if !digits.is_empty() || minutes <= 0 || minutes > MAX_DEADLINE_MINUTES {
+                return None;
+            }
+            Some(now + chrono::Duration::minutes(minutes))
+        }
+        _ => None,
+    }
+}
+
+// 締め切りの何分前にリマインドを送るか
+pub const REMIND_MINUTES_BEFORE_CLOSE: i64 = 10;
+
+// 締め切りのリマインドを送る時刻。既に過ぎている場合は送らない
+pub fn remind_at(close_at: DateTimeUtc, now: DateTimeUtc) -> Option<DateTimeUtc> {
+    let remind_at = close_at - chrono::Duration::minutes(REMIND_MINUTES_BEFORE_CLOSE);
+    (remind_at > now).then_some(remind_at)
+}
+
+// 終了しないまま放置された match を無効にする時刻。締め切り時刻 (無ければ作成時刻) から ttl_hours 後とする
+// ttl_hours が 0 以下の場合は無効にしない
+pub fn expires_at(
+    created_at: DateTimeUtc,
+    close_at: Option<DateTimeUtc>,
+    ttl_hours: i32,
+) -> Option<DateTimeUtc> {
+    if ttl_hours <= 0 {
+        return None;
+    }
+    let since = close_at.map_or(created_at, |close_at| close_at.max(created_at));
+    Some(since + chrono::Duration::hours(ttl_hours as i64))
+}
+
+// 締め切り時に参加の条件を満たしているか。stakes は bet したユーザーの ID と候補の ID の組で、同じ組が複数あってもよい
+pub fn meets_participation(
+    min_bettors: Option<i32>,
+    min_candidates: Option<i32>,
+    stakes: &[(&str, &str)],
+) -> bool {
+    let mut bettors = stakes.iter().map(|(user, _)| *user).collect::<Vec<_>>();
+    bettors.sort();
+    bettors.dedup();
+    let mut candidates = stakes
+        .iter()
+        .map(|(_, candidate)| *candidate)
+        .collect::<Vec<_>>();
+    candidates.sort();
+    candidates.dedup();
+    bettors.len() as i64 >= min_bettors.unwrap_or_default() as i64
+        && candidates.len() as i64 >= min_candidates.unwrap_or_default() as i64
+}
+
+// 払い戻しの決め方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketType {
+    // 賭けられたポイントの合計を勝者に bet した人で分ける
+    Parimutuel,
+    // match の開始時に決めた倍率で払い戻し、差額はハウスが負担する
+    FixedOdds,
+    // 候補ではなく数値を予想し、結果に最も近い予想をした人で賭けられたポイントの合計を分ける
+    ClosestGuess,
+    // 候補ごとの株を LMSR で決まる価格で売買し、勝った候補の株 1 株につき 1pt を払い戻す
+    Lmsr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchStatus {
+    Open,
+    Closed,
+    Settled,
+    Cancelled,
+    Void,
+}
+
+impl MatchStatus {
+    // これ以上状態が変わらない (進行中ではない) かどうか
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            MatchStatus::Settled | MatchStatus::Cancelled | MatchStatus::Void
+        )
+    }
+}
+
+// match に対する操作。状態を変えないものも含めて transition で可否を判定する
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchEvent {
+    Bet,
+    Close,
+    Settle,
+    Cancel,
+    Void,
+    // 締め切り前に bet を取り下げてポイントを受け取る
+    CashOut,
+    // 終了しないまま放置された match を無効にする
+    Expire,
+}
+
+#[derive(Error, Debug)]
+#[error("{event:?} is not allowed while the match is {status:?}")]
+pub struct TransitionError {
+    pub status: MatchStatus,
+    pub event: MatchEvent,
+}
+
+pub fn transition(status: MatchStatus, event: MatchEvent) -> Result<MatchStatus, TransitionError> {
+    match (status, event) {
+        (MatchStatus::Open, MatchEvent::Bet) => Ok(MatchStatus::Open),
+        (MatchStatus::Open, MatchEvent::Close) => Ok(MatchStatus::Closed),
+        (MatchStatus::Open | MatchStatus::Closed, MatchEvent::Settle) => Ok(MatchStatus::Settled),
+        (MatchStatus::Open | MatchStatus::Closed, MatchEvent::Cancel) => Ok(MatchStatus::Cancelled),
+        // 結果が出なかった場合などに締め切り後の match を無効にする
+        (MatchStatus::Closed, MatchEvent::Void) => Ok(MatchStatus::Void),
+        // 締め切り後は結果がほぼ分かっているので、cash out は受付中に限る
+        (MatchStatus::Open, MatchEvent::CashOut) => Ok(MatchStatus::Open),
+        (MatchStatus::Open | MatchStatus::Closed, MatchEvent::Expire) => Ok(MatchStatus::Void),
+        _ => Err(TransitionError { status, event }),
+    }
+}
+
+#[derive(Debug)]
 pub struct NewMatch {
     pub id: Id<Match>,
     pub title: String,
     pub channel_id: Id<Channel>,
     pub created_at: DateTimeUtc,
+    pub close_at: Option<DateTimeUtc>,
+    pub market_type: MarketType,
+    pub over_under_line: Option<i32>,
+    pub liquidity: Option<i32>,
+    pub min_bettors: Option<i32>,
+    pub min_candidates: Option<i32>,
 }
 
 #[derive(new, Debug)]
 pub struct UpdateMatchForLatest {
     pub channel_id: Id<Channel>,
+    pub match_number: Option<i32>,
     pub closed_at: Option<Option<DateTimeUtc>>,
-    pub winner_candidate_name: Option<Option<String>>,
 }
 
 #[derive(new, Debug)]
@@ -33,3 +238,191 @@ pub struct UpdateMatch {
     pub id: Id<Match>,
     pub message_id: Option<Option<Id<Message>>>,
 }
+
+#[derive(new, Debug)]
+pub struct CancelMatchForLatest {
+    pub channel_id: Id<Channel>,
+    pub match_number: Option<i32>,
+    pub cancelled_at: DateTimeUtc,
+}
+
+#[derive(new, Debug)]
+pub struct VoidMatchForLatest {
+    pub channel_id: Id<Channel>,
+    pub match_number: Option<i32>,
+    pub voided_at: DateTimeUtc,
+}
+
+// キャンセルや無効によって全ての bet が返却された match
+#[derive(new, Debug)]
+pub struct RefundedMatch {
+    pub r#match: Match,
+    pub refunds: Vec<Refund>,
+    // leg が無効になったことで全ての leg の結果が出たパーレー
+    pub parlays: Vec<ParlaySlip>,
+}
+
+// 締め切りや精算の際に、start で指定された参加の条件を確かめた結果
+#[derive(Debug)]
+pub enum Participation<T> {
+    Met(T),
+    // 条件を満たさなかったため、締め切りや精算の代わりに無効にしてポイントを返却した
+    Voided(Box<RefundedMatch>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_over_under_winners() {
+        assert_eq!(over_under_winners(2450, 2600), vec!["Over"]);
+        assert_eq!(over_under_winners(2450, 2400), vec!["Under"]);
+        assert!(over_under_winners(2400, 2400).is_empty());
+    }
+
+    #[test]
+    fn test_parse_deadline() {
+        // 2024-06-26 10:00 JST
+        let now = "2024-06-26T01:00:00Z".parse::<DateTimeUtc>().unwrap();
+        let at = |s: &str| Some(s.parse::<DateTimeUtc>().unwrap());
+        assert_eq!(
+            parse_deadline("--until", "19:55", now),
+            at("2024-06-26T10:55:00Z")
+        );
+        // 過ぎた時刻は翌日として扱う
+        assert_eq!(
+            parse_deadline("--until", "09:30", now),
+            at("2024-06-27T00:30:00Z")
+        );
+        assert_eq!(
+            parse_deadline("--in", "30m", now),
+            at("2024-06-26T01:30:00Z")
+        );
+        assert_eq!(
+            parse_deadline("--in", "1h30m", now),
+            at("2024-06-26T02:30:00Z")
+        );
+        assert_eq!(parse_deadline("--until", "25:00", now), None);
+        assert_eq!(parse_deadline("--in", "30", now), None);
+        assert_eq!(parse_deadline("--in", "0m", now), None);
+        assert_eq!(parse_deadline("--in", "8000h", now), None);
+    }
+
+    #[test]
+    fn test_remind_at() {
+        let now = "2024-07-03T01:00:00Z".parse::<DateTimeUtc>().unwrap();
+        let at = |s: &str| s.parse::<DateTimeUtc>().unwrap();
+        assert_eq!(
+            remind_at(at("2024-07-03T02:00:00Z"), now),
+            Some(at("2024-07-03T01:50:00Z"))
+        );
+        // 締め切りまでの時間が短い場合はリマインドしない
+        assert_eq!(remind_at(at("2024-07-03T01:05:00Z"), now), None);
+    }
+
+    #[test]
+    fn test_expires_at() {
+        let at = |s: &str| s.parse::<DateTimeUtc>().unwrap();
+        let created_at = at("2024-07-06T01:00:00Z");
+        assert_eq!(
+            expires_at(created_at, None, 24),
+            Some(at("2024-07-07T01:00:00Z"))
+        );
+        // 締め切り時刻がある場合は締め切りから数える
+        assert_eq!(
+            expires_at(created_at, Some(at("2024-07-08T01:00:00Z")), 24),
+            Some(at("2024-07-09T01:00:00Z"))
+        );
+        assert_eq!(expires_at(created_at, None, 0), None);
+    }
+
+    #[test]
+    fn test_meets_participation() {
+        let stakes = [("a", "A"), ("a", "B"), ("a", "A")];
+        assert!(meets_participation(None, None, &stakes));
+        assert!(meets_participation(None, Some(2), &stakes));
+        // 1 人で複数の候補に bet しても 1 人として数える
+        assert!(!meets_participation(Some(2), None, &stakes));
+        let stakes = [("a", "A"), ("b", "A")];
+        assert!(meets_participation(Some(2), None, &stakes));
+        assert!(!meets_participation(Some(2), Some(2), &stakes));
+        assert!(!meets_participation(Some(1), None, &[]));
+    }
+
+    #[test]
+    fn test_transition_from_open() {
+        assert_eq!(
+            transition(MatchStatus::Open, MatchEvent::Bet).unwrap(),
+            MatchStatus::Open
+        );
+        assert_eq!(
+            transition(MatchStatus::Open, MatchEvent::Close).unwrap(),
+            MatchStatus::Closed
+        );
+        assert_eq!(
+            transition(MatchStatus::Open, MatchEvent::Settle).unwrap(),
+            MatchStatus::Settled
+        );
+        assert_eq!(
+            transition(MatchStatus::Open, MatchEvent::Cancel).unwrap(),
+            MatchStatus::Cancelled
+        );
+        assert!(transition(MatchStatus::Open, MatchEvent::Void).is_err());
+        assert_eq!(
+            transition(MatchStatus::Open, MatchEvent::CashOut).unwrap(),
+            MatchStatus::Open
+        );
+        assert_eq!(
+            transition(MatchStatus::Open, MatchEvent::Expire).unwrap(),
+            MatchStatus::Void
+        );
+    }
+
+    #[test]
+    fn test_transition_from_closed() {
+        assert!(transition(MatchStatus::Closed, MatchEvent::Bet).is_err());
+        assert!(transition(MatchStatus::Closed, MatchEvent::Close).is_err());
+        assert_eq!(
+            transition(MatchStatus::Closed, MatchEvent::Settle).unwrap(),
+            MatchStatus::Settled
+        );
+        assert_eq!(
+            transition(MatchStatus::Closed, MatchEvent::Cancel).unwrap(),
+            MatchStatus::Cancelled
+        );
+        assert_eq!(
+            transition(MatchStatus::Closed, MatchEvent::Void).unwrap(),
+            MatchStatus::Void
+        );
+        assert!(transition(MatchStatus::Closed, MatchEvent::CashOut).is_err());
+        assert_eq!(
+            transition(MatchStatus::Closed, MatchEvent::Expire).unwrap(),
+            MatchStatus::Void
+        );
+    }
+
+    #[test]
+    fn test_transition_from_finished() {
+        for status in [
+            MatchStatus::Settled,
+            MatchStatus::Cancelled,
+            MatchStatus::Void,
+        ] {
+            assert!(status.is_finished());
+            for event in [
+                MatchEvent::Bet,
+                MatchEvent::Close,
+                MatchEvent::Settle,
+                MatchEvent::Cancel,
+                MatchEvent::Void,
+                MatchEvent::CashOut,
+                MatchEvent::Expire,
+            ] {
+                let err = transition(status, event).unwrap_err();
+                assert_eq!(err.status, status);
+                assert_eq!(err.event, event);
+            }
+        }
+    }
+}
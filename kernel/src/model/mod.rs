@@ -4,9 +4,15 @@ use uuid::Uuid;
 
 pub mod bet;
 pub mod candidate;
+pub mod challenge;
 pub mod channel;
+pub mod job;
+pub mod lmsr;
 pub mod r#match;
 pub mod message;
+pub mod parlay;
+pub mod position;
+pub mod settlement;
 pub mod stamp;
 pub mod statistic;
 pub mod user;
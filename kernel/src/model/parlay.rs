@@ -0,0 +1,244 @@
+use derive_new::new;
+
+use super::{
+    candidate::Candidate, channel::Channel, r#match::Match, settlement::Distribution,
+    statistic::Statistic, user::User, DateTimeUtc, Id,
+};
+
+// 1 つのパーレーに含められる leg の数
+pub const MIN_PARLAY_LEGS: usize = 2;
+pub const MAX_PARLAY_LEGS: usize = 8;
+
+// 複数の match の候補を組み合わせ、全て的中した場合に倍率の積で払い戻す賭け
+#[derive(Debug)]
+pub struct Parlay {
+    pub id: Id<Parlay>,
+    // パーレーを作成した channel。ポイントの差し引きと払い戻しはこの channel で行う
+    pub channel_id: Id<Channel>,
+    pub user_id: Id<User>,
+    pub amount: i32,
+    pub status: ParlayStatus,
+    pub payout: Option<i32>,
+    pub created_at: DateTimeUtc,
+    pub settled_at: Option<DateTimeUtc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParlayStatus {
+    // まだ結果の出ていない leg がある
+    Open,
+    Won,
+    Lost,
+    // 全ての leg が無効になったので賭けたポイントを返却した
+    Refunded,
+}
+
+// パーレーに含まれる 1 つの match の予想
+#[derive(Debug)]
+pub struct ParlayLeg {
+    pub id: Id<ParlayLeg>,
+    pub parlay_id: Id<Parlay>,
+    pub match_id: Id<Match>,
+    pub candidate_id: Id<Candidate>,
+    pub result: LegResult,
+    // 的中した場合の倍率を 100 倍した整数
+    pub odds: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegResult {
+    Pending,
+    Won,
+    Lost,
+    // match がキャンセルや無効になったか、倍率が定まらなかった
+    Void,
+}
+
+// 精算した match の勝者から leg の結果と倍率を決める
+// 勝者が空の場合は引き分け (push) として無効にする
+// 倍率は選んだ候補に 1 着を当てる bet をした場合に実際に払い戻された割合とする
+// 勝った候補に誰も bet しておらず払い戻しの割合が定まらない場合は、固定の倍率か 1 倍とする
+pub fn resolve_leg(
+    candidate_id: &Id<Candidate>,
+    winner_ids: &[Id<Candidate>],
+    statistics: &[Statistic],
+    distribution: &Distribution,
+) -> (LegResult, Option<i32>) {
+    if winner_ids.is_empty() {
+        return (LegResult::Void, None);
+    }
+    if !winner_ids.iter().any(|id| id.value == candidate_id.value) {
+        return (LegResult::Lost, Some(0));
+    }
+    let statistic = statistics
+        .iter()
+        .find(|s| s.candidate.id.value == candidate_id.value);
+    let Some(statistic) = statistic.filter(|s| s.amount > 0) else {
+        let odds = statistic.and_then(|s| s.candidate.odds).unwrap_or(100);
+        return (LegResult::Won, Some(odds));
+    };
+    let paid = distribution
+        .payouts
+        .iter()
+        .filter(|p| statistic.bets.iter().any(|b| b.id.value == p.bet_id.value))
+        .map(|p| p.amount as i64)
+        .sum::<i64>();
+    let odds = (paid * 100 / statistic.amount as i64) as i32;
+    if odds == 0 {
+        (LegResult::Lost, Some(0))
+    } else {
+        (LegResult::Won, Some(odds))
+    }
+}
+
+// 全ての leg の結果が出ていれば、パーレーの結果と払い戻すポイントを返す
+// 無効になった leg は除いて計算し、全て無効の場合は賭けたポイントを返却する。端数は leg ごとに切り捨てる
+pub fn parlay_outcome(amount: i32, legs: &[ParlayLeg]) -> Option<(ParlayStatus, i32)> {
+    if legs.iter().any(|l| l.result == LegResult::Pending) {
+        return None;
+    }
+    if legs.iter().any(|l| l.result == LegResult::Lost) {
+        return Some((ParlayStatus::Lost, 0));
+    }
+    let won = legs
+        .iter()
+        .filter(|l| l.result == LegResult::Won)
+        .collect::<Vec<_>>();
+    if won.is_empty() {
+        return Some((ParlayStatus::Refunded, amount));
+    }
+    let payout = won.iter().fold(amount as i128, |payout, leg| {
+        (payout * leg.odds.unwrap_or(100) as i128 / 100).min(i32::MAX as i128)
+    });
+    Some((ParlayStatus::Won, payout as i32))
+}
+
+#[derive(new, Debug)]
+pub struct NewParlayLeg {
+    // この channel の match の候補を選ぶ
+    pub channel_id: Id<Channel>,
+    pub match_number: Option<i32>,
+    pub candidate_name: String,
+}
+
+#[derive(new, Debug)]
+pub struct NewParlay {
+    pub id: Id<Parlay>,
+    pub channel_id: Id<Channel>,
+    pub traq_id: String,
+    pub amount: i32,
+    pub legs: Vec<NewParlayLeg>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(new, Debug)]
+pub struct ParlaySlip {
+    pub parlay: Parlay,
+    pub legs: Vec<ParlayLeg>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        settlement::{
+            tests::{bet, candidate},
+            Payout,
+        },
+        statistic::new_statistics,
+    };
+
+    fn leg(result: LegResult, odds: Option<i32>) -> ParlayLeg {
+        ParlayLeg {
+            id: Id::gen(),
+            parlay_id: Id::new("parlay".to_string()),
+            match_id: Id::gen(),
+            candidate_id: Id::new("A".to_string()),
+            result,
+            odds,
+        }
+    }
+
+    #[test]
+    fn test_resolve_leg_uses_actual_payout() {
+        let statistics = new_statistics(
+            vec![bet(0, "A", 100), bet(1, "A", 300), bet(2, "B", 600)],
+            vec![candidate("A"), candidate("B"), candidate("C")],
+        );
+        let distribution = Distribution {
+            payouts: vec![
+                Payout::new(
+                    Id::new("bet0".to_string()),
+                    Id::new("user0".to_string()),
+                    100,
+                    250,
+                ),
+                Payout::new(
+                    Id::new("bet1".to_string()),
+                    Id::new("user1".to_string()),
+                    300,
+                    750,
+                ),
+            ],
+            house: 0,
+            commission: 0,
+            jackpot: 0,
+            rollover: 0,
+            no_winner_policy: None,
+        };
+        let id = |name: &str| Id::new(name.to_string());
+        let winners = [id("A")];
+        assert_eq!(
+            resolve_leg(&id("A"), &winners, &statistics, &distribution),
+            (LegResult::Won, Some(250))
+        );
+        assert_eq!(
+            resolve_leg(&id("B"), &winners, &statistics, &distribution),
+            (LegResult::Lost, Some(0))
+        );
+        // 誰も bet していない候補でも、負けていれば外れになる
+        assert_eq!(
+            resolve_leg(&id("C"), &winners, &statistics, &distribution),
+            (LegResult::Lost, Some(0))
+        );
+        // 誰も bet していない候補が勝った場合は 1 倍とする
+        assert_eq!(
+            resolve_leg(&id("C"), &[id("C")], &statistics, &distribution),
+            (LegResult::Won, Some(100))
+        );
+        // 引き分け (push) の場合は無効にする
+        assert_eq!(
+            resolve_leg(&id("A"), &[], &statistics, &distribution),
+            (LegResult::Void, None)
+        );
+    }
+
+    #[test]
+    fn test_parlay_outcome() {
+        let won = || leg(LegResult::Won, Some(250));
+        assert_eq!(
+            parlay_outcome(100, &[won(), leg(LegResult::Pending, None)]),
+            None
+        );
+        assert_eq!(
+            parlay_outcome(100, &[won(), leg(LegResult::Won, Some(180))]),
+            Some((ParlayStatus::Won, 450))
+        );
+        // 無効になった leg は除いて計算する
+        assert_eq!(
+            parlay_outcome(100, &[won(), leg(LegResult::Void, None)]),
+            Some((ParlayStatus::Won, 250))
+        );
+        assert_eq!(
+            parlay_outcome(
+                100,
+                &[leg(LegResult::Void, None), leg(LegResult::Void, None)]
+            ),
+            Some((ParlayStatus::Refunded, 100))
+        );
+        assert_eq!(
+            parlay_outcome(100, &[won(), leg(LegResult::Lost, Some(0))]),
+            Some((ParlayStatus::Lost, 0))
+        );
+    }
+}
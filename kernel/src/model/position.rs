@@ -0,0 +1,36 @@
+use derive_new::new;
+
+use super::{candidate::Candidate, r#match::Match, user::User, DateTimeUtc, Id};
+
+// LMSR の賭けでユーザーが候補ごとに保有している株
+#[derive(Debug)]
+pub struct Position {
+    pub id: Id<Position>,
+    pub match_id: Id<Match>,
+    pub user_id: Id<User>,
+    pub candidate_id: Id<Candidate>,
+    pub shares: i32,
+    // 買うのに支払ったポイントから売って受け取ったポイントを引いたもの。利益が出ている場合は負になる
+    pub cost: i32,
+    pub created_at: DateTimeUtc,
+}
+
+// 最新の match の候補の株を売買する。shares が正なら購入、負なら売却を表す
+#[derive(new, Debug)]
+pub struct TradeForLatestMatch {
+    pub id: Id<Position>,
+    pub traq_id: String,
+    pub channel_id: String,
+    pub match_number: Option<i32>,
+    pub candidate_name: String,
+    pub shares: i32,
+    pub traded_at: DateTimeUtc,
+}
+
+// 売買の結果。points は購入なら支払ったポイント、売却なら受け取ったポイント
+#[derive(new, Debug)]
+pub struct Trade {
+    pub position: Position,
+    pub points: i32,
+    pub balance: i32,
+}
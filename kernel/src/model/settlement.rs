@@ -0,0 +1,1036 @@
+use derive_new::new;
+
+use super::{
+    bet::{Bet, BetType},
+    candidate::Candidate,
+    channel::Channel,
+    parlay::ParlaySlip,
+    position::Position,
+    r#match::{MarketType, Match},
+    statistic::Statistic,
+    user::User,
+    DateTimeUtc, Id,
+};
+
+#[derive(new, Debug)]
+pub struct Settlement {
+    pub id: Id<Settlement>,
+    pub match_id: Id<Match>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Debug)]
+pub struct SettleMatchForLatest {
+    pub id: Id<Settlement>,
+    pub channel_id: Id<Channel>,
+    pub match_number: Option<i32>,
+    // 複数指定された場合はデッドヒートとして扱う
+    pub winner_candidate_names: Vec<String>,
+    // 1 着から順に並べた着順。着順を当てる bet の精算に使う
+    pub placing_candidate_names: Vec<String>,
+    // over/under の賭けで記録する結果の値
+    pub result_value: Option<i32>,
+    // ハンディキャップのある賭けで記録する候補ごとの得点
+    pub scores: Vec<(String, i32)>,
+    pub settled_at: DateTimeUtc,
+}
+
+#[derive(new, Debug)]
+pub struct SettledMatch {
+    pub r#match: Match,
+    pub settlement: Settlement,
+    pub distribution: Distribution,
+    // この match の精算で全ての leg の結果が出たパーレー
+    pub parlays: Vec<ParlaySlip>,
+}
+
+// bet ごとの払い戻し。stake は bet したタイミングで差し引かれているので amount だけが残高に加算される
+#[derive(new, Debug)]
+pub struct Payout {
+    // LMSR の賭けでは position の ID
+    pub bet_id: Id<Bet>,
+    pub user_id: Id<User>,
+    pub stake: i32,
+    pub amount: i32,
+}
+
+// 払い戻しを整数に丸めたときに出る端数の扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingPolicy {
+    // 切り捨てた端数が大きい bet から順に 1pt ずつ配る
+    LargestRemainder,
+    // 端数はすべてハウスに入る
+    House,
+    // 端数はすべて最も早く bet したユーザーに入る
+    EarliestBettor,
+}
+
+// 勝者に誰も bet していなかった場合の扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoWinnerPolicy {
+    // bet されたポイントを全て返却する
+    Refund,
+    // プールをチャンネルの次の match に持ち越す
+    Jackpot,
+    // プールはすべてハウスに入る
+    House,
+}
+
+// 払い戻しの一覧と、誰にも払い戻されなかったポイントの行き先
+// payouts.sum(amount) + house + rollover は常に bet の合計 + jackpot と一致する
+#[derive(new, Debug)]
+pub struct Distribution {
+    pub payouts: Vec<Payout>,
+    // 丸めや勝者不在、手数料でハウスに入ったポイント
+    pub house: i32,
+    // house のうち、手数料としてプールから差し引いたポイント
+    pub commission: i32,
+    // 前の match から持ち越されてプールに上乗せされたポイント
+    pub jackpot: i32,
+    // 次の match に持ち越すポイント
+    pub rollover: i32,
+    // 勝者に誰も bet していなかった場合に適用した policy
+    pub no_winner_policy: Option<NoWinnerPolicy>,
+}
+
+impl Distribution {
+    // 別のプールの払い戻しをまとめる。持ち越しはそれぞれのプールで扱われないものとする
+    pub fn merge(mut self, other: Distribution) -> Distribution {
+        self.payouts.extend(other.payouts);
+        self.house += other.house;
+        self.commission += other.commission;
+        self
+    }
+}
+
+// 払い戻しを numerator / denominator の有理数で表したもの
+struct Share<'a> {
+    bet: &'a Bet,
+    numerator: i128,
+    denominator: i128,
+}
+
+// パリミュチュエル方式の払い戻しを計算する
+// 勝者が複数いる場合 (デッドヒート) は、負けた候補に bet されたポイントを勝者の数で等分し、
+// それぞれの勝者の中で bet したポイントの比率で分配する。丸めで出た端数は channel の rounding_policy に従って配分する
+// channel に手数料が設定されている場合は、bet されたポイントの合計から手数料を差し引き、残りを同じ比率で分配する
+pub fn new_distribution(
+    statistics: &[Statistic],
+    winner_candidate_ids: &[Id<Candidate>],
+    market_type: MarketType,
+    channel: &Channel,
+) -> Distribution {
+    if market_type == MarketType::FixedOdds {
+        return new_fixed_odds_distribution(statistics, winner_candidate_ids, channel);
+    }
+
+    let is_winner = |s: &&Statistic| {
+        winner_candidate_ids
+            .iter()
+            .any(|id| id.value == s.candidate.id.value)
+    };
+    let pool = statistics.iter().map(|s| s.amount as i128).sum::<i128>() + channel.jackpot as i128;
+    // 誰も bet していない勝者の取り分は無いものとして、bet がある勝者だけで分ける
+    let winners = statistics
+        .iter()
+        .filter(is_winner)
+        .filter(|s| s.amount > 0)
+        .collect::<Vec<_>>();
+    if winners.is_empty() {
+        return new_no_winner_distribution(statistics, channel);
+    }
+    let dead_heat = winners.len() as i128;
+    let losing_pool = pool - winners.iter().map(|s| s.amount as i128).sum::<i128>();
+    // 持ち越されたポイントには手数料をかけない
+    let commission = statistics.iter().map(|s| s.amount as i128).sum::<i128>()
+        * channel.commission_percent as i128
+        / 100;
+    let net_pool = pool - commission;
+
+    // 候補 c に a_i pt bet した場合の払い戻しは (a_i + a_i * (losing_pool / k) / pool_c) * net_pool / pool
+    let shares = winners
+        .iter()
+        .flat_map(|s| {
+            let winning_pool = s.amount as i128;
+            s.bets.iter().map(move |bet| Share {
+                bet,
+                numerator: bet.amount as i128 * (dead_heat * winning_pool + losing_pool) * net_pool,
+                denominator: dead_heat * winning_pool * pool,
+            })
+        })
+        .collect::<Vec<_>>();
+    let (amounts, house) = distribute(net_pool, &shares, channel.rounding_policy);
+
+    let mut payouts = shares
+        .iter()
+        .zip(amounts)
+        .map(|(share, amount)| new_payout(share.bet, amount as i32))
+        .collect::<Vec<_>>();
+    payouts.extend(
+        statistics
+            .iter()
+            .flat_map(|s| s.bets.iter())
+            .filter(|bet| {
+                !shares
+                    .iter()
+                    .any(|share| share.bet.id.value == bet.id.value)
+            })
+            .map(|bet| new_payout(bet, 0)),
+    );
+
+    Distribution::new(
+        payouts,
+        (house + commission) as i32,
+        commission as i32,
+        channel.jackpot,
+        0,
+        None,
+    )
+}
+
+// 固定オッズの場合は bet した時点の倍率で払い戻し、賭けられたポイントとの差額はハウスが負担する
+// 勝者が複数いる場合 (デッドヒート) は賭けたポイントを勝者の数で割ってから倍率をかける
+// 1pt 未満の端数は切り捨ててハウスに入れる。持ち越されたポイントは使わずにそのまま次の match に持ち越す
+// 倍率にハウスの取り分が含まれているので、手数料は差し引かない
+fn new_fixed_odds_distribution(
+    statistics: &[Statistic],
+    winner_candidate_ids: &[Id<Candidate>],
+    channel: &Channel,
+) -> Distribution {
+    let dead_heat = statistics
+        .iter()
+        .filter(|s| {
+            winner_candidate_ids
+                .iter()
+                .any(|id| id.value == s.candidate.id.value)
+        })
+        .count() as i64;
+    let bets = statistics.iter().flat_map(|s| s.bets.iter());
+    let pool = bets.clone().map(|bet| bet.amount).sum::<i32>();
+
+    let payouts = bets
+        .map(|bet| {
+            let is_winner = winner_candidate_ids
+                .iter()
+                .any(|id| id.value == bet.candidate_id.value);
+            if !is_winner {
+                return new_payout(bet, 0);
+            }
+            let odds = bet.odds.unwrap_or(100) as i64;
+            new_payout(bet, (bet.amount as i64 * odds / (100 * dead_heat)) as i32)
+        })
+        .collect::<Vec<_>>();
+    let house = pool - payouts.iter().map(|p| p.amount).sum::<i32>();
+
+    Distribution::new(payouts, house, 0, 0, channel.jackpot, None)
+}
+
+// 着順を当てる bet を、bet_type ごとに別のプールとして精算する
+// 記録された着順と bet した着順が一致した bet だけで、プールを bet したポイントの比率で分ける
+// 一致した bet が無い場合や着順が記録されていない場合は、bet されたポイントを全て返却する
+pub fn new_ordered_distribution(
+    bets: &[Bet],
+    bet_type: BetType,
+    placing: &[Id<Candidate>],
+    channel: &Channel,
+) -> Distribution {
+    let bets = bets
+        .iter()
+        .filter(|bet| bet.bet_type == bet_type)
+        .collect::<Vec<_>>();
+    let is_hit = |bet: &Bet| {
+        placing.len() >= bet_type.placings()
+            && bet
+                .order()
+                .iter()
+                .zip(placing)
+                .all(|(a, b)| a.value == b.value)
+    };
+    let pool = bets.iter().map(|bet| bet.amount as i128).sum::<i128>();
+    let winning_pool = bets
+        .iter()
+        .filter(|bet| is_hit(bet))
+        .map(|bet| bet.amount as i128)
+        .sum::<i128>();
+    if winning_pool == 0 {
+        let payouts = bets.iter().map(|bet| new_payout(bet, bet.amount)).collect();
+        return Distribution::new(payouts, 0, 0, 0, 0, None);
+    }
+
+    let commission = pool * channel.commission_percent as i128 / 100;
+    let net_pool = pool - commission;
+    let shares = bets
+        .iter()
+        .filter(|bet| is_hit(bet))
+        .map(|bet| Share {
+            bet,
+            numerator: bet.amount as i128 * net_pool,
+            denominator: winning_pool,
+        })
+        .collect::<Vec<_>>();
+    let (amounts, house) = distribute(net_pool, &shares, channel.rounding_policy);
+
+    let mut payouts = shares
+        .iter()
+        .zip(amounts)
+        .map(|(share, amount)| new_payout(share.bet, amount as i32))
+        .collect::<Vec<_>>();
+    payouts.extend(
+        bets.iter()
+            .filter(|bet| !is_hit(bet))
+            .map(|bet| new_payout(bet, 0)),
+    );
+
+    Distribution::new(
+        payouts,
+        (house + commission) as i32,
+        commission as i32,
+        0,
+        0,
+        None,
+    )
+}
+
+// 数値を予想する賭けを精算する。結果との差が最も小さい予想をした bet で、プールを bet したポイントの比率で分ける
+// 予想の bet だけの賭けなので、持ち越されたポイントもプールに加える
+pub fn new_closest_guess_distribution(
+    bets: &[Bet],
+    result: i32,
+    channel: &Channel,
+) -> Distribution {
+    let bets = bets
+        .iter()
+        .filter(|bet| bet.bet_type == BetType::Guess)
+        .collect::<Vec<_>>();
+    let distance = |bet: &Bet| (bet.guess.unwrap_or_default() as i64 - result as i64).abs();
+    let Some(closest) = bets.iter().map(|bet| distance(bet)).min() else {
+        return Distribution::new(Vec::new(), 0, 0, channel.jackpot, channel.jackpot, None);
+    };
+    let is_closest = |bet: &Bet| distance(bet) == closest;
+
+    let stakes = bets.iter().map(|bet| bet.amount as i128).sum::<i128>();
+    let pool = stakes + channel.jackpot as i128;
+    let commission = stakes * channel.commission_percent as i128 / 100;
+    let net_pool = pool - commission;
+    let winning_pool = bets
+        .iter()
+        .filter(|bet| is_closest(bet))
+        .map(|bet| bet.amount as i128)
+        .sum::<i128>();
+    let shares = bets
+        .iter()
+        .filter(|bet| is_closest(bet))
+        .map(|bet| Share {
+            bet,
+            numerator: bet.amount as i128 * net_pool,
+            denominator: winning_pool,
+        })
+        .collect::<Vec<_>>();
+    let (amounts, house) = distribute(net_pool, &shares, channel.rounding_policy);
+
+    let mut payouts = shares
+        .iter()
+        .zip(amounts)
+        .map(|(share, amount)| new_payout(share.bet, amount as i32))
+        .collect::<Vec<_>>();
+    payouts.extend(
+        bets.iter()
+            .filter(|bet| !is_closest(bet))
+            .map(|bet| new_payout(bet, 0)),
+    );
+
+    Distribution::new(
+        payouts,
+        (house + commission) as i32,
+        commission as i32,
+        channel.jackpot,
+        0,
+        None,
+    )
+}
+
+// ハンディキャップを加えた得点が最も高い候補を勝者とする
+// 最も高い候補が複数ある場合は引き分け (push) として None を返す
+pub fn handicap_winner(candidates: &[Candidate]) -> Option<&Candidate> {
+    let best = candidates.iter().filter_map(|c| c.adjusted_score()).max()?;
+    let mut winners = candidates
+        .iter()
+        .filter(|c| c.adjusted_score() == Some(best));
+    match (winners.next(), winners.next()) {
+        (Some(winner), None) => Some(winner),
+        _ => None,
+    }
+}
+
+// 引き分け (push) の場合は全ての bet を返却し、持ち越されていたポイントはそのまま次の match に持ち越す
+pub fn new_push_distribution(bets: &[Bet], channel: &Channel) -> Distribution {
+    let payouts = bets.iter().map(|bet| new_payout(bet, bet.amount)).collect();
+    Distribution::new(payouts, 0, 0, channel.jackpot, channel.jackpot, None)
+}
+
+// LMSR の賭けでは勝った候補の株 1 株につき 1pt を払い戻す。デッドヒートの場合は勝者の数で等分し、端数は切り捨てる
+// 売買で集めたポイントと払い戻しの差額はハウスが受け取るか負担し、持ち越されていたポイントはそのまま次の match に持ち越す
+pub fn new_lmsr_distribution(
+    positions: &[Position],
+    winner_ids: &[Id<Candidate>],
+    channel: &Channel,
+) -> Distribution {
+    let winners = winner_ids.len().max(1) as i32;
+    let payouts = positions
+        .iter()
+        .map(|position| {
+            let is_winner = winner_ids
+                .iter()
+                .any(|id| id.value == position.candidate_id.value);
+            Payout::new(
+                Id::new(position.id.value.clone()),
+                Id::new(position.user_id.value.clone()),
+                position.cost,
+                if is_winner {
+                    position.shares / winners
+                } else {
+                    0
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    let collected = positions.iter().map(|p| p.cost).sum::<i32>();
+    let paid = payouts.iter().map(|p| p.amount).sum::<i32>();
+    Distribution::new(
+        payouts,
+        collected - paid,
+        0,
+        channel.jackpot,
+        channel.jackpot,
+        None,
+    )
+}
+
+// 勝者に誰も bet していなかった場合は、プールを channel の no_winner_policy に従って扱う
+// 払い戻しが発生しないので手数料は差し引かない
+fn new_no_winner_distribution(statistics: &[Statistic], channel: &Channel) -> Distribution {
+    let bets = statistics.iter().flat_map(|s| s.bets.iter());
+    let pool = bets.clone().map(|bet| bet.amount).sum::<i32>();
+    let policy = channel.no_winner_policy;
+    let (payouts, house, rollover) = match policy {
+        // 返却する場合、持ち越されていたポイントはさらに次の match に持ち越す
+        NoWinnerPolicy::Refund => (
+            bets.map(|bet| new_payout(bet, bet.amount)).collect(),
+            0,
+            channel.jackpot,
+        ),
+        NoWinnerPolicy::Jackpot => (
+            bets.map(|bet| new_payout(bet, 0)).collect(),
+            0,
+            pool + channel.jackpot,
+        ),
+        NoWinnerPolicy::House => (
+            bets.map(|bet| new_payout(bet, 0)).collect(),
+            pool + channel.jackpot,
+            0,
+        ),
+    };
+    Distribution::new(payouts, house, 0, channel.jackpot, rollover, Some(policy))
+}
+
+fn new_payout(bet: &Bet, amount: i32) -> Payout {
+    Payout::new(
+        Id::new(bet.id.value.clone()),
+        Id::new(bet.user_id.value.clone()),
+        bet.amount,
+        amount,
+    )
+}
+
+// 締め切り前に bet を取り下げる cash out で、提示額から差し引く割合 (%)
+pub const CASHOUT_HAIRCUT_PERCENT: i32 = 10;
+
+// パリミュチュエルの bet を締め切り前に取り下げる場合の提示額
+// 現在のプールの分け方で候補が勝った場合の払い戻し (手数料を差し引いた後) に候補が勝つ確率を掛け、そこから haircut を差し引く
+// 確率は取り下げる bet を除いたプールに占める候補の割合とする。自分の bet で確率が上がらないので、提示額は賭けたポイントを超えない
+// 他のユーザーの bet が無い場合や、候補がプールに含まれていない場合は 0 を返す。端数はハウスに有利になるように切り捨てる
+pub fn cashout_value(bet: &Bet, statistics: &[Statistic], channel: &Channel) -> i32 {
+    let total = statistics.iter().map(|s| s.amount as i128).sum::<i128>();
+    let Some(statistic) = statistics
+        .iter()
+        .find(|s| s.candidate.id.value == bet.candidate_id.value && s.amount > 0)
+    else {
+        return 0;
+    };
+    let stake = bet.amount as i128;
+    let amount = statistic.amount as i128;
+    if total <= stake {
+        return 0;
+    }
+    // 払い戻し: stake × (total / amount) × (100 - 手数料) / 100、確率: (amount - stake) / (total - stake)
+    let numerator = stake
+        * total
+        * (amount - stake).max(0)
+        * (100 - channel.commission_percent) as i128
+        * (100 - CASHOUT_HAIRCUT_PERCENT) as i128;
+    (numerator / (amount * (total - stake) * 100 * 100)) as i32
+}
+
+// shares の合計がちょうど pool になることを前提に、各 share を切り捨てた上で端数を policy に従って配分する
+fn distribute(pool: i128, shares: &[Share], policy: RoundingPolicy) -> (Vec<i128>, i128) {
+    let mut amounts = shares
+        .iter()
+        .map(|s| s.numerator / s.denominator)
+        .collect::<Vec<_>>();
+    let leftover = pool - amounts.iter().sum::<i128>();
+
+    let mut order = (0..shares.len()).collect::<Vec<_>>();
+    let earliest = |a: &usize, b: &usize| {
+        (shares[*a].bet.created_at, &shares[*a].bet.id.value)
+            .cmp(&(shares[*b].bet.created_at, &shares[*b].bet.id.value))
+    };
+    match policy {
+        RoundingPolicy::House => return (amounts, leftover),
+        RoundingPolicy::EarliestBettor => {
+            let first = order.iter().copied().min_by(earliest).unwrap();
+            amounts[first] += leftover;
+        }
+        RoundingPolicy::LargestRemainder => {
+            // 切り捨てた部分 (numerator % denominator) / denominator の大きい順に並べる
+            order.sort_by(|a, b| {
+                let (sa, sb) = (&shares[*a], &shares[*b]);
+                let ra = (sa.numerator % sa.denominator) * sb.denominator;
+                let rb = (sb.numerator % sb.denominator) * sa.denominator;
+                rb.cmp(&ra).then_with(|| earliest(a, b))
+            });
+            for i in order.iter().cycle().take(leftover as usize) {
+                amounts[*i] += 1;
+            }
+        }
+    }
+    (amounts, 0)
+}
+
+// パーレーのテストでも同じ候補と bet を使う
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::model::statistic::new_statistics;
+    use chrono::TimeZone;
+    use proptest::prelude::*;
+
+    pub(crate) fn candidate(name: &str) -> Candidate {
+        Candidate {
+            id: Id::new(name.to_string()),
+            name: name.to_string(),
+            match_id: Id::new("match".to_string()),
+            is_winner: None,
+            odds: None,
+            placing: None,
+            handicap: None,
+            score: None,
+        }
+    }
+
+    pub(crate) fn bet(id: usize, candidate: &str, amount: i32) -> Bet {
+        Bet {
+            id: Id::new(format!("bet{}", id)),
+            user_id: Id::new(format!("user{}", id)),
+            match_id: Id::new("match".to_string()),
+            candidate_id: Id::new(candidate.to_string()),
+            amount,
+            created_at: chrono::Utc
+                .timestamp_opt(1_700_000_000 + id as i64, 0)
+                .unwrap(),
+            odds: None,
+            bet_type: BetType::Win,
+            following_candidate_ids: Vec::new(),
+            guess: None,
+        }
+    }
+
+    fn guess_bet(id: usize, guess: i32, amount: i32) -> Bet {
+        let mut bet = bet(id, "guess", amount);
+        bet.bet_type = BetType::Guess;
+        bet.guess = Some(guess);
+        bet
+    }
+
+    fn ordered_bet(id: usize, order: &[&str], amount: i32) -> Bet {
+        let mut bet = bet(id, order[0], amount);
+        bet.bet_type = if order.len() == 2 {
+            BetType::Exacta
+        } else {
+            BetType::Trifecta
+        };
+        bet.following_candidate_ids = order[1..].iter().map(|c| Id::new(c.to_string())).collect();
+        bet
+    }
+
+    fn channel(rounding: RoundingPolicy, no_winner: NoWinnerPolicy, jackpot: i32) -> Channel {
+        commission_channel(rounding, no_winner, jackpot, 0)
+    }
+
+    fn commission_channel(
+        rounding: RoundingPolicy,
+        no_winner: NoWinnerPolicy,
+        jackpot: i32,
+        commission_percent: i32,
+    ) -> Channel {
+        let mut channel = Channel::default_for(Id::new("channel".to_string()));
+        channel.rounding_policy = rounding;
+        channel.no_winner_policy = no_winner;
+        channel.jackpot = jackpot;
+        channel.commission_percent = commission_percent;
+        channel
+    }
+
+    fn distribution(bets: Vec<Bet>, policy: RoundingPolicy) -> Distribution {
+        let statistics = new_statistics(bets, vec![candidate("A"), candidate("B"), candidate("C")]);
+        new_distribution(
+            &statistics,
+            &[Id::new("A".to_string())],
+            MarketType::Parimutuel,
+            &channel(policy, NoWinnerPolicy::House, 0),
+        )
+    }
+
+    fn amount_of(distribution: &Distribution, bet_id: &str) -> i32 {
+        distribution
+            .payouts
+            .iter()
+            .find(|p| p.bet_id.value == bet_id)
+            .unwrap()
+            .amount
+    }
+
+    #[test]
+    fn test_pays_total_pool_by_winning_ratio() {
+        // プール 1000pt を A に bet した 300pt : 100pt で分ける
+        let d = distribution(
+            vec![bet(0, "A", 300), bet(1, "A", 100), bet(2, "B", 600)],
+            RoundingPolicy::House,
+        );
+        assert_eq!(amount_of(&d, "bet0"), 750);
+        assert_eq!(amount_of(&d, "bet1"), 250);
+        assert_eq!(amount_of(&d, "bet2"), 0);
+        assert_eq!(d.house, 0);
+    }
+
+    #[test]
+    fn test_rounding_policies() {
+        // プール 100pt を 3 人で等分すると 1pt 余る
+        let bets = || {
+            vec![
+                bet(0, "A", 10),
+                bet(1, "A", 10),
+                bet(2, "A", 10),
+                bet(3, "B", 70),
+            ]
+        };
+
+        let d = distribution(bets(), RoundingPolicy::House);
+        assert_eq!(
+            (
+                amount_of(&d, "bet0"),
+                amount_of(&d, "bet1"),
+                amount_of(&d, "bet2")
+            ),
+            (33, 33, 33)
+        );
+        assert_eq!(d.house, 1);
+
+        let d = distribution(bets(), RoundingPolicy::EarliestBettor);
+        assert_eq!(
+            (
+                amount_of(&d, "bet0"),
+                amount_of(&d, "bet1"),
+                amount_of(&d, "bet2")
+            ),
+            (34, 33, 33)
+        );
+        assert_eq!(d.house, 0);
+
+        let d = distribution(
+            vec![bet(0, "A", 10), bet(1, "A", 20), bet(2, "B", 70)],
+            RoundingPolicy::LargestRemainder,
+        );
+        // 33.33.. と 66.66.. なので端数の大きい bet1 に 1pt 配る
+        assert_eq!((amount_of(&d, "bet0"), amount_of(&d, "bet1")), (33, 67));
+        assert_eq!(d.house, 0);
+    }
+
+    #[test]
+    fn test_no_winner_policies() {
+        let statistics = new_statistics(
+            vec![bet(0, "B", 100), bet(1, "C", 50)],
+            vec![candidate("A"), candidate("B"), candidate("C")],
+        );
+        let winners = [Id::new("A".to_string())];
+        let rounding = RoundingPolicy::LargestRemainder;
+
+        let d = new_distribution(
+            &statistics,
+            &winners,
+            MarketType::Parimutuel,
+            &channel(rounding, NoWinnerPolicy::Refund, 30),
+        );
+        assert_eq!((amount_of(&d, "bet0"), amount_of(&d, "bet1")), (100, 50));
+        assert_eq!((d.house, d.rollover), (0, 30));
+        assert_eq!(d.no_winner_policy, Some(NoWinnerPolicy::Refund));
+
+        let d = new_distribution(
+            &statistics,
+            &winners,
+            MarketType::Parimutuel,
+            &channel(rounding, NoWinnerPolicy::Jackpot, 30),
+        );
+        assert!(d.payouts.iter().all(|p| p.amount == 0));
+        assert_eq!((d.house, d.rollover), (0, 180));
+
+        let d = new_distribution(
+            &statistics,
+            &winners,
+            MarketType::Parimutuel,
+            &channel(rounding, NoWinnerPolicy::House, 30),
+        );
+        assert!(d.payouts.iter().all(|p| p.amount == 0));
+        assert_eq!((d.house, d.rollover), (180, 0));
+    }
+
+    #[test]
+    fn test_jackpot_is_added_to_pool() {
+        let statistics = new_statistics(
+            vec![bet(0, "A", 100), bet(1, "B", 100)],
+            vec![candidate("A"), candidate("B")],
+        );
+        let d = new_distribution(
+            &statistics,
+            &[Id::new("A".to_string())],
+            MarketType::Parimutuel,
+            &channel(RoundingPolicy::House, NoWinnerPolicy::Jackpot, 500),
+        );
+        assert_eq!(amount_of(&d, "bet0"), 700);
+        assert_eq!((d.house, d.jackpot, d.rollover), (0, 500, 0));
+        assert_eq!(d.no_winner_policy, None);
+    }
+
+    #[test]
+    fn test_dead_heat_splits_losing_pool_between_winners() {
+        // 負けた C の 600pt を A と B で 300pt ずつ分ける
+        let statistics = new_statistics(
+            vec![
+                bet(0, "A", 100),
+                bet(1, "A", 100),
+                bet(2, "B", 200),
+                bet(3, "C", 600),
+            ],
+            vec![candidate("A"), candidate("B"), candidate("C")],
+        );
+        let d = new_distribution(
+            &statistics,
+            &[Id::new("A".to_string()), Id::new("B".to_string())],
+            MarketType::Parimutuel,
+            &channel(RoundingPolicy::House, NoWinnerPolicy::House, 0),
+        );
+        assert_eq!(amount_of(&d, "bet0"), 250);
+        assert_eq!(amount_of(&d, "bet1"), 250);
+        assert_eq!(amount_of(&d, "bet2"), 500);
+        assert_eq!(amount_of(&d, "bet3"), 0);
+        assert_eq!(d.house, 0);
+
+        // bet が無い勝者の取り分は他の勝者に回る
+        let d = new_distribution(
+            &statistics,
+            &[Id::new("A".to_string()), Id::new("D".to_string())],
+            MarketType::Parimutuel,
+            &channel(RoundingPolicy::House, NoWinnerPolicy::House, 0),
+        );
+        assert_eq!(amount_of(&d, "bet0") + amount_of(&d, "bet1"), 1000);
+    }
+
+    #[test]
+    fn test_commission_is_taken_from_pool() {
+        // プール 1000pt から 10% の 100pt を差し引き、残りの 900pt を 3:1 で分ける
+        let statistics = new_statistics(
+            vec![bet(0, "A", 300), bet(1, "A", 100), bet(2, "B", 600)],
+            vec![candidate("A"), candidate("B")],
+        );
+        let d = new_distribution(
+            &statistics,
+            &[Id::new("A".to_string())],
+            MarketType::Parimutuel,
+            &commission_channel(RoundingPolicy::House, NoWinnerPolicy::House, 200, 10),
+        );
+        // 持ち越された 200pt には手数料をかけない
+        assert_eq!(amount_of(&d, "bet0"), 825);
+        assert_eq!(amount_of(&d, "bet1"), 275);
+        assert_eq!((d.house, d.commission), (100, 100));
+    }
+
+    #[test]
+    fn test_cashout_value_takes_haircut() {
+        let bets = || vec![bet(0, "A", 1000), bet(1, "A", 555), bet(2, "B", 3000)];
+        let statistics =
+            new_statistics(bets(), vec![candidate("A"), candidate("B"), candidate("C")]);
+        let no_commission = channel(RoundingPolicy::House, NoWinnerPolicy::Refund, 0);
+        // 払い戻し 1000 × 4555 / 1555pt に、自分を除いた確率 555 / 3555 を掛けて haircut を差し引く
+        assert_eq!(cashout_value(&bets()[0], &statistics, &no_commission), 411);
+        assert_eq!(cashout_value(&bets()[1], &statistics, &no_commission), 365);
+        // 他に誰も bet していない候補の bet には何も支払わない
+        let commission = commission_channel(RoundingPolicy::House, NoWinnerPolicy::Refund, 0, 10);
+        assert_eq!(cashout_value(&bets()[2], &statistics, &commission), 0);
+        // プールに含まれていない候補への bet には何も支払わない
+        assert_eq!(
+            cashout_value(&bet(3, "C", 100), &statistics, &commission),
+            0
+        );
+    }
+
+    #[test]
+    fn test_cashout_value_follows_pool() {
+        let no_commission = channel(RoundingPolicy::House, NoWinnerPolicy::Refund, 0);
+        let value = |extra: Option<Bet>| {
+            let mut bets = vec![bet(0, "A", 1000), bet(1, "A", 555), bet(2, "B", 3000)];
+            bets.extend(extra);
+            let statistics = new_statistics(bets, vec![candidate("A"), candidate("B")]);
+            cashout_value(&bet(0, "A", 1000), &statistics, &no_commission)
+        };
+        assert_eq!(value(None), 411);
+        // 同じ候補に bet が増えると、勝つ確率が上がるので提示額も上がる
+        assert_eq!(value(Some(bet(3, "A", 1000))), 668);
+        // 他の候補に bet が増えると、払い戻しは増えるが勝つ確率が下がるので提示額は下がる
+        assert_eq!(value(Some(bet(3, "B", 1000))), 391);
+    }
+
+    #[test]
+    fn test_fixed_odds_pays_locked_odds_from_house() {
+        let mut bets = vec![bet(0, "A", 100), bet(1, "A", 55), bet(2, "B", 100)];
+        bets[0].odds = Some(180);
+        bets[1].odds = Some(210);
+        bets[2].odds = Some(210);
+        let statistics = new_statistics(bets, vec![candidate("A"), candidate("B")]);
+        let channel = channel(RoundingPolicy::House, NoWinnerPolicy::Jackpot, 40);
+
+        let d = new_distribution(
+            &statistics,
+            &[Id::new("A".to_string())],
+            MarketType::FixedOdds,
+            &channel,
+        );
+        // 55 * 2.1 = 115.5 は切り捨てる
+        assert_eq!((amount_of(&d, "bet0"), amount_of(&d, "bet1")), (180, 115));
+        assert_eq!(amount_of(&d, "bet2"), 0);
+        assert_eq!(d.house, 255 - 295);
+        assert_eq!((d.jackpot, d.rollover), (0, 40));
+
+        // デッドヒートの場合は賭けたポイントを勝者の数で割る
+        let d = new_distribution(
+            &statistics,
+            &[Id::new("A".to_string()), Id::new("B".to_string())],
+            MarketType::FixedOdds,
+            &channel,
+        );
+        assert_eq!(amount_of(&d, "bet0"), 90);
+        assert_eq!(amount_of(&d, "bet2"), 105);
+    }
+
+    #[test]
+    fn test_ordered_bets_pay_only_matching_order() {
+        let bets = vec![
+            ordered_bet(0, &["A", "B"], 100),
+            ordered_bet(1, &["A", "B"], 300),
+            ordered_bet(2, &["B", "A"], 600),
+            ordered_bet(3, &["A", "B", "C"], 100),
+            bet(4, "A", 1000),
+        ];
+        let placing = ["A", "B", "C"].map(|c| Id::new(c.to_string()));
+        let channel = channel(RoundingPolicy::House, NoWinnerPolicy::House, 0);
+
+        let d = new_ordered_distribution(&bets, BetType::Exacta, &placing, &channel);
+        assert_eq!(d.payouts.len(), 3);
+        assert_eq!((amount_of(&d, "bet0"), amount_of(&d, "bet1")), (250, 750));
+        assert_eq!(amount_of(&d, "bet2"), 0);
+
+        // 一致する bet がいない場合は返却する
+        let d = new_ordered_distribution(&bets, BetType::Exacta, &placing[1..], &channel);
+        assert_eq!((amount_of(&d, "bet0"), amount_of(&d, "bet2")), (100, 600));
+
+        // 着順が 3 着まで記録されていない場合も返却する
+        let d = new_ordered_distribution(&bets, BetType::Trifecta, &placing[..2], &channel);
+        assert_eq!(amount_of(&d, "bet3"), 100);
+        assert_eq!(d.house, 0);
+
+        // 1 着を当てる bet のプールには含めない
+        let statistics = new_statistics(bets, vec![candidate("A"), candidate("B")]);
+        assert_eq!(statistics.iter().map(|s| s.amount).sum::<i32>(), 1000);
+    }
+
+    #[test]
+    fn test_closest_guesses_split_pool() {
+        let bets = vec![
+            guess_bet(0, 2500, 100),
+            guess_bet(1, 2700, 300),
+            guess_bet(2, 2000, 600),
+            guess_bet(3, 3000, 200),
+        ];
+        let channel = channel(RoundingPolicy::House, NoWinnerPolicy::House, 200);
+
+        // 25 と 27 はどちらも結果の 26 から 1 離れているので、持ち越しを含めた 1400pt を 1:3 で分ける
+        let d = new_closest_guess_distribution(&bets, 2600, &channel);
+        assert_eq!((amount_of(&d, "bet0"), amount_of(&d, "bet1")), (350, 1050));
+        assert_eq!((amount_of(&d, "bet2"), amount_of(&d, "bet3")), (0, 0));
+        assert_eq!((d.house, d.jackpot, d.rollover), (0, 200, 0));
+
+        let d = new_closest_guess_distribution(&bets, 1000, &channel);
+        assert_eq!(amount_of(&d, "bet2"), 1400);
+
+        // 予想が無い場合は持ち越しをそのまま次に回す
+        let d = new_closest_guess_distribution(&[], 1000, &channel);
+        assert_eq!((d.house, d.rollover), (0, 200));
+    }
+
+    #[test]
+    fn test_handicap_decides_winner_and_push() {
+        let with_score = |name: &str, handicap: i32, score: i32| {
+            let mut c = candidate(name);
+            c.handicap = Some(handicap);
+            c.score = Some(score);
+            c
+        };
+
+        // 3-1 でも -3.5 のハンディキャップがあれば負けになる
+        let candidates = [with_score("A", -350, 3), with_score("B", 0, 1)];
+        assert_eq!(handicap_winner(&candidates).unwrap().name, "B");
+        let candidates = [with_score("A", -350, 5), with_score("B", 0, 1)];
+        assert_eq!(handicap_winner(&candidates).unwrap().name, "A");
+        // ハンディキャップを加えて同点なら push
+        let candidates = [with_score("A", -300, 4), with_score("B", 0, 1)];
+        assert!(handicap_winner(&candidates).is_none());
+
+        let bets = vec![bet(0, "A", 100), bet(1, "B", 300)];
+        let d = new_push_distribution(
+            &bets,
+            &channel(RoundingPolicy::House, NoWinnerPolicy::House, 50),
+        );
+        assert_eq!((amount_of(&d, "bet0"), amount_of(&d, "bet1")), (100, 300));
+        assert_eq!((d.house, d.rollover), (0, 50));
+    }
+
+    #[test]
+    fn test_lmsr_pays_one_point_per_winning_share() {
+        let position = |id: usize, candidate: &str, shares: i32, cost: i32| Position {
+            id: Id::new(format!("position{}", id)),
+            match_id: Id::new("match".to_string()),
+            user_id: Id::new(format!("user{}", id)),
+            candidate_id: Id::new(candidate.to_string()),
+            shares,
+            cost,
+            created_at: chrono::Utc::now(),
+        };
+        // 途中で全て売った position は利益が出ていれば cost が負になる
+        let positions = vec![
+            position(0, "A", 100, 60),
+            position(1, "B", 50, 30),
+            position(2, "A", 0, -5),
+        ];
+        let channel = channel(RoundingPolicy::House, NoWinnerPolicy::House, 20);
+
+        let d = new_lmsr_distribution(&positions, &[Id::new("A".to_string())], &channel);
+        assert_eq!(
+            (
+                amount_of(&d, "position0"),
+                amount_of(&d, "position1"),
+                amount_of(&d, "position2")
+            ),
+            (100, 0, 0)
+        );
+        assert_eq!((d.house, d.rollover), (85 - 100, 20));
+
+        // デッドヒートでは 1 株あたりの払い戻しを勝者の数で等分する
+        let winners = [Id::new("A".to_string()), Id::new("B".to_string())];
+        let d = new_lmsr_distribution(&positions, &winners, &channel);
+        assert_eq!(
+            (amount_of(&d, "position0"), amount_of(&d, "position1")),
+            (50, 25)
+        );
+    }
+
+    fn policy_strategy() -> impl Strategy<Value = RoundingPolicy> {
+        prop_oneof![
+            Just(RoundingPolicy::LargestRemainder),
+            Just(RoundingPolicy::House),
+            Just(RoundingPolicy::EarliestBettor),
+        ]
+    }
+
+    fn no_winner_policy_strategy() -> impl Strategy<Value = NoWinnerPolicy> {
+        prop_oneof![
+            Just(NoWinnerPolicy::Refund),
+            Just(NoWinnerPolicy::Jackpot),
+            Just(NoWinnerPolicy::House),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_total_pool_is_conserved(
+            stakes in prop::collection::vec((0..3usize, 1..100_000i32), 0..50),
+            winners in prop::sample::subsequence(vec!["A", "B", "C"], 1..=3),
+            policy in policy_strategy(),
+            no_winner_policy in no_winner_policy_strategy(),
+            jackpot in 0..100_000i32,
+            commission_percent in 0..=100i32,
+        ) {
+            let bets = stakes
+                .iter()
+                .enumerate()
+                .map(|(i, (c, amount))| bet(i, ["A", "B", "C"][*c], *amount))
+                .collect::<Vec<_>>();
+            let pool = bets.iter().map(|b| b.amount as i64).sum::<i64>();
+            let count = bets.len();
+
+            let statistics = new_statistics(bets, vec![candidate("A"), candidate("B"), candidate("C")]);
+            let winners = winners
+                .into_iter()
+                .map(|w| Id::new(w.to_string()))
+                .collect::<Vec<_>>();
+            let d = new_distribution(
+                &statistics,
+                &winners,
+                MarketType::Parimutuel,
+                &commission_channel(policy, no_winner_policy, jackpot, commission_percent),
+            );
+            let paid = d.payouts.iter().map(|p| p.amount as i64).sum::<i64>();
+            prop_assert_eq!(paid + d.house as i64 + d.rollover as i64, pool + jackpot as i64);
+            prop_assert_eq!(d.payouts.len(), count);
+            prop_assert!(d.payouts.iter().all(|p| p.amount >= 0));
+            if policy != RoundingPolicy::House && d.no_winner_policy.is_none() {
+                prop_assert_eq!(d.house, d.commission);
+            }
+        }
+
+        #[test]
+        fn prop_largest_remainder_is_within_one_point(
+            stakes in prop::collection::vec((0..3usize, 1..100_000i32), 1..50),
+        ) {
+            let bets = stakes
+                .iter()
+                .enumerate()
+                .map(|(i, (c, amount))| bet(i, ["A", "B", "C"][*c], *amount))
+                .collect::<Vec<_>>();
+            let pool = bets.iter().map(|b| b.amount as f64).sum::<f64>();
+            let winning_pool = bets
+                .iter()
+                .filter(|b| b.candidate_id.value == "A")
+                .map(|b| b.amount as f64)
+                .sum::<f64>();
+            let exact = bets
+                .iter()
+                .filter(|b| b.candidate_id.value == "A")
+                .map(|b| (b.id.value.clone(), pool * b.amount as f64 / winning_pool))
+                .collect::<Vec<_>>();
+
+            let d = distribution(bets, RoundingPolicy::LargestRemainder);
+            for (bet_id, exact) in exact {
+                let amount = amount_of(&d, &bet_id) as f64;
+                prop_assert!((amount - exact).abs() < 1.0 + 1e-6);
+            }
+        }
+    }
+}
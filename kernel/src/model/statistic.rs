@@ -1,6 +1,9 @@
 use std::collections::HashMap;
 
-use super::{bet::Bet, candidate::Candidate};
+use super::{
+    bet::{Bet, BetType},
+    candidate::Candidate,
+};
 
 #[derive(Debug)]
 pub struct Statistic {
@@ -15,8 +18,14 @@ struct DraftStatistic {
     pub bets: Vec<Bet>,
 }
 
+// 着順を当てる bet は別のプールで精算するので、1 着を当てる bet だけを集計する
 pub fn new_statistics(bets: Vec<Bet>, candidates: Vec<Candidate>) -> Vec<Statistic> {
+    let bets = bets
+        .into_iter()
+        .filter(|b| b.bet_type == BetType::Win)
+        .collect::<Vec<_>>();
     let mut statistics = Vec::new();
+    let total_amount: i32 = bets.iter().map(|b| b.amount).sum();
     let mut draft_statistics = candidates
         .into_iter()
         .map(|c| {
@@ -37,7 +46,12 @@ pub fn new_statistics(bets: Vec<Bet>, candidates: Vec<Candidate>) -> Vec<Statist
     }
     for (_, draft_statistic) in draft_statistics {
         let amount = draft_statistic.bets.iter().map(|b| b.amount).sum();
-        let rate = amount as f64 / draft_statistic.bets.len() as f64;
+        // 誰も bet していない候補の倍率は定まらないので 0 とする
+        let rate = if amount > 0 {
+            total_amount as f64 / amount as f64
+        } else {
+            0.0
+        };
         statistics.push(Statistic {
             candidate: draft_statistic.candidate,
             amount,
@@ -45,6 +59,6 @@ pub fn new_statistics(bets: Vec<Bet>, candidates: Vec<Candidate>) -> Vec<Statist
             bets: draft_statistic.bets,
         });
     }
-    statistics.sort_by(|a, b| b.amount.cmp(&a.amount));
+    statistics.sort_by_key(|s| std::cmp::Reverse(s.amount));
     statistics
 }
@@ -9,6 +9,8 @@ pub struct User {
     pub traq_display_id: String,
     pub channel_id: String,
     pub balance: i32,
+    // false の場合は締め切りのリマインドでメンションしない
+    pub notify: bool,
 }
 
 #[derive(new, Debug)]
@@ -26,6 +28,13 @@ pub struct FindUser {
     pub channel_id: Id<Channel>,
 }
 
+#[derive(new, Debug)]
+pub struct UpdateNotify {
+    pub traq_id: String,
+    pub channel_id: Id<Channel>,
+    pub notify: bool,
+}
+
 #[derive(new, Debug)]
 pub struct UpdateBalance {
     pub user_id: Id<User>,
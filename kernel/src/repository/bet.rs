@@ -1,5 +1,8 @@
 use crate::model::{
-    bet::{Bet, NewBetForLatestMatch},
+    bet::{
+        Bet, CashOut, CashOutBetForLatestMatch, DeleteBetForLatestMatch, NewBetForLatestMatch,
+        NewGuessBetForLatestMatch, NewOrderedBetForLatestMatch, UpdateBetForLatestMatch,
+    },
     r#match::Match,
     Id,
 };
@@ -10,7 +13,27 @@ pub trait BetRepository {
     fn insert_for_latest_match(
         &self,
         m: NewBetForLatestMatch,
+    ) -> impl std::future::Future<Output = Result<Vec<Bet>, RepositoryError>> + Send;
+    fn insert_ordered_for_latest_match(
+        &self,
+        m: NewOrderedBetForLatestMatch,
     ) -> impl std::future::Future<Output = Result<Bet, RepositoryError>> + Send;
+    fn insert_guess_for_latest_match(
+        &self,
+        m: NewGuessBetForLatestMatch,
+    ) -> impl std::future::Future<Output = Result<Bet, RepositoryError>> + Send;
+    fn update_for_latest_match(
+        &self,
+        m: UpdateBetForLatestMatch,
+    ) -> impl std::future::Future<Output = Result<Bet, RepositoryError>> + Send;
+    fn delete_for_latest_match(
+        &self,
+        m: DeleteBetForLatestMatch,
+    ) -> impl std::future::Future<Output = Result<Vec<Bet>, RepositoryError>> + Send;
+    fn cash_out_for_latest_match(
+        &self,
+        m: CashOutBetForLatestMatch,
+    ) -> impl std::future::Future<Output = Result<CashOut, RepositoryError>> + Send;
     fn select_by_match_id(
         &self,
         match_id: Id<Match>,
@@ -0,0 +1,29 @@
+use crate::model::challenge::{
+    AcceptChallengeForLatest, Challenge, DeclineChallengeForLatest, NewChallenge,
+    ResolveChallengeForLatest,
+};
+
+use super::error::RepositoryError;
+
+pub trait ChallengeRepository {
+    // 挑んだ側のポイントを預かって勝負を作成する
+    fn insert(
+        &self,
+        challenge: NewChallenge,
+    ) -> impl std::future::Future<Output = Result<Challenge, RepositoryError>> + Send;
+    // 受けた側のポイントを預かって勝負を成立させる
+    fn accept_for_latest(
+        &self,
+        challenge: AcceptChallengeForLatest,
+    ) -> impl std::future::Future<Output = Result<Challenge, RepositoryError>> + Send;
+    // 預かっている挑んだ側のポイントを返却する
+    fn decline_for_latest(
+        &self,
+        challenge: DeclineChallengeForLatest,
+    ) -> impl std::future::Future<Output = Result<Challenge, RepositoryError>> + Send;
+    // 預かっている両者のポイントを勝者に払い戻す
+    fn resolve_for_latest(
+        &self,
+        challenge: ResolveChallengeForLatest,
+    ) -> impl std::future::Future<Output = Result<Challenge, RepositoryError>> + Send;
+}
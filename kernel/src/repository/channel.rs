@@ -0,0 +1,18 @@
+use crate::model::{
+    channel::{Channel, UpdateChannel},
+    Id,
+};
+
+use super::error::RepositoryError;
+
+pub trait ChannelRepository {
+    // 設定が保存されていない場合はデフォルトの設定を返す
+    fn find(
+        &self,
+        channel_id: Id<Channel>,
+    ) -> impl std::future::Future<Output = Result<Channel, RepositoryError>> + Send;
+    fn upsert(
+        &self,
+        channel: UpdateChannel,
+    ) -> impl std::future::Future<Output = Result<Channel, RepositoryError>> + Send;
+}
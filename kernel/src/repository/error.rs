@@ -1,13 +1,26 @@
 use thiserror::Error;
 
+use crate::model::r#match::TransitionError;
+
 #[derive(Error, Debug)]
 pub enum RepositoryError {
     #[error("Record not found: {0}")]
     RecordNotFound(String),
     #[error("Duplicated record: {0}")]
     DuplicatedRecord(String),
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
     #[error("Insufficient balance")]
     InsufficientBalance,
+    #[error("Insufficient shares")]
+    InsufficientShares,
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    // channel で複数の match が進行中で、対象の match の番号が指定されなかった
+    #[error("Match number is required")]
+    AmbiguousMatch,
+    #[error("Invalid transition: {0}")]
+    InvalidTransition(#[from] TransitionError),
     #[error("Unexpected error: {0}")]
     UnexpectedError(#[from] anyhow::Error),
 }
@@ -0,0 +1,38 @@
+use crate::model::{
+    job::{Job, JobLease, NewJob},
+    DateTimeUtc,
+};
+
+use super::error::RepositoryError;
+
+pub trait JobRepository {
+    fn insert(
+        &self,
+        j: NewJob,
+    ) -> impl std::future::Future<Output = Result<Job, RepositoryError>> + Send;
+    // 同じ種類のジョブが待機中か実行中であれば登録せずにそのジョブを返す。繰り返すジョブの登録に使う
+    fn insert_unless_scheduled(
+        &self,
+        j: NewJob,
+    ) -> impl std::future::Future<Output = Result<Job, RepositoryError>> + Send;
+    // 実行時刻を過ぎたジョブを limit 件まで取得し、実行中にする
+    // 実行中のまま期限を過ぎたジョブも、worker が落ちたものとして再び取得する
+    // ただし実行した回数が上限に達している場合は取得せず、失敗したものとして扱う
+    fn claim_due(
+        &self,
+        now: DateTimeUtc,
+        limit: u64,
+    ) -> impl std::future::Future<Output = Result<Vec<Job>, RepositoryError>> + Send;
+    // 結果の記録は lease を持っている間だけ行う。取得し直されていた場合は PermissionDenied を返す
+    fn complete(
+        &self,
+        lease: JobLease,
+        now: DateTimeUtc,
+    ) -> impl std::future::Future<Output = Result<Job, RepositoryError>> + Send;
+    fn fail(
+        &self,
+        lease: JobLease,
+        error: String,
+        now: DateTimeUtc,
+    ) -> impl std::future::Future<Output = Result<Job, RepositoryError>> + Send;
+}
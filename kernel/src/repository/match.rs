@@ -1,7 +1,11 @@
 use crate::model::{
     channel::Channel,
-    r#match::{Match, NewMatch, UpdateMatch, UpdateMatchForLatest},
-    Id,
+    r#match::{
+        CancelMatchForLatest, Match, NewMatch, Participation, RefundedMatch, UpdateMatch,
+        UpdateMatchForLatest, VoidMatchForLatest,
+    },
+    settlement::{SettleMatchForLatest, SettledMatch},
+    DateTimeUtc, Id,
 };
 
 use super::error::RepositoryError;
@@ -15,10 +19,11 @@ pub trait MatchRepository {
         &self,
         m: UpdateMatch,
     ) -> impl std::future::Future<Output = Result<Match, RepositoryError>> + Send;
+    // 締め切る場合は参加の条件を同じトランザクションで確かめ、満たしていなければ無効にする
     fn update_for_latest(
         &self,
         m: UpdateMatchForLatest,
-    ) -> impl std::future::Future<Output = Result<Match, RepositoryError>> + Send;
+    ) -> impl std::future::Future<Output = Result<Participation<Match>, RepositoryError>> + Send;
     fn find(
         &self,
         match_id: Id<Match>,
@@ -26,9 +31,31 @@ pub trait MatchRepository {
     fn find_latest(
         &self,
         channel_id: Id<Channel>,
+        match_number: Option<i32>,
     ) -> impl std::future::Future<Output = Result<Option<Match>, RepositoryError>> + Send;
-    fn delete_latest(
+    // 参加の条件を同じトランザクションで確かめ、満たしていなければ精算せずに無効にする
+    fn settle_latest(
         &self,
-        channel_id: Id<Channel>,
-    ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+        m: SettleMatchForLatest,
+    ) -> impl std::future::Future<Output = Result<Participation<SettledMatch>, RepositoryError>> + Send;
+    fn cancel_latest(
+        &self,
+        m: CancelMatchForLatest,
+    ) -> impl std::future::Future<Output = Result<RefundedMatch, RepositoryError>> + Send;
+    fn void_latest(
+        &self,
+        m: VoidMatchForLatest,
+    ) -> impl std::future::Future<Output = Result<RefundedMatch, RepositoryError>> + Send;
+    // チャンネルの設定した時間が経っても終了していない match を全て返す
+    fn find_expired(
+        &self,
+        now: DateTimeUtc,
+    ) -> impl std::future::Future<Output = Result<Vec<Match>, RepositoryError>> + Send;
+    // 期限の過ぎた match を無効にし、賭けられたポイントを返却する
+    // 既に終了していたり期限が延びていたりして無効にしなかった場合は None を返す
+    fn void_expired(
+        &self,
+        match_id: Id<Match>,
+        now: DateTimeUtc,
+    ) -> impl std::future::Future<Output = Result<Option<RefundedMatch>, RepositoryError>> + Send;
 }
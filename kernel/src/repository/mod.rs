@@ -1,5 +1,10 @@
 pub mod bet;
 pub mod candidate;
+pub mod challenge;
+pub mod channel;
 pub mod error;
+pub mod job;
 pub mod r#match;
+pub mod parlay;
+pub mod position;
 pub mod user;
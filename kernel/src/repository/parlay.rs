@@ -0,0 +1,11 @@
+use crate::model::parlay::{NewParlay, ParlaySlip};
+
+use super::error::RepositoryError;
+
+pub trait ParlayRepository {
+    // 賭けるポイントを差し引いてパーレーを作成する
+    fn insert(
+        &self,
+        parlay: NewParlay,
+    ) -> impl std::future::Future<Output = Result<ParlaySlip, RepositoryError>> + Send;
+}
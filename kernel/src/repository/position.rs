@@ -0,0 +1,18 @@
+use crate::model::{
+    position::{Position, Trade, TradeForLatestMatch},
+    r#match::Match,
+    Id,
+};
+
+use super::error::RepositoryError;
+
+pub trait PositionRepository {
+    fn trade_for_latest_match(
+        &self,
+        m: TradeForLatestMatch,
+    ) -> impl std::future::Future<Output = Result<Trade, RepositoryError>> + Send;
+    fn select_by_match_id(
+        &self,
+        match_id: Id<Match>,
+    ) -> impl std::future::Future<Output = Result<Vec<Position>, RepositoryError>> + Send;
+}
@@ -1,5 +1,5 @@
 use crate::model::{
-    user::{FindUser, NewUser, UpdateBalance, User},
+    user::{FindUser, NewUser, UpdateBalance, UpdateNotify, User},
     Id,
 };
 
@@ -22,4 +22,8 @@ pub trait UserRepository {
         &self,
         users: Vec<UpdateBalance>,
     ) -> impl std::future::Future<Output = Result<(), RepositoryError>> + Send;
+    fn update_notify(
+        &self,
+        user: UpdateNotify,
+    ) -> impl std::future::Future<Output = Result<User, RepositoryError>> + Send;
 }
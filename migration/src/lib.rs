@@ -1,12 +1,58 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20240412_101522_initial_create_table;
+mod m20240420_091244_add_match_cancelled_at;
+mod m20240423_203015_add_match_status;
+mod m20240427_142310_create_settlement_table;
+mod m20240501_110842_create_channel_table;
+mod m20240506_183407_add_match_voided_at;
+mod m20240510_212655_add_channel_no_winner_policy;
+mod m20240514_094518_allow_multiple_bets_per_user;
+mod m20240518_160233_add_fixed_odds;
+mod m20240521_203114_add_channel_commission;
+mod m20240525_141907_add_ordered_bets;
+mod m20240529_190540_add_match_over_under;
+mod m20240602_113025_add_bet_guess;
+mod m20240606_205318_add_candidate_handicap;
+mod m20240610_213847_create_challenge_table;
+mod m20240614_192205_create_position_table;
+mod m20240618_204512_create_parlay_table;
+mod m20240622_183940_add_match_number;
+mod m20240626_201117_add_match_close_at;
+mod m20240630_094412_create_job_table;
+mod m20240703_212045_add_user_notify;
+mod m20240707_163528_add_channel_match_ttl_hours;
+mod m20240711_195204_add_match_participation_threshold;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20240412_101522_initial_create_table::Migration)]
+        vec![
+            Box::new(m20240412_101522_initial_create_table::Migration),
+            Box::new(m20240420_091244_add_match_cancelled_at::Migration),
+            Box::new(m20240423_203015_add_match_status::Migration),
+            Box::new(m20240427_142310_create_settlement_table::Migration),
+            Box::new(m20240501_110842_create_channel_table::Migration),
+            Box::new(m20240506_183407_add_match_voided_at::Migration),
+            Box::new(m20240510_212655_add_channel_no_winner_policy::Migration),
+            Box::new(m20240514_094518_allow_multiple_bets_per_user::Migration),
+            Box::new(m20240518_160233_add_fixed_odds::Migration),
+            Box::new(m20240521_203114_add_channel_commission::Migration),
+            Box::new(m20240525_141907_add_ordered_bets::Migration),
+            Box::new(m20240529_190540_add_match_over_under::Migration),
+            Box::new(m20240602_113025_add_bet_guess::Migration),
+            Box::new(m20240606_205318_add_candidate_handicap::Migration),
+            Box::new(m20240610_213847_create_challenge_table::Migration),
+            Box::new(m20240614_192205_create_position_table::Migration),
+            Box::new(m20240618_204512_create_parlay_table::Migration),
+            Box::new(m20240622_183940_add_match_number::Migration),
+            Box::new(m20240626_201117_add_match_close_at::Migration),
+            Box::new(m20240630_094412_create_job_table::Migration),
+            Box::new(m20240703_212045_add_user_notify::Migration),
+            Box::new(m20240707_163528_add_channel_match_ttl_hours::Migration),
+            Box::new(m20240711_195204_add_match_participation_threshold::Migration),
+        ]
     }
 }
@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .add_column(
+                        ColumnDef::new(Match::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("Open"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 既存の match の状態を closed_at, winner_candidate_id, cancelled_at から埋める
+        let db = manager.get_connection();
+        db.execute_unprepared("UPDATE `match` SET status = 'Closed' WHERE closed_at IS NOT NULL")
+            .await?;
+        db.execute_unprepared(
+            "UPDATE `match` SET status = 'Settled' WHERE winner_candidate_id IS NOT NULL",
+        )
+        .await?;
+        db.execute_unprepared(
+            "UPDATE `match` SET status = 'Cancelled' WHERE cancelled_at IS NOT NULL",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .drop_column(Match::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Match {
+    Table,
+    Status,
+}
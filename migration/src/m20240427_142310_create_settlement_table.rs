@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Settlement::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Settlement::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Settlement::MatchId).string().not_null())
+                    .col(
+                        ColumnDef::new(Settlement::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_settlement_match_id")
+                    .from_tbl(Settlement::Table)
+                    .to_tbl(Match::Table)
+                    .from_col(Settlement::MatchId)
+                    .to_col(Match::Id)
+                    .to_owned(),
+            )
+            .await?;
+        // 1つの match が二重に精算されないように match_id にユニーク制約を追加
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE settlement ADD CONSTRAINT unique_settlement_match_id UNIQUE (match_id)",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Settlement::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Match {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Settlement {
+    Table,
+    Id,
+    MatchId,
+    CreatedAt,
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Channel::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Channel::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Channel::RoundingPolicy)
+                            .string_len(32)
+                            .not_null()
+                            .default("LargestRemainder"),
+                    )
+                    .col(
+                        ColumnDef::new(Channel::HouseBalance)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 端数を最も早く bet したユーザーに配分するために bet の作成日時を記録する
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bet::Table)
+                    .add_column(
+                        ColumnDef::new(Bet::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bet::Table)
+                    .drop_column(Bet::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Channel::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Channel {
+    Table,
+    Id,
+    RoundingPolicy,
+    HouseBalance,
+}
+
+#[derive(DeriveIden)]
+enum Bet {
+    Table,
+    CreatedAt,
+}
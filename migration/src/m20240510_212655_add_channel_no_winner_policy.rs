@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Channel::Table)
+                    .add_column(
+                        ColumnDef::new(Channel::NoWinnerPolicy)
+                            .string_len(16)
+                            .not_null()
+                            .default("Refund"),
+                    )
+                    .add_column(
+                        ColumnDef::new(Channel::Jackpot)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Channel::Table)
+                    .drop_column(Channel::NoWinnerPolicy)
+                    .drop_column(Channel::Jackpot)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Channel {
+    Table,
+    NoWinnerPolicy,
+    Jackpot,
+}
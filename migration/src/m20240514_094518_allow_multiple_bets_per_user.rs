@@ -0,0 +1,31 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1 つの match で複数の候補に bet できるように、ユニーク制約に candidate_id を含める
+        // 外部キーのためのインデックスが無くならないように、新しい制約を先に追加する
+        manager.get_connection().execute_unprepared("ALTER TABLE bet ADD CONSTRAINT unique_bet_match_id_user_id_candidate_id UNIQUE (match_id, user_id, candidate_id)").await?;
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE bet DROP INDEX unique_bet_match_id_user_id")
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.get_connection().execute_unprepared("ALTER TABLE bet ADD CONSTRAINT unique_bet_match_id_user_id UNIQUE (match_id, user_id)").await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE bet DROP INDEX unique_bet_match_id_user_id_candidate_id",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .add_column(
+                        ColumnDef::new(Match::MarketType)
+                            .string_len(16)
+                            .not_null()
+                            .default("Parimutuel"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 倍率は 100 倍した整数で保存する
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Candidate::Table)
+                    .add_column(ColumnDef::new(Candidate::Odds).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bet::Table)
+                    .add_column(ColumnDef::new(Bet::Odds).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bet::Table)
+                    .drop_column(Bet::Odds)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Candidate::Table)
+                    .drop_column(Candidate::Odds)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .drop_column(Match::MarketType)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Match {
+    Table,
+    MarketType,
+}
+
+#[derive(DeriveIden)]
+enum Candidate {
+    Table,
+    Odds,
+}
+
+#[derive(DeriveIden)]
+enum Bet {
+    Table,
+    Odds,
+}
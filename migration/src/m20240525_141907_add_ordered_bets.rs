@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 着順を当てる bet は 1 着の候補を candidate_id に、2 着以降の候補の id をカンマ区切りで保存する
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bet::Table)
+                    .add_column(
+                        ColumnDef::new(Bet::Type)
+                            .string_len(16)
+                            .not_null()
+                            .default("Win"),
+                    )
+                    .add_column(
+                        ColumnDef::new(Bet::FollowingCandidateIds)
+                            .string_len(80)
+                            .not_null()
+                            .default(""),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Candidate::Table)
+                    .add_column(ColumnDef::new(Candidate::Placing).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 同じ候補への 1 着の bet と着順の bet を両立できるように、ユニーク制約に着順を含める
+        // インデックスの長さの上限に収まるように、id は先頭の 64 文字だけを使う
+        manager.get_connection().execute_unprepared("ALTER TABLE bet ADD CONSTRAINT unique_bet_match_id_user_id_order UNIQUE (match_id(64), user_id(64), candidate_id(64), bet_type, following_candidate_ids)").await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE bet DROP INDEX unique_bet_match_id_user_id_candidate_id",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.get_connection().execute_unprepared("ALTER TABLE bet ADD CONSTRAINT unique_bet_match_id_user_id_candidate_id UNIQUE (match_id, user_id, candidate_id)").await?;
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE bet DROP INDEX unique_bet_match_id_user_id_order")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Candidate::Table)
+                    .drop_column(Candidate::Placing)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Bet::Table)
+                    .drop_column(Bet::Type)
+                    .drop_column(Bet::FollowingCandidateIds)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Bet {
+    Table,
+    #[sea_orm(iden = "bet_type")]
+    Type,
+    FollowingCandidateIds,
+}
+
+#[derive(DeriveIden)]
+enum Candidate {
+    Table,
+    Placing,
+}
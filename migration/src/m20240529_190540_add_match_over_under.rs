@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 基準値と結果の値は 100 倍した整数で保存する
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .add_column(ColumnDef::new(Match::OverUnderLine).integer().null())
+                    .add_column(ColumnDef::new(Match::ResultValue).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .drop_column(Match::OverUnderLine)
+                    .drop_column(Match::ResultValue)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Match {
+    Table,
+    OverUnderLine,
+    ResultValue,
+}
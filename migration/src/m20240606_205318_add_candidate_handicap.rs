@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ハンディキャップは 100 倍した整数で、得点はそのまま保存する
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Candidate::Table)
+                    .add_column(ColumnDef::new(Candidate::Handicap).integer().null())
+                    .add_column(ColumnDef::new(Candidate::Score).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Candidate::Table)
+                    .drop_column(Candidate::Handicap)
+                    .drop_column(Candidate::Score)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Candidate {
+    Table,
+    Handicap,
+    Score,
+}
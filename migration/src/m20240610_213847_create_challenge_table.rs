@@ -0,0 +1,102 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Challenge::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Challenge::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Challenge::ChannelId).string().not_null())
+                    .col(ColumnDef::new(Challenge::Title).string().not_null())
+                    .col(ColumnDef::new(Challenge::Amount).integer().not_null())
+                    .col(ColumnDef::new(Challenge::ChallengerId).string().not_null())
+                    .col(ColumnDef::new(Challenge::OpponentId).string().not_null())
+                    .col(ColumnDef::new(Challenge::ArbiterId).string().null())
+                    .col(ColumnDef::new(Challenge::WinnerId).string().null())
+                    .col(
+                        ColumnDef::new(Challenge::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("Pending"),
+                    )
+                    .col(
+                        ColumnDef::new(Challenge::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Challenge::AcceptedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(Challenge::FinishedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        for (name, column) in [
+            ("fk_challenge_challenger_id", Challenge::ChallengerId),
+            ("fk_challenge_opponent_id", Challenge::OpponentId),
+            ("fk_challenge_arbiter_id", Challenge::ArbiterId),
+            ("fk_challenge_winner_id", Challenge::WinnerId),
+        ] {
+            manager
+                .create_foreign_key(
+                    ForeignKey::create()
+                        .name(name)
+                        .from_tbl(Challenge::Table)
+                        .to_tbl(User::Table)
+                        .from_col(column)
+                        .to_col(User::Id)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Challenge::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Challenge {
+    Table,
+    Id,
+    ChannelId,
+    Title,
+    Amount,
+    ChallengerId,
+    OpponentId,
+    ArbiterId,
+    WinnerId,
+    Status,
+    CreatedAt,
+    AcceptedAt,
+    FinishedAt,
+}
@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .add_column(ColumnDef::new(Match::Liquidity).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Position::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Position::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Position::MatchId).string().not_null())
+                    .col(ColumnDef::new(Position::UserId).string().not_null())
+                    .col(ColumnDef::new(Position::CandidateId).string().not_null())
+                    .col(
+                        ColumnDef::new(Position::Shares)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Position::Cost)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(Position::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_position_match_id")
+                    .from_tbl(Position::Table)
+                    .to_tbl(Match::Table)
+                    .from_col(Position::MatchId)
+                    .to_col(Match::Id)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_position_user_id")
+                    .from_tbl(Position::Table)
+                    .to_tbl(User::Table)
+                    .from_col(Position::UserId)
+                    .to_col(User::Id)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_position_candidate_id")
+                    .from_tbl(Position::Table)
+                    .to_tbl(Candidate::Table)
+                    .from_col(Position::CandidateId)
+                    .to_col(Candidate::Id)
+                    .to_owned(),
+            )
+            .await?;
+        // 1 人のユーザーは候補ごとに 1 つの position だけを持ち、売買のたびに株数を更新する
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE position ADD CONSTRAINT unique_position_match_id_user_id_candidate_id UNIQUE (match_id, user_id, candidate_id)",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Position::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .drop_column(Match::Liquidity)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Match {
+    Table,
+    Id,
+    Liquidity,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Candidate {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Position {
+    Table,
+    Id,
+    MatchId,
+    UserId,
+    CandidateId,
+    Shares,
+    Cost,
+    CreatedAt,
+}
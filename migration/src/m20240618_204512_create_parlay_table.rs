@@ -0,0 +1,169 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Parlay::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Parlay::Id).string().not_null().primary_key())
+                    .col(ColumnDef::new(Parlay::ChannelId).string().not_null())
+                    .col(ColumnDef::new(Parlay::UserId).string().not_null())
+                    .col(ColumnDef::new(Parlay::Amount).integer().not_null())
+                    .col(
+                        ColumnDef::new(Parlay::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("Open"),
+                    )
+                    .col(ColumnDef::new(Parlay::Payout).integer().null())
+                    .col(
+                        ColumnDef::new(Parlay::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Parlay::SettledAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_parlay_user_id")
+                    .from_tbl(Parlay::Table)
+                    .to_tbl(User::Table)
+                    .from_col(Parlay::UserId)
+                    .to_col(User::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ParlayLeg::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ParlayLeg::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ParlayLeg::ParlayId).string().not_null())
+                    .col(ColumnDef::new(ParlayLeg::MatchId).string().not_null())
+                    .col(ColumnDef::new(ParlayLeg::CandidateId).string().not_null())
+                    .col(
+                        ColumnDef::new(ParlayLeg::Result)
+                            .string_len(16)
+                            .not_null()
+                            .default("Pending"),
+                    )
+                    .col(ColumnDef::new(ParlayLeg::Odds).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_parlay_leg_parlay_id")
+                    .from_tbl(ParlayLeg::Table)
+                    .to_tbl(Parlay::Table)
+                    .from_col(ParlayLeg::ParlayId)
+                    .to_col(Parlay::Id)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_parlay_leg_match_id")
+                    .from_tbl(ParlayLeg::Table)
+                    .to_tbl(Match::Table)
+                    .from_col(ParlayLeg::MatchId)
+                    .to_col(Match::Id)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_parlay_leg_candidate_id")
+                    .from_tbl(ParlayLeg::Table)
+                    .to_tbl(Candidate::Table)
+                    .from_col(ParlayLeg::CandidateId)
+                    .to_col(Candidate::Id)
+                    .to_owned(),
+            )
+            .await?;
+        // 1 つのパーレーに同じ match を複数含めることはできない
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE parlay_leg ADD CONSTRAINT unique_parlay_leg_parlay_id_match_id UNIQUE (parlay_id, match_id)",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ParlayLeg::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Parlay::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Match {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Candidate {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Parlay {
+    Table,
+    Id,
+    ChannelId,
+    UserId,
+    Amount,
+    Status,
+    Payout,
+    CreatedAt,
+    SettledAt,
+}
+
+#[derive(DeriveIden)]
+enum ParlayLeg {
+    Table,
+    Id,
+    ParlayId,
+    MatchId,
+    CandidateId,
+    Result,
+    Odds,
+}
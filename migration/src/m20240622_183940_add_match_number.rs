@@ -0,0 +1,57 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .add_column(
+                        ColumnDef::new(Match::Number)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 既存の match に channel ごとの作成順で番号を振る
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "UPDATE `match` m JOIN (SELECT id, ROW_NUMBER() OVER (PARTITION BY channel_id ORDER BY created_at) AS n FROM `match`) t ON m.id = t.id SET m.number = t.n",
+        )
+        .await?;
+        db.execute_unprepared(
+            "ALTER TABLE `match` ADD CONSTRAINT unique_match_channel_id_number UNIQUE (channel_id, number)",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE `match` DROP INDEX unique_match_channel_id_number")
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Match::Table)
+                    .drop_column(Match::Number)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Match {
+    Table,
+    Number,
+}
@@ -0,0 +1,93 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Job::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Job::Id).string().not_null().primary_key())
+                    .col(ColumnDef::new(Job::Kind).string_len(32).not_null())
+                    .col(ColumnDef::new(Job::Payload).text().not_null())
+                    .col(
+                        ColumnDef::new(Job::RunAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Job::IntervalSecs).integer().null())
+                    .col(
+                        ColumnDef::new(Job::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("Pending"),
+                    )
+                    .col(
+                        ColumnDef::new(Job::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Job::MaxAttempts).integer().not_null())
+                    .col(
+                        ColumnDef::new(Job::LockedUntil)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(ColumnDef::new(Job::LastError).text().null())
+                    .col(
+                        ColumnDef::new(Job::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_job_status_run_at")
+                    .table(Job::Table)
+                    .col(Job::Status)
+                    .col(Job::RunAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 締め切り時刻が設定された受付中の match を、ジョブで締め切るようにする
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO job (id, kind, payload, run_at, status, attempts, max_attempts, created_at) SELECT UUID(), 'CloseMatch', id, close_at, 'Pending', 0, 5, NOW() FROM `match` WHERE status = 'Open' AND close_at IS NOT NULL",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Job::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Job {
+    Table,
+    Id,
+    Kind,
+    Payload,
+    RunAt,
+    IntervalSecs,
+    Status,
+    Attempts,
+    MaxAttempts,
+    LockedUntil,
+    LastError,
+    CreatedAt,
+}